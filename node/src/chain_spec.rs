@@ -1,8 +1,10 @@
 use sp_core::{Pair, Public, sr25519};
+use std::path::Path;
 use node_template_runtime::{
 	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig,
-	SudoConfig, SystemConfig, WASM_BINARY, Signature
+	SudoConfig, SystemConfig, TendermintClientModuleConfig, WASM_BINARY, Signature
 };
+use node_template_runtime::tendermint_client::types::{ExportedClient, TMCreateClientPayload};
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::traits::{Verify, IdentifyAccount};
@@ -38,7 +40,24 @@ pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
 	)
 }
 
-pub fn development_config() -> Result<ChainSpec, String> {
+/// Tendermint clients to create at genesis.
+#[derive(Clone, Default)]
+pub struct GenesisClients {
+	/// `init_client` payloads of the clients to create.
+	pub created: Vec<TMCreateClientPayload>,
+	/// Clients exported from another chain by `export-tm-clients`.
+	pub imported: Vec<ExportedClient>,
+}
+
+/// Read a JSON file of genesis clients: a list of `init_client` payloads, or the output of `export-tm-clients`.
+pub fn genesis_clients_from_file<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Vec<T>, String> {
+	let file = std::fs::File::open(path)
+		.map_err(|e| format!("Error opening genesis clients file {}: {}", path.display(), e))?;
+	serde_json::from_reader(std::io::BufReader::new(file))
+		.map_err(|e| format!("Error parsing genesis clients file {}: {}", path.display(), e))
+}
+
+pub fn development_config(genesis_clients: GenesisClients) -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or("Development wasm binary not available".to_string())?;
 
 	Ok(ChainSpec::from_genesis(
@@ -66,6 +85,8 @@ pub fn development_config() -> Result<ChainSpec, String> {
 				get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 				get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 			],
+			// Tendermint clients
+			genesis_clients.clone(),
 			true,
 		),
 		// Bootnodes
@@ -81,7 +102,7 @@ pub fn development_config() -> Result<ChainSpec, String> {
 	))
 }
 
-pub fn local_testnet_config() -> Result<ChainSpec, String> {
+pub fn local_testnet_config(genesis_clients: GenesisClients) -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or("Development wasm binary not available".to_string())?;
 
 	Ok(ChainSpec::from_genesis(
@@ -118,6 +139,8 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 				get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 				get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 			],
+			// Tendermint clients
+			genesis_clients.clone(),
 			true,
 		),
 		// Bootnodes
@@ -139,6 +162,7 @@ fn testnet_genesis(
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
+	genesis_clients: GenesisClients,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -161,5 +185,9 @@ fn testnet_genesis(
 			// Assign network admin rights.
 			key: root_key,
 		}),
+		tendermint_client: Some(TendermintClientModuleConfig {
+			clients: genesis_clients.created,
+			imported_clients: genesis_clients.imported,
+		}),
 	}
 }
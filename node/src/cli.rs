@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use structopt::StructOpt;
 use sc_cli::RunCmd;
 
@@ -8,6 +9,16 @@ pub struct Cli {
 
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// JSON file listing `init_client` payloads of the Tendermint clients to create at genesis of the
+	/// `dev` and `local` chains.
+	#[structopt(long, global = true, parse(from_os_str))]
+	pub genesis_clients: Option<PathBuf>,
+
+	/// JSON file of Tendermint clients exported by `export-tm-clients`, to import at genesis of the `dev` and `local`
+	/// chains.
+	#[structopt(long, global = true, parse(from_os_str))]
+	pub import_tm_clients: Option<PathBuf>,
 }
 
 #[derive(Debug, StructOpt)]
@@ -36,4 +47,12 @@ pub enum Subcommand {
 	/// The custom benchmark subcommmand benchmarking runtime pallets.
 	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+
+	/// Submit and inspect Tendermint clients of a running node.
+	#[structopt(name = "tm-client")]
+	TmClient(crate::tm_client::TmClientCmd),
+
+	/// Export the Tendermint clients at a block into a JSON file, to import at genesis of another chain.
+	#[structopt(name = "export-tm-clients")]
+	ExportTmClients(crate::tm_client::ExportTmClientsCmd),
 }
@@ -48,8 +48,8 @@ impl SubstrateCli for Cli {
 
 	fn load_spec(&self, id: &str) -> Result<Box<dyn sc_service::ChainSpec>, String> {
 		Ok(match id {
-			"dev" => Box::new(chain_spec::development_config()?),
-			"" | "local" => Box::new(chain_spec::local_testnet_config()?),
+			"dev" => Box::new(chain_spec::development_config(self.genesis_clients()?)?),
+			"" | "local" => Box::new(chain_spec::local_testnet_config(self.genesis_clients()?)?),
 			path => Box::new(chain_spec::ChainSpec::from_json_file(
 				std::path::PathBuf::from(path),
 			)?),
@@ -61,6 +61,20 @@ impl SubstrateCli for Cli {
 	}
 }
 
+impl Cli {
+	/// Tendermint clients to create at genesis, as given by `--genesis-clients` and `--import-tm-clients`.
+	fn genesis_clients(&self) -> Result<chain_spec::GenesisClients, String> {
+		let mut clients = chain_spec::GenesisClients::default();
+		if let Some(path) = &self.genesis_clients {
+			clients.created = chain_spec::genesis_clients_from_file(path)?;
+		}
+		if let Some(path) = &self.import_tm_clients {
+			clients.imported = chain_spec::genesis_clients_from_file(path)?;
+		}
+		Ok(clients)
+	}
+}
+
 /// Parse and run command line arguments
 pub fn run() -> sc_cli::Result<()> {
 	let cli = Cli::from_args();
@@ -124,6 +138,14 @@ pub fn run() -> sc_cli::Result<()> {
 				You can enable it with `--features runtime-benchmarks`.".into())
 			}
 		},
+		Some(Subcommand::TmClient(cmd)) => cmd.run(),
+		Some(Subcommand::ExportTmClients(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, .. } = service::new_partial(&config)?;
+				cmd.run(client)
+			})
+		},
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
 			runner.run_node_until_exit(|config| match config.role {
@@ -0,0 +1,446 @@
+//! `tm-client` subcommand: submits and inspects Tendermint clients of a running node over its WebSocket RPC.
+//! `export-tm-clients` subcommand: exports the Tendermint clients of the local database.
+
+use std::{path::{Path, PathBuf}, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use codec::{Decode, Encode};
+use futures01::Future;
+use jsonrpc_core::{Params, Value};
+use jsonrpc_core_client::{transports::ws, RawClient};
+use node_template_runtime::{
+	tendermint_client::{
+		self,
+		types::{
+			ExportedClient, Height, TMClientInfo, TMCreateClientPayload, TMUpdateClientPayload, TendermintClient,
+		},
+		validation,
+	},
+	AccountId, Block, Call, Hash, Index, Runtime, SignedExtra, UncheckedExtrinsic,
+};
+use sc_cli::{BlockNumberOrHash, CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::StorageProvider;
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	crypto::{Pair, Ss58Codec},
+	hashing::{blake2_128, twox_128},
+	sr25519, storage::StorageKey, Bytes,
+};
+use sp_runtime::{
+	generic::{BlockId, Era, SignedPayload},
+	traits::IdentifyAccount,
+	MultiSignature, MultiSigner,
+};
+use structopt::StructOpt;
+
+/// Storage prefix of the tendermint-client pallet, as named in `construct_runtime!`.
+const PALLET_PREFIX: &[u8] = b"TendermintClientModule";
+
+#[derive(Debug, StructOpt)]
+pub struct TmClientCmd {
+	#[structopt(subcommand)]
+	pub action: TmClientAction,
+
+	/// WebSocket RPC endpoint of the node.
+	#[structopt(long, default_value = "ws://127.0.0.1:9944")]
+	pub url: String,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum TmClientAction {
+	/// Create a client from a json encoded `TMCreateClientPayload`.
+	Create {
+		/// File holding the payload.
+		#[structopt(parse(from_os_str))]
+		payload: PathBuf,
+
+		#[structopt(flatten)]
+		signer: SignerParams,
+	},
+
+	/// Update a client with a json encoded `TMUpdateClientPayload`.
+	Update {
+		/// File holding the payload.
+		#[structopt(parse(from_os_str))]
+		payload: PathBuf,
+
+		#[structopt(flatten)]
+		signer: SignerParams,
+	},
+
+	/// Print the state of a client, or its consensus state at a height.
+	Show {
+		/// Id of the client.
+		client_id: String,
+
+		/// Height of the consensus state to print, as `{revision number}-{revision height}`.
+		#[structopt(long, parse(try_from_str = parse_height))]
+		height: Option<Height>,
+	},
+
+	/// List the clients with their chain id and latest height.
+	List,
+
+	/// Check whether a create client payload would be accepted, without submitting it.
+	CheckCreate {
+		/// File holding the payload.
+		#[structopt(parse(from_os_str))]
+		payload: PathBuf,
+
+		/// Time to check the payload at, in RFC 3339. Defaults to now.
+		#[structopt(long)]
+		time: Option<DateTime<Utc>>,
+	},
+
+	/// Check whether an update client payload would be accepted, without submitting it.
+	CheckUpdate {
+		/// File holding the payload.
+		#[structopt(parse(from_os_str))]
+		payload: PathBuf,
+
+		/// File holding the client state, as printed by `show`. Read from the node if not given.
+		#[structopt(long, parse(from_os_str))]
+		client_state: Option<PathBuf>,
+
+		/// Time to check the payload at, in RFC 3339. Defaults to now.
+		#[structopt(long)]
+		time: Option<DateTime<Utc>>,
+	},
+}
+
+#[derive(Debug, StructOpt)]
+pub struct SignerParams {
+	/// Secret URI of the sr25519 account signing the extrinsic: a seed phrase, a hex seed or a derivation
+	/// such as `//Alice`.
+	#[structopt(long, default_value = "//Alice")]
+	pub suri: String,
+
+	/// Password of the secret URI.
+	#[structopt(long)]
+	pub password: Option<String>,
+}
+
+fn parse_height(s: &str) -> Result<Height, String> {
+	let (revision_number, revision_height) = match s.find('-') {
+		Some(split) => (&s[..split], &s[split + 1..]),
+		None => ("0", s),
+	};
+	let parse = |n: &str| n.parse::<u64>().map_err(|e| format!("Invalid height {}: {}", s, e));
+	Ok(Height::new(parse(revision_number)?, parse(revision_height)?))
+}
+
+impl TmClientCmd {
+	/// Run the command against the node at `url`.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		match &self.action {
+			TmClientAction::CheckCreate { payload, time } => return check_create(payload, time.unwrap_or_else(Utc::now)),
+			TmClientAction::CheckUpdate { payload, client_state: Some(client_state), time } => {
+				let client: TendermintClient = read_json(client_state, "client state")?;
+				let latest_height = client.state.as_ref()
+					.map(|s| Height::from_chain_id(&client.chain_id, s.state.header().height))
+					.ok_or("Client state has no consensus state")?;
+				return check_update(payload, &client, latest_height, time.unwrap_or_else(Utc::now));
+			},
+			_ => {},
+		}
+
+		let mut runtime = tokio01::runtime::Runtime::new()?;
+		let rpc = Rpc::connect(&mut runtime, &self.url)?;
+		match &self.action {
+			TmClientAction::Create { payload, signer } => {
+				let payload = std::fs::read(payload)?;
+				let create: TMCreateClientPayload = serde_json::from_slice(&payload)
+					.map_err(|e| format!("Invalid create client payload: {}", e))?;
+				eprintln!("Creating client {}", String::from_utf8_lossy(&create.client_id));
+				let call = tendermint_client::Call::init_client(payload);
+				rpc.submit(signer, Call::TendermintClientModule(call))
+			},
+			TmClientAction::Update { payload, signer } => {
+				let payload = std::fs::read(payload)?;
+				let update: TMUpdateClientPayload = serde_json::from_slice(&payload)
+					.map_err(|e| format!("Invalid update client payload: {}", e))?;
+				eprintln!(
+					"Updating client {} to height {}",
+					String::from_utf8_lossy(&update.client_id),
+					update.header.signed_header.header.height,
+				);
+				let call = tendermint_client::Call::update_client(payload);
+				rpc.submit(signer, Call::TendermintClientModule(call))
+			},
+			TmClientAction::Show { client_id, height } => rpc.show(client_id.as_bytes(), *height),
+			TmClientAction::List => rpc.list(),
+			TmClientAction::CheckUpdate { payload, time, .. } => {
+				let update: TMUpdateClientPayload = read_json(payload, "update client payload")?;
+				let (client, latest_height) = rpc.client(&update.client_id)?;
+				check_update(payload, &client, latest_height, time.unwrap_or_else(Utc::now))
+			},
+			TmClientAction::CheckCreate { .. } => unreachable!("checked without connecting to the node; qed"),
+		}
+	}
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path, what: &str) -> Result<T, String> {
+	let json = std::fs::read(path).map_err(|e| format!("Unable to read {} {}: {}", what, path.display(), e))?;
+	serde_json::from_slice(&json).map_err(|e| format!("Invalid {} {}: {}", what, path.display(), e))
+}
+
+/// Runs the checks of `init_client` on the payload at `path`, apart from the client id being unused.
+fn check_create(path: &Path, now: DateTime<Utc>) -> sc_cli::Result<()> {
+	let payload: TMCreateClientPayload = read_json(path, "create client payload")?;
+	let (mut client, header) = validation::client_from_payload(payload);
+	match validation::initialise_client(&mut client, header, now) {
+		Ok(height) => {
+			eprintln!(
+				"OK: client {} of chain {} would be created at height {}",
+				String::from_utf8_lossy(&client.client_id),
+				String::from_utf8_lossy(&client.chain_id),
+				format_height(&height),
+			);
+			Ok(())
+		},
+		Err(e) => Err(format!("Rejected: {:?}", e).into()),
+	}
+}
+
+/// Runs the checks of `update_client` on the payload at `path` against `client` at `latest_height`.
+fn check_update(
+	path: &Path,
+	client: &TendermintClient,
+	latest_height: Height,
+	now: DateTime<Utc>,
+) -> sc_cli::Result<()> {
+	let payload: TMUpdateClientPayload = read_json(path, "update client payload")?;
+	let trusted_state = &client.state.as_ref().ok_or("Client has no consensus state")?.state;
+	match validation::verify_update(
+		client,
+		trusted_state,
+		latest_height,
+		&payload.header,
+		Some(&payload.next_validator_set),
+		None,
+		now,
+	) {
+		Ok((height, _)) => {
+			eprintln!(
+				"OK: client {} would be updated from height {} to {}",
+				String::from_utf8_lossy(&client.client_id),
+				format_height(&latest_height),
+				format_height(&height),
+			);
+			Ok(())
+		},
+		Err(e) => Err(format!("Rejected: {:?}", e).into()),
+	}
+}
+
+/// Blocking JSON-RPC client over a WebSocket connection.
+struct Rpc<'a> {
+	runtime: &'a mut tokio01::runtime::Runtime,
+	client: RawClient,
+}
+
+impl<'a> Rpc<'a> {
+	fn connect(runtime: &'a mut tokio01::runtime::Runtime, url: &str) -> Result<Self, String> {
+		let connect = ws::try_connect::<RawClient>(url).map_err(|e| format!("Invalid url {}: {:?}", url, e))?;
+		let client = runtime.block_on(connect).map_err(|e| format!("Unable to connect to {}: {:?}", url, e))?;
+		Ok(Rpc { runtime, client })
+	}
+
+	fn call<T: serde::de::DeserializeOwned>(&mut self, method: &str, params: Vec<Value>) -> Result<T, String> {
+		let response = self.client.call_method(method, Params::Array(params))
+			.map_err(|e| format!("{} failed: {:?}", method, e));
+		let value = self.runtime.block_on(response)?;
+		serde_json::from_value(value).map_err(|e| format!("Unexpected {} response: {}", method, e))
+	}
+
+	/// Raw value of storage item `key`, if set.
+	fn storage(&mut self, key: Vec<u8>) -> Result<Option<Vec<u8>>, String> {
+		let value: Option<Bytes> = self.call("state_getStorage", vec![to_value(Bytes(key))])?;
+		Ok(value.map(|v| v.0))
+	}
+
+	/// SCALE decoded value of storage item `key`, if set.
+	fn decoded_storage<T: Decode>(&mut self, key: Vec<u8>) -> Result<Option<T>, String> {
+		self.storage(key)?
+			.map(|bytes| T::decode(&mut &bytes[..]).map_err(|e| format!("Unable to decode storage: {}", e)))
+			.transpose()
+	}
+
+	/// Signs `call` with the account of `signer` and submits it to the transaction pool.
+	fn submit(mut self, signer: &SignerParams, call: Call) -> sc_cli::Result<()> {
+		let pair = sr25519::Pair::from_string(&signer.suri, signer.password.as_deref())
+			.map_err(|e| format!("Invalid secret URI: {:?}", e))?;
+		let account: AccountId = MultiSigner::from(pair.public()).into_account();
+
+		let nonce: Index = self.call("system_accountNextIndex", vec![to_value(account.to_ss58check())])?;
+		let genesis_hash: Hash = self.call("chain_getBlockHash", vec![to_value(0)])?;
+		let version: Value = self.call("state_getRuntimeVersion", vec![])?;
+		let version_field = |field: &str| version[field].as_u64()
+			.map(|v| v as u32)
+			.ok_or_else(|| format!("Runtime version has no {}", field));
+		let (spec_version, transaction_version) = (version_field("specVersion")?, version_field("transactionVersion")?);
+
+		let extra: SignedExtra = (
+			frame_system::CheckSpecVersion::<Runtime>::new(),
+			frame_system::CheckTxVersion::<Runtime>::new(),
+			frame_system::CheckGenesis::<Runtime>::new(),
+			frame_system::CheckEra::<Runtime>::from(Era::Immortal),
+			frame_system::CheckNonce::<Runtime>::from(nonce),
+			frame_system::CheckWeight::<Runtime>::new(),
+			pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+		);
+		let additional = (spec_version, transaction_version, genesis_hash, genesis_hash, (), (), ());
+		let payload = SignedPayload::from_raw(call, extra, additional);
+		let signature = MultiSignature::from(payload.using_encoded(|p| pair.sign(p)));
+		let (call, extra, _) = payload.deconstruct();
+		let extrinsic = UncheckedExtrinsic::new_signed(call, account.clone(), signature, extra);
+
+		let hash: Hash = self.call("author_submitExtrinsic", vec![to_value(Bytes(extrinsic.encode()))])?;
+		eprintln!("Submitted extrinsic {:?} from {} with nonce {}", hash, account.to_ss58check(), nonce);
+		Ok(())
+	}
+
+	/// State and latest height of client `client_id`.
+	fn client(&mut self, client_id: &[u8]) -> Result<(TendermintClient, Height), String> {
+		let client = self.storage(map_key(b"TMClientStorage", client_id))?
+			.ok_or_else(|| format!("Client {} not found", String::from_utf8_lossy(client_id)))?;
+		let client: TendermintClient = serde_json::from_slice(&client)
+			.map_err(|e| format!("Unable to decode client: {}", e))?;
+		let info: TMClientInfo = self.decoded_storage(map_key(b"ClientInfoMap", client_id))?.unwrap_or_default();
+		Ok((client, info.last_block))
+	}
+
+	fn show(mut self, client_id: &[u8], height: Option<Height>) -> sc_cli::Result<()> {
+		let (client, _) = self.client(client_id)?;
+
+		match height {
+			Some(height) => {
+				let key = [map_key(b"ConsensusStates", client_id), blake2_128_concat(&height.encode())].concat();
+				let state = self.storage(key)?.ok_or_else(|| format!("No consensus state at {:?}", height))?;
+				let state: Value = serde_json::from_slice(&state)
+					.map_err(|e| format!("Unable to decode consensus state: {}", e))?;
+				println!("{}", pretty(&state));
+			},
+			None => {
+				let heights: Vec<Height> = self.decoded_storage(map_key(b"ConsensusHeights", client_id))?
+					.unwrap_or_default();
+				println!("{}", pretty(&client));
+				// Kept off stdout, so that the client state can be saved for `check-update`
+				eprintln!("Consensus states: {}", heights.iter().map(format_height).collect::<Vec<_>>().join(", "));
+			},
+		}
+		Ok(())
+	}
+
+	fn list(mut self) -> sc_cli::Result<()> {
+		let clients: Vec<Vec<u8>> = self.decoded_storage(value_key(b"AvailableClients"))?.unwrap_or_default();
+		for client_id in clients {
+			let info: TMClientInfo = self.decoded_storage(map_key(b"ClientInfoMap", &client_id))?.unwrap_or_default();
+			println!(
+				"{}\t{}\t{}",
+				String::from_utf8_lossy(&client_id),
+				String::from_utf8_lossy(&info.chain_id),
+				format_height(&info.last_block),
+			);
+		}
+		Ok(())
+	}
+}
+
+#[derive(Debug, StructOpt)]
+pub struct ExportTmClientsCmd {
+	/// Output JSON file, to be given to `--import-tm-clients`.
+	#[structopt(parse(from_os_str))]
+	pub output: PathBuf,
+
+	/// Block hash or number to export the clients at. Defaults to the best block.
+	#[structopt(long, value_name = "HASH or NUMBER")]
+	pub at: Option<BlockNumberOrHash>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl ExportTmClientsCmd {
+	/// Write the clients stored at the requested block of `client` to the output file.
+	pub fn run(&self, client: Arc<crate::service::FullClient>) -> sc_cli::Result<()> {
+		let at = match &self.at {
+			Some(at) => at.parse::<Block>()?,
+			None => BlockId::Hash(client.info().best_hash),
+		};
+		let storage = |key: Vec<u8>| -> sc_cli::Result<Option<Vec<u8>>> {
+			Ok(client.storage(&at, &StorageKey(key)).map_err(|e| format!("{:?}", e))?.map(|data| data.0))
+		};
+		let decode_error = |what: &str, client_id: &[u8]| {
+			format!("Unable to decode {} of client {}", what, String::from_utf8_lossy(client_id))
+		};
+
+		let clients = match storage(value_key(b"AvailableClients"))? {
+			Some(bytes) => Vec::<Vec<u8>>::decode(&mut &bytes[..]).map_err(|e| format!("Unable to decode clients: {}", e))?,
+			None => Vec::new(),
+		};
+		let mut exported = Vec::with_capacity(clients.len());
+		for client_id in clients {
+			let client_state = storage(map_key(b"TMClientStorage", &client_id))?.unwrap_or_default();
+			let client_info = storage(map_key(b"ClientInfoMap", &client_id))?.unwrap_or_default();
+			exported.push(ExportedClient {
+				client: serde_json::from_slice(&client_state).map_err(|_| decode_error("state", &client_id))?,
+				info: TMClientInfo::decode(&mut &client_info[..]).map_err(|_| decode_error("info", &client_id))?,
+			});
+		}
+
+		let file = std::fs::File::create(&self.output)?;
+		serde_json::to_writer_pretty(std::io::BufWriter::new(file), &exported)
+			.map_err(|e| format!("Unable to write {}: {}", self.output.display(), e))?;
+		eprintln!("Exported {} clients at {:?} to {}", exported.len(), at, self.output.display());
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportTmClientsCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+fn to_value<T: serde::Serialize>(value: T) -> Value {
+	serde_json::to_value(value).expect("RPC parameters are serializable; qed")
+}
+
+fn pretty<T: serde::Serialize>(value: &T) -> String {
+	serde_json::to_string_pretty(value).expect("decoded state is serializable; qed")
+}
+
+fn format_height(height: &Height) -> String {
+	format!("{}-{}", height.revision_number, height.revision_height)
+}
+
+fn value_key(item: &[u8]) -> Vec<u8> {
+	[twox_128(PALLET_PREFIX), twox_128(item)].concat()
+}
+
+/// Key of `key` in the `blake2_128_concat` map `item`.
+fn map_key(item: &[u8], key: &[u8]) -> Vec<u8> {
+	[value_key(item), blake2_128_concat(&key.encode())].concat()
+}
+
+fn blake2_128_concat(encoded: &[u8]) -> Vec<u8> {
+	[&blake2_128(encoded)[..], encoded].concat()
+}
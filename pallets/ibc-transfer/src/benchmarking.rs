@@ -0,0 +1,53 @@
+//! Benchmarks of the ibc-transfer pallet, over the length of the transfer payload.
+
+use super::*;
+
+use frame_benchmarking::{benchmarks, whitelisted_caller};
+use frame_system::RawOrigin;
+use sp_runtime::traits::UniqueSaturatedInto;
+use tendermint_client::types::Height;
+
+use crate::testing::{open_channel, CHANNEL_ID};
+
+/// Length of the longest receiver benchmarked, well past the length of Cosmos addresses.
+const MAX_RECEIVER_LENGTH: u32 = 1_000;
+
+benchmarks! {
+    _ { }
+
+    // Native tokens sent over an open channel, with a receiver of `l` bytes as the variable part of the payload. Vouchers
+    // cost as much, reading their trace and moving balances of the pallet's ledger instead of the currency's.
+    transfer {
+        let l in 0 .. MAX_RECEIVER_LENGTH;
+        let caller: T::AccountId = whitelisted_caller();
+        open_channel::<T>(caller.clone())?;
+        let amount = T::Currency::minimum_balance().max(1u32.into()) * 10u32.into();
+        let _ = T::Currency::make_free_balance_be(&caller, amount * 10u32.into());
+        let payload = TransferPayload {
+            source_channel: CHANNEL_ID.to_vec(),
+            denom: T::NativeDenom::get().to_vec(),
+            amount: amount.unique_saturated_into(),
+            receiver: "c".repeat(l as usize),
+            timeout_height: Height::new(1, 1000),
+            timeout_timestamp: 0,
+        };
+        let payload = serde_json::to_vec(&payload).expect("payload is serializable");
+    }: _(RawOrigin::Signed(caller), payload)
+    verify {
+        assert!(tendermint_client::Module::<T>::packet_commitment((PORT_ID.to_vec(), CHANNEL_ID.to_vec()), 1).is_some());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_transfer::<Test>());
+        });
+    }
+}
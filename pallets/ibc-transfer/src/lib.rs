@@ -0,0 +1,332 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// Wormhole IBC Transfer Pallet. Moves fungible tokens to and from Cosmos chains over ICS-20 channels of the
+/// tendermint-client pallet.
+///
+/// Native tokens sent out are escrowed in an account of the channel until they return. Tokens of other chains are
+/// received as vouchers, kept in the pallet's ledger and identified by the hash of their denomination trace.
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, dispatch, ensure,
+    traits::{Currency, ExistenceRequirement, Get},
+    transactional,
+};
+use frame_system::{self as system, ensure_signed};
+use sp_runtime::{traits::AccountIdConversion, ModuleId};
+use sp_std::{convert::TryFrom, vec::Vec};
+
+extern crate alloc;
+use alloc::string::{String, ToString};
+use log::{debug, error};
+
+use tendermint_client::channel::{ChannelEnd, Order, Packet};
+use tendermint_client::module::IbcModule;
+
+pub mod types;
+pub mod weights;
+
+use crate::types::{
+    decode_account, denom_prefix, encode_account, parse_ibc_denom, Acknowledgement, DenomTrace,
+    FungibleTokenPacketData, TransferPayload, PORT_ID, VERSION,
+};
+use crate::weights::WeightInfo;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+#[cfg(any(test, feature = "runtime-benchmarks"))]
+mod testing;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+
+/// Escrow accounts are sub accounts of the pallet's account, by channel id.
+const MODULE_ID: ModuleId = ModuleId(*b"ibc/trsf");
+
+/// The pallet's configuration trait.
+pub trait Trait: tendermint_client::Trait {
+    /// The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+    /// Currency transferred as this chain's native denomination.
+    type Currency: Currency<Self::AccountId>;
+
+    /// Denomination of `Currency` in packets. Must be UTF-8 and contain no `/`.
+    type NativeDenom: Get<&'static [u8]>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as IbcTransfer {
+        /// Stores the trace of each denomination received as vouchers, by its hash
+        DenomTraces get(fn denom_trace): map hasher(blake2_128_concat) Vec<u8> => Option<DenomTrace>;
+        /// Stores voucher balances, by denomination hash and account
+        Vouchers get(fn voucher_balance): double_map hasher(blake2_128_concat) Vec<u8>, hasher(blake2_128_concat) T::AccountId => u128;
+        /// Stores the amount of vouchers in circulation or escrowed, by denomination hash
+        VoucherSupply get(fn voucher_supply): map hasher(blake2_128_concat) Vec<u8> => u128;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as system::Trait>::AccountId,
+    {
+        /// Event `Transfer` is declared with a parameter of the type `string` (channel id), `string` (denomination
+        /// path), `u128` (amount), `u64` (packet sequence) and is fired when tokens are sent to the counterparty.
+        Transfer(AccountId, Vec<u8>, Vec<u8>, u128, u64),
+        /// Event `Received` is declared with a parameter of the type `string` (denomination), `u128` (amount) and is
+        /// fired when tokens sent by the counterparty are credited to the receiver.
+        Received(AccountId, Vec<u8>, u128),
+        /// Event `Refunded` is declared with a parameter of the type `string` (denomination), `u128` (amount) and is
+        /// fired when tokens of a transfer that failed or timed out are credited back to the sender.
+        Refunded(AccountId, Vec<u8>, u128),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// Unable to deserialize extrinsic.
+        DeserializeError,
+        /// Amount is zero or does not fit the currency.
+        InvalidAmount,
+        /// Denomination is neither native nor of known vouchers.
+        UnknownDenom,
+        /// Account holds fewer vouchers than transferred.
+        InsufficientVouchers,
+        /// Voucher supply would overflow.
+        VoucherOverflow,
+        /// Packet data is not ICS-20 packet data.
+        InvalidPacketData,
+        /// Receiver is not an account of this chain.
+        InvalidReceiver,
+        /// Escrow of the channel holds fewer tokens than returned.
+        InsufficientEscrow,
+        /// Acknowledgement is not an ICS-20 acknowledgement.
+        InvalidAcknowledgement,
+        /// Channel is not unordered or not of the ICS-20 version.
+        InvalidChannel,
+        /// ICS-20 channels cannot be closed by users.
+        CannotCloseChannel,
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Sends tokens to an account of the counterparty.
+        /// takes json encoded `TransferPayload` struct. Native tokens are escrowed, vouchers returning to the chain
+        /// they came from over `source_channel` are burnt, and other vouchers are escrowed.
+        #[weight = T::WeightInfo::transfer(payload.len() as u32)]
+        #[transactional]
+        pub fn transfer(origin, payload: Vec<u8>) -> dispatch::DispatchResult {
+            // Check it was signed
+            let sender = ensure_signed(origin)?;
+
+            debug!("Submitted transfer payload: {:?}", payload);
+
+            let transfer_payload: TransferPayload = serde_json::from_slice(&payload[..]).map_err(|e| {
+              error!("Deserialization Error: {}", e);
+              Error::<T>::DeserializeError
+            })?;
+            let TransferPayload { source_channel, denom, amount, receiver, timeout_height, timeout_timestamp } = transfer_payload;
+            ensure!(amount > 0, Error::<T>::InvalidAmount);
+            let escrow = Self::escrow_account(&source_channel);
+
+            let full_denom = if denom == T::NativeDenom::get() {
+                T::Currency::transfer(&sender, &escrow, Self::balance(amount)?, ExistenceRequirement::KeepAlive)?;
+                denom
+            } else {
+                let hash = parse_ibc_denom(&denom).ok_or(Error::<T>::UnknownDenom)?;
+                let trace = DenomTraces::get(&hash).ok_or(Error::<T>::UnknownDenom)?;
+                if trace.full_path().starts_with(&denom_prefix(PORT_ID, &source_channel)) {
+                    Self::burn(&hash, &sender, amount)?;
+                } else {
+                    Self::move_vouchers(&hash, &sender, &escrow, amount)?;
+                }
+                trace.full_path()
+            };
+
+            let data = FungibleTokenPacketData {
+                amount: amount.to_string(),
+                denom: String::from_utf8(full_denom.clone()).map_err(|_| Error::<T>::UnknownDenom)?,
+                receiver,
+                sender: encode_account(&sender),
+            };
+            let sequence = <tendermint_client::Module<T>>::send_packet(
+                PORT_ID.to_vec(),
+                source_channel.clone(),
+                data.to_bytes(),
+                timeout_height,
+                timeout_timestamp,
+            )?;
+
+            Self::deposit_event(RawEvent::Transfer(sender, source_channel, full_denom, amount, sequence));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Account holding the tokens escrowed while sent over `channel_id`.
+    pub fn escrow_account(channel_id: &[u8]) -> T::AccountId {
+        MODULE_ID.into_sub_account(channel_id)
+    }
+
+    fn balance(amount: u128) -> Result<BalanceOf<T>, Error<T>> {
+        BalanceOf::<T>::try_from(amount).map_err(|_| Error::<T>::InvalidAmount)
+    }
+
+    fn mint(hash: &[u8], who: &T::AccountId, amount: u128) -> Result<(), Error<T>> {
+        let supply = VoucherSupply::get(hash).checked_add(amount).ok_or(Error::<T>::VoucherOverflow)?;
+        VoucherSupply::insert(hash, supply);
+        <Vouchers<T>>::mutate(hash, who, |balance| *balance += amount);
+        Ok(())
+    }
+
+    fn burn(hash: &[u8], who: &T::AccountId, amount: u128) -> Result<(), Error<T>> {
+        let balance = <Vouchers<T>>::get(hash, who).checked_sub(amount).ok_or(Error::<T>::InsufficientVouchers)?;
+        <Vouchers<T>>::insert(hash, who, balance);
+        VoucherSupply::mutate(hash, |supply| *supply -= amount);
+        Ok(())
+    }
+
+    fn move_vouchers(hash: &[u8], from: &T::AccountId, to: &T::AccountId, amount: u128) -> Result<(), Error<T>> {
+        let balance = <Vouchers<T>>::get(hash, from).checked_sub(amount).ok_or(Error::<T>::InsufficientVouchers)?;
+        <Vouchers<T>>::insert(hash, from, balance);
+        <Vouchers<T>>::mutate(hash, to, |balance| *balance += amount);
+        Ok(())
+    }
+
+    /// Credits the tokens of a received packet to its receiver: tokens returning to this chain leave the escrow of the
+    /// channel, and others are minted as vouchers of the denomination prefixed with the channel. Nothing is written
+    /// unless the packet is credited.
+    fn receive(packet: &Packet) -> Result<(), Error<T>> {
+        let data = FungibleTokenPacketData::from_bytes(&packet.data).ok_or(Error::<T>::InvalidPacketData)?;
+        let amount = data.amount().ok_or(Error::<T>::InvalidAmount)?;
+        let receiver: T::AccountId = decode_account(&data.receiver).ok_or(Error::<T>::InvalidReceiver)?;
+        let denom = data.denom.as_bytes();
+        let source_prefix = denom_prefix(&packet.source_port, &packet.source_channel);
+
+        let received = if denom.starts_with(&source_prefix) {
+            let unprefixed = &denom[source_prefix.len()..];
+            let escrow = Self::escrow_account(&packet.destination_channel);
+            if unprefixed == T::NativeDenom::get() {
+                T::Currency::transfer(&escrow, &receiver, Self::balance(amount)?, ExistenceRequirement::AllowDeath)
+                    .map_err(|_| Error::<T>::InsufficientEscrow)?;
+                unprefixed.to_vec()
+            } else {
+                let trace = DenomTrace::parse(unprefixed);
+                Self::move_vouchers(&trace.hash(), &escrow, &receiver, amount)
+                    .map_err(|_| Error::<T>::InsufficientEscrow)?;
+                trace.ibc_denom()
+            }
+        } else {
+            let prefixed = [&denom_prefix(&packet.destination_port, &packet.destination_channel)[..], denom].concat();
+            let trace = DenomTrace::parse(&prefixed);
+            let hash = trace.hash();
+            Self::mint(&hash, &receiver, amount)?;
+            if !DenomTraces::contains_key(&hash) {
+                DenomTraces::insert(&hash, &trace);
+            }
+            trace.ibc_denom()
+        };
+
+        Self::deposit_event(RawEvent::Received(receiver, received, amount));
+        Ok(())
+    }
+
+    /// Credits the tokens of a packet sent by this chain back to its sender, undoing `transfer`.
+    fn refund(packet: &Packet) -> dispatch::DispatchResult {
+        let data = FungibleTokenPacketData::from_bytes(&packet.data).ok_or(Error::<T>::InvalidPacketData)?;
+        let amount = data.amount().ok_or(Error::<T>::InvalidAmount)?;
+        let sender: T::AccountId = decode_account(&data.sender).ok_or(Error::<T>::InvalidPacketData)?;
+        let denom = data.denom.as_bytes();
+        let trace = DenomTrace::parse(denom);
+
+        let refunded = if denom.starts_with(&denom_prefix(&packet.source_port, &packet.source_channel)) {
+            // Burnt when sent
+            Self::mint(&trace.hash(), &sender, amount)?;
+            trace.ibc_denom()
+        } else {
+            let escrow = Self::escrow_account(&packet.source_channel);
+            if denom == T::NativeDenom::get() {
+                T::Currency::transfer(&escrow, &sender, Self::balance(amount)?, ExistenceRequirement::AllowDeath)?;
+                denom.to_vec()
+            } else {
+                Self::move_vouchers(&trace.hash(), &escrow, &sender, amount)?;
+                trace.ibc_denom()
+            }
+        };
+
+        Self::deposit_event(RawEvent::Refunded(sender, refunded, amount));
+        Ok(())
+    }
+
+    fn check_channel(ordering: Order, version: &[u8]) -> dispatch::DispatchResult {
+        ensure!(ordering == Order::Unordered && version == VERSION, Error::<T>::InvalidChannel);
+        Ok(())
+    }
+}
+
+impl<T: Trait> IbcModule for Module<T> {
+    fn port_id() -> &'static [u8] {
+        PORT_ID
+    }
+
+    fn on_chan_open_init(_channel_id: &[u8], channel: &ChannelEnd) -> dispatch::DispatchResult {
+        Self::check_channel(channel.ordering, &channel.version)
+    }
+
+    fn on_chan_open_try(
+        _channel_id: &[u8],
+        channel: &ChannelEnd,
+        counterparty_version: &[u8],
+    ) -> Result<Vec<u8>, dispatch::DispatchError> {
+        Self::check_channel(channel.ordering, counterparty_version)?;
+        Ok(VERSION.to_vec())
+    }
+
+    fn on_chan_open_ack(_channel_id: &[u8], counterparty_version: &[u8]) -> dispatch::DispatchResult {
+        ensure!(counterparty_version == VERSION, Error::<T>::InvalidChannel);
+        Ok(())
+    }
+
+    fn on_chan_close_init(_channel_id: &[u8]) -> dispatch::DispatchResult {
+        Err(Error::<T>::CannotCloseChannel.into())
+    }
+
+    fn on_recv_packet(packet: &Packet) -> Vec<u8> {
+        let acknowledgement = match Self::receive(packet) {
+            Ok(()) => Acknowledgement::success(),
+            Err(e) => {
+                let message: &'static str = e.into();
+                error!("Transfer Error: {}", message);
+                Acknowledgement::error(message)
+            }
+        };
+        acknowledgement.to_bytes()
+    }
+
+    fn on_acknowledgement_packet(packet: &Packet, acknowledgement: &[u8]) -> dispatch::DispatchResult {
+        let acknowledgement = Acknowledgement::from_bytes(acknowledgement).ok_or(Error::<T>::InvalidAcknowledgement)?;
+        if !acknowledgement.is_success() {
+            Self::refund(packet)?;
+        }
+        Ok(())
+    }
+
+    fn on_timeout_packet(packet: &Packet) -> dispatch::DispatchResult {
+        Self::refund(packet)
+    }
+}
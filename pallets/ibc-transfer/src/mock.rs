@@ -0,0 +1,133 @@
+// Creating mock runtime here
+
+use crate::{Module, Trait};
+use frame_support::{impl_outer_event, impl_outer_origin, parameter_types, weights::Weight};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+    Perbill,
+};
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+mod ibc_transfer {
+    pub use crate::Event;
+}
+
+impl_outer_event! {
+    pub enum TestEvent for Test {
+        system<T>,
+        pallet_balances<T>,
+        tendermint_client<T>,
+        ibc_transfer<T>,
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: Weight = 1024;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+    pub const MinimumPeriod: u64 = 1;
+    pub const ExistentialDeposit: u64 = 10;
+    pub const MaxConsensusStates: u32 = 3;
+    pub const PruningBudget: u32 = 10;
+    pub const SelfChainId: &'static [u8] = b"wormhole";
+    pub const AuthoritySetId: u64 = 1;
+    pub const SelfHistory: u32 = 3;
+    pub const MaxExpectedTimePerBlock: u64 = 6_000;
+    pub const NativeDenom: &'static [u8] = b"worm";
+}
+impl system::Trait for Test {
+    type BaseCallFilter = ();
+    type Origin = Origin;
+    type Call = ();
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = TestEvent;
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type DbWeight = ();
+    type BlockExecutionWeight = ();
+    type ExtrinsicBaseWeight = ();
+    type MaximumExtrinsicWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type PalletInfo = ();
+    type AccountData = pallet_balances::AccountData<u64>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+}
+impl pallet_balances::Trait for Test {
+    type MaxLocks = ();
+    type Balance = u64;
+    type Event = TestEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+impl pallet_timestamp::Trait for Test {
+    type Moment = u64;
+    type OnTimestampSet = ();
+    type MinimumPeriod = MinimumPeriod;
+    type WeightInfo = ();
+}
+impl tendermint_client::Trait for Test {
+    type Event = TestEvent;
+    type MaxConsensusStates = MaxConsensusStates;
+    type PruningBudget = PruningBudget;
+    type Modules = (IbcTransfer,);
+    type SelfChainId = SelfChainId;
+    type AuthoritySetId = AuthoritySetId;
+    type SelfHistory = SelfHistory;
+    type MaxExpectedTimePerBlock = MaxExpectedTimePerBlock;
+    type WeightInfo = ();
+}
+impl Trait for Test {
+    type Event = TestEvent;
+    type Currency = Balances;
+    type NativeDenom = NativeDenom;
+    type WeightInfo = ();
+}
+
+pub type IbcTransfer = Module<Test>;
+pub type TendermintClient = tendermint_client::Module<Test>;
+pub type System = system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
+
+/// Balance of the accounts funded at genesis.
+pub const INITIAL_BALANCE: u64 = 1_000;
+
+// Accounts 1 and 2, and the escrow account of `channel-0`, are funded with `INITIAL_BALANCE`.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![
+            (1, INITIAL_BALANCE),
+            (2, INITIAL_BALANCE),
+            (IbcTransfer::escrow_account(b"channel-0"), INITIAL_BALANCE),
+        ],
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+    let mut ext: sp_io::TestExternalities = storage.into();
+    // Events are not recorded in the genesis block
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
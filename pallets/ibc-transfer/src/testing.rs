@@ -0,0 +1,196 @@
+//! ICS-20 channel to a simulated counterparty, opened through the tendermint-client pallet's
+//! handshakes, for the pallet's tests and benchmarks.
+
+use alloc::vec;
+use frame_system::RawOrigin;
+use sp_runtime::{traits::UniqueSaturatedFrom, DispatchError};
+use sp_std::vec::Vec;
+
+use tendermint_client::channel::{
+    channel_path, ChanOpenConfirmPayload, ChanOpenTryPayload, ChannelCounterparty, ChannelEnd,
+    ChannelState, Order,
+};
+use tendermint_client::connection::{
+    client_state_path, connection_path, consensus_state_path, ConnOpenConfirmPayload,
+    ConnOpenTryPayload, ConnectionEnd, ConnectionState, Counterparty, Version,
+};
+use tendermint_client::testing::{validators, TestChain, TestStore};
+use tendermint_client::types::Height;
+
+use crate::types::{PORT_ID, VERSION};
+use crate::Trait;
+
+type Client<T> = tendermint_client::Module<T>;
+
+/// Channel of this chain `open_channel` opens.
+pub const CHANNEL_ID: &[u8] = b"channel-0";
+/// Counterparty's end of `CHANNEL_ID`.
+pub const COUNTERPARTY_CHANNEL_ID: &[u8] = b"channel-7";
+const CLIENT_ID: &[u8] = b"07-tendermint-0";
+const COUNTERPARTY_CLIENT_ID: &[u8] = b"07-tendermint-5";
+const COUNTERPARTY_CONNECTION_ID: &[u8] = b"connection-3";
+/// Height of the counterparty's consensus state of this chain, proven in the connection handshake.
+const SELF_CONSENSUS_HEIGHT: Height = Height {
+    revision_number: 0,
+    revision_height: 1,
+};
+
+/// Moves the block time just past the current header of `chain`.
+fn set_time<T: Trait>(chain: &TestChain) {
+    let millis = chain.time.timestamp_millis() as u64 + 1_000;
+    pallet_timestamp::Module::<T>::set_timestamp(T::Moment::unique_saturated_from(millis));
+}
+
+/// Updates the client of `chain` to a header committing to `store`.
+fn commit_store<T: Trait>(
+    signer: &T::AccountId,
+    chain: &mut TestChain,
+    store: &TestStore,
+) -> Result<Height, DispatchError> {
+    chain.advance(1, 5);
+    chain.app_hash = store.root();
+    set_time::<T>(chain);
+    let payload = chain.update_payload(CLIENT_ID.to_vec());
+    Client::<T>::update_client(
+        RawOrigin::Signed(signer.clone()).into(),
+        serde_json::to_vec(&payload).expect("payload is serializable"),
+    )
+    .map_err(|e| e.error)?;
+    Ok(chain.ibc_height())
+}
+
+/// Store of the counterparty, holding its end of `connection-0` of this chain in `state`, and its
+/// client of this chain. The client is not validated without the `self-client-validation`
+/// feature of the tendermint-client pallet, so its states are opaque.
+fn counterparty_connection_store(state: ConnectionState) -> TestStore {
+    let connection_id = match state {
+        ConnectionState::Init => Vec::new(),
+        _ => b"connection-0".to_vec(),
+    };
+    let mut store = TestStore::new();
+    store.set_connection(
+        COUNTERPARTY_CONNECTION_ID,
+        &ConnectionEnd {
+            state,
+            client_id: COUNTERPARTY_CLIENT_ID.to_vec(),
+            counterparty: Counterparty {
+                client_id: CLIENT_ID.to_vec(),
+                connection_id,
+                prefix: b"ibc".to_vec(),
+            },
+            versions: vec![Version::default()],
+            delay_period: 0,
+        },
+    );
+    store.set(
+        client_state_path(COUNTERPARTY_CLIENT_ID),
+        b"client state".to_vec(),
+    );
+    store.set(
+        consensus_state_path(COUNTERPARTY_CLIENT_ID, SELF_CONSENSUS_HEIGHT),
+        b"consensus state".to_vec(),
+    );
+    store
+}
+
+/// Store of the counterparty, holding its end of the ICS-20 channel to `channel_id` of this chain
+/// in `state`.
+fn counterparty_channel_store(state: ChannelState, channel_id: &[u8]) -> TestStore {
+    let mut store = TestStore::new();
+    store.set_channel(
+        PORT_ID,
+        COUNTERPARTY_CHANNEL_ID,
+        &ChannelEnd {
+            state,
+            ordering: Order::Unordered,
+            counterparty: ChannelCounterparty {
+                port_id: PORT_ID.to_vec(),
+                channel_id: channel_id.to_vec(),
+            },
+            connection_hops: vec![COUNTERPARTY_CONNECTION_ID.to_vec()],
+            version: VERSION.to_vec(),
+        },
+    );
+    store
+}
+
+/// Opens `CHANNEL_ID` of the `transfer` port to the counterparty's `COUNTERPARTY_CHANNEL_ID`,
+/// over connection `connection-0`, both from the counterparty's `Init` ends. The handshakes are
+/// signed by `signer`. Returns the counterparty.
+pub fn open_channel<T: Trait>(signer: T::AccountId) -> Result<TestChain, DispatchError> {
+    let origin = || -> T::Origin { RawOrigin::Signed(signer.clone()).into() };
+    let mut chain = TestChain::new("counterparty-1", validators(4, 10));
+    set_time::<T>(&chain);
+    let payload = chain.create_payload(CLIENT_ID.to_vec(), 86400);
+    Client::<T>::init_client(
+        origin(),
+        serde_json::to_vec(&payload).expect("payload is serializable"),
+    )
+    .map_err(|e| e.error)?;
+
+    let store = counterparty_connection_store(ConnectionState::Init);
+    let payload = ConnOpenTryPayload {
+        client_id: CLIENT_ID.to_vec(),
+        counterparty: Counterparty {
+            client_id: COUNTERPARTY_CLIENT_ID.to_vec(),
+            connection_id: COUNTERPARTY_CONNECTION_ID.to_vec(),
+            prefix: b"ibc".to_vec(),
+        },
+        delay_period: 0,
+        counterparty_versions: vec![Version::default()],
+        client_state: b"client state".to_vec(),
+        consensus_state: b"consensus state".to_vec(),
+        consensus_height: SELF_CONSENSUS_HEIGHT,
+        proof_height: commit_store::<T>(&signer, &mut chain, &store)?,
+        proof_init: store.prove(&connection_path(COUNTERPARTY_CONNECTION_ID)),
+        proof_client: store.prove(&client_state_path(COUNTERPARTY_CLIENT_ID)),
+        proof_consensus: store.prove(&consensus_state_path(
+            COUNTERPARTY_CLIENT_ID,
+            SELF_CONSENSUS_HEIGHT,
+        )),
+    };
+    Client::<T>::conn_open_try(
+        origin(),
+        serde_json::to_vec(&payload).expect("payload is serializable"),
+    )?;
+    let store = counterparty_connection_store(ConnectionState::Open);
+    let payload = ConnOpenConfirmPayload {
+        connection_id: b"connection-0".to_vec(),
+        proof_height: commit_store::<T>(&signer, &mut chain, &store)?,
+        proof_ack: store.prove(&connection_path(COUNTERPARTY_CONNECTION_ID)),
+    };
+    Client::<T>::conn_open_confirm(
+        origin(),
+        serde_json::to_vec(&payload).expect("payload is serializable"),
+    )?;
+
+    let store = counterparty_channel_store(ChannelState::Init, b"");
+    let payload = ChanOpenTryPayload {
+        port_id: PORT_ID.to_vec(),
+        connection_id: b"connection-0".to_vec(),
+        ordering: Order::Unordered,
+        counterparty: ChannelCounterparty {
+            port_id: PORT_ID.to_vec(),
+            channel_id: COUNTERPARTY_CHANNEL_ID.to_vec(),
+        },
+        counterparty_version: VERSION.to_vec(),
+        proof_height: commit_store::<T>(&signer, &mut chain, &store)?,
+        proof_init: store.prove(&channel_path(PORT_ID, COUNTERPARTY_CHANNEL_ID)),
+    };
+    Client::<T>::chan_open_try(
+        origin(),
+        serde_json::to_vec(&payload).expect("payload is serializable"),
+    )?;
+    let store = counterparty_channel_store(ChannelState::Open, CHANNEL_ID);
+    let payload = ChanOpenConfirmPayload {
+        port_id: PORT_ID.to_vec(),
+        channel_id: CHANNEL_ID.to_vec(),
+        proof_height: commit_store::<T>(&signer, &mut chain, &store)?,
+        proof_ack: store.prove(&channel_path(PORT_ID, COUNTERPARTY_CHANNEL_ID)),
+    };
+    Client::<T>::chan_open_confirm(
+        origin(),
+        serde_json::to_vec(&payload).expect("payload is serializable"),
+    )?;
+    Ok(chain)
+}
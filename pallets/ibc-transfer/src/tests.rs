@@ -0,0 +1,343 @@
+use crate::testing::open_channel;
+use crate::types::{
+    encode_account, Acknowledgement, DenomTrace, FungibleTokenPacketData, TransferPayload,
+};
+use crate::weights::WeightInfo;
+use crate::{mock::*, Call, DenomTraces, Error, RawEvent, VoucherSupply, Vouchers};
+use frame_support::{
+    assert_noop, assert_ok, dispatch::DispatchResult, weights::GetDispatchInfo, StorageDoubleMap,
+    StorageMap,
+};
+use tendermint_client::channel::{ChannelCounterparty, ChannelEnd, ChannelState, Order, Packet};
+use tendermint_client::module::IbcModule;
+use tendermint_client::types::Height;
+
+const SENDER: u64 = 1;
+const RECEIVER: u64 = 2;
+
+fn events() -> Vec<TestEvent> {
+    System::events().into_iter().map(|r| r.event).collect()
+}
+
+fn event(e: RawEvent<u64>) -> TestEvent {
+    TestEvent::ibc_transfer(e)
+}
+
+fn packet_data(denom: &str, amount: &str, sender: String, receiver: String) -> Vec<u8> {
+    FungibleTokenPacketData {
+        amount: amount.into(),
+        denom: denom.into(),
+        receiver,
+        sender,
+    }
+    .to_bytes()
+}
+
+/// Packet sent by the counterparty over its `channel-7`, to `channel-0` of this chain.
+fn received(denom: &str, amount: &str, receiver: String) -> Packet {
+    Packet {
+        sequence: 1,
+        source_port: b"transfer".to_vec(),
+        source_channel: b"channel-7".to_vec(),
+        destination_port: b"transfer".to_vec(),
+        destination_channel: b"channel-0".to_vec(),
+        data: packet_data(denom, amount, "cosmos1sender".into(), receiver),
+        timeout_height: Height::new(0, 1000),
+        timeout_timestamp: 0,
+    }
+}
+
+/// Packet sent by `SENDER` over `channel-0`.
+fn sent(denom: &str, amount: &str) -> Packet {
+    Packet {
+        sequence: 1,
+        source_port: b"transfer".to_vec(),
+        source_channel: b"channel-0".to_vec(),
+        destination_port: b"transfer".to_vec(),
+        destination_channel: b"channel-7".to_vec(),
+        data: packet_data(
+            denom,
+            amount,
+            encode_account(&SENDER),
+            "cosmos1receiver".into(),
+        ),
+        timeout_height: Height::new(1, 1000),
+        timeout_timestamp: 0,
+    }
+}
+
+fn recv(packet: &Packet) -> Acknowledgement {
+    Acknowledgement::from_bytes(&IbcTransfer::on_recv_packet(packet)).unwrap()
+}
+
+fn atom_trace() -> DenomTrace {
+    DenomTrace::parse(b"transfer/channel-0/uatom")
+}
+
+fn escrow() -> u64 {
+    IbcTransfer::escrow_account(b"channel-0")
+}
+
+#[test]
+fn receives_vouchers_of_foreign_tokens() {
+    new_test_ext().execute_with(|| {
+        let hash = atom_trace().hash();
+        assert_eq!(
+            recv(&received("uatom", "100", encode_account(&RECEIVER))),
+            Acknowledgement::success()
+        );
+        assert_eq!(Vouchers::<Test>::get(&hash, RECEIVER), 100);
+        assert_eq!(VoucherSupply::get(&hash), 100);
+        assert_eq!(DenomTraces::get(&hash), Some(atom_trace()));
+        assert_eq!(
+            events().last(),
+            Some(&event(RawEvent::Received(
+                RECEIVER,
+                atom_trace().ibc_denom(),
+                100
+            )))
+        );
+
+        assert_eq!(
+            recv(&received("uatom", "100", "cosmos1receiver".into())),
+            Acknowledgement::error("InvalidReceiver")
+        );
+        assert_eq!(
+            recv(&received("uatom", "-1", encode_account(&RECEIVER))),
+            Acknowledgement::error("InvalidAmount")
+        );
+        assert_eq!(VoucherSupply::get(&hash), 100);
+    });
+}
+
+#[test]
+fn returns_escrowed_tokens() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            recv(&received(
+                "transfer/channel-7/worm",
+                "300",
+                encode_account(&RECEIVER)
+            )),
+            Acknowledgement::success()
+        );
+        assert_eq!(Balances::free_balance(RECEIVER), INITIAL_BALANCE + 300);
+        assert_eq!(Balances::free_balance(escrow()), INITIAL_BALANCE - 300);
+
+        assert_eq!(
+            recv(&received(
+                "transfer/channel-7/worm",
+                "800",
+                encode_account(&RECEIVER)
+            )),
+            Acknowledgement::error("InsufficientEscrow")
+        );
+        assert_eq!(Balances::free_balance(RECEIVER), INITIAL_BALANCE + 300);
+
+        // Vouchers of another chain, escrowed when sent to the counterparty
+        let trace = DenomTrace::parse(b"transfer/channel-3/uosmo");
+        Vouchers::<Test>::insert(trace.hash(), escrow(), 50);
+        assert_eq!(
+            recv(&received(
+                "transfer/channel-7/transfer/channel-3/uosmo",
+                "50",
+                encode_account(&RECEIVER)
+            )),
+            Acknowledgement::success()
+        );
+        assert_eq!(Vouchers::<Test>::get(trace.hash(), RECEIVER), 50);
+        assert_eq!(Vouchers::<Test>::get(trace.hash(), escrow()), 0);
+    });
+}
+
+#[test]
+fn refunds_failed_transfers() {
+    new_test_ext().execute_with(|| {
+        let packet = sent("worm", "200");
+        assert_ok!(IbcTransfer::on_acknowledgement_packet(
+            &packet,
+            &Acknowledgement::success().to_bytes()
+        ));
+        assert_eq!(Balances::free_balance(SENDER), INITIAL_BALANCE);
+
+        assert_ok!(IbcTransfer::on_acknowledgement_packet(
+            &packet,
+            &Acknowledgement::error("InvalidReceiver").to_bytes()
+        ));
+        assert_eq!(Balances::free_balance(SENDER), INITIAL_BALANCE + 200);
+        assert_eq!(Balances::free_balance(escrow()), INITIAL_BALANCE - 200);
+        assert_eq!(
+            events().last(),
+            Some(&event(RawEvent::Refunded(SENDER, b"worm".to_vec(), 200)))
+        );
+        assert_noop!(
+            IbcTransfer::on_acknowledgement_packet(&packet, b"AQ=="),
+            Error::<Test>::InvalidAcknowledgement
+        );
+
+        // Vouchers returning to the chain they came from were burnt, and are minted back
+        let hash = atom_trace().hash();
+        assert_ok!(IbcTransfer::on_timeout_packet(&sent(
+            "transfer/channel-0/uatom",
+            "40"
+        )));
+        assert_eq!(Vouchers::<Test>::get(&hash, SENDER), 40);
+        assert_eq!(VoucherSupply::get(&hash), 40);
+    });
+}
+
+fn transfer_payload(denom: &[u8], amount: u128) -> Vec<u8> {
+    let payload = TransferPayload {
+        source_channel: b"channel-0".to_vec(),
+        denom: denom.to_vec(),
+        amount,
+        receiver: "cosmos1receiver".into(),
+        timeout_height: Height::new(1, 1000),
+        timeout_timestamp: 0,
+    };
+    serde_json::to_vec(&payload).unwrap()
+}
+
+/// Sends `amount` of `denom` from `SENDER` to the counterparty over `channel-0`.
+fn transfer(denom: &[u8], amount: u128) -> DispatchResult {
+    IbcTransfer::transfer(Origin::signed(SENDER), transfer_payload(denom, amount))
+}
+
+/// Asserts that packet `sequence` of `channel-0` carries the transfer of `amount` of `denom` by
+/// `SENDER`, and that the transfer was the last event.
+fn assert_sent(sequence: u64, denom: &str, amount: u128) {
+    let packet = Packet {
+        sequence,
+        ..sent(denom, &amount.to_string())
+    };
+    assert_eq!(
+        TendermintClient::packet_commitment(
+            (b"transfer".to_vec(), b"channel-0".to_vec()),
+            sequence
+        ),
+        Some(packet.commitment())
+    );
+    assert_eq!(
+        events().last(),
+        Some(&event(RawEvent::Transfer(
+            SENDER,
+            b"channel-0".to_vec(),
+            denom.as_bytes().to_vec(),
+            amount,
+            sequence
+        )))
+    );
+}
+
+#[test]
+fn sends_tokens_only_over_channels() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(transfer(b"worm", 0), Error::<Test>::InvalidAmount);
+        assert_noop!(transfer(b"uatom", 10), Error::<Test>::UnknownDenom);
+        assert_noop!(
+            transfer(&atom_trace().ibc_denom(), 10),
+            Error::<Test>::UnknownDenom
+        );
+        DenomTraces::insert(atom_trace().hash(), atom_trace());
+        assert_noop!(
+            transfer(&atom_trace().ibc_denom(), 10),
+            Error::<Test>::InsufficientVouchers
+        );
+        assert_noop!(
+            IbcTransfer::transfer(Origin::signed(SENDER), b"{}".to_vec()),
+            Error::<Test>::DeserializeError
+        );
+
+        // Tokens are escrowed only if the packet is sent
+        assert_noop!(
+            transfer(b"worm", 100),
+            tendermint_client::Error::<Test>::ChannelNotFound
+        );
+        assert_eq!(Balances::free_balance(SENDER), INITIAL_BALANCE);
+    });
+}
+
+#[test]
+fn sends_tokens_over_open_channels() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(open_channel::<Test>(SENDER));
+
+        // Transfers are weighted by the length of their payload
+        let payload = transfer_payload(b"worm", 100);
+        assert_eq!(
+            Call::<Test>::transfer(payload.clone())
+                .get_dispatch_info()
+                .weight,
+            <() as WeightInfo>::transfer(payload.len() as u32)
+        );
+
+        // Native tokens are escrowed
+        assert_ok!(transfer(b"worm", 100));
+        assert_eq!(Balances::free_balance(SENDER), INITIAL_BALANCE - 100);
+        assert_eq!(Balances::free_balance(escrow()), INITIAL_BALANCE + 100);
+        assert_sent(1, "worm", 100);
+
+        // Vouchers received over the channel return to the counterparty, and are burnt
+        let hash = atom_trace().hash();
+        assert_eq!(
+            recv(&received("uatom", "100", encode_account(&SENDER))),
+            Acknowledgement::success()
+        );
+        assert_ok!(transfer(&atom_trace().ibc_denom(), 40));
+        assert_eq!(Vouchers::<Test>::get(&hash, SENDER), 60);
+        assert_eq!(Vouchers::<Test>::get(&hash, escrow()), 0);
+        assert_eq!(VoucherSupply::get(&hash), 60);
+        assert_sent(2, "transfer/channel-0/uatom", 40);
+
+        // Vouchers received over another channel are escrowed
+        let trace = DenomTrace::parse(b"transfer/channel-3/uosmo");
+        DenomTraces::insert(trace.hash(), &trace);
+        Vouchers::<Test>::insert(trace.hash(), SENDER, 50);
+        VoucherSupply::insert(trace.hash(), 50);
+        assert_ok!(transfer(&trace.ibc_denom(), 30));
+        assert_eq!(Vouchers::<Test>::get(trace.hash(), SENDER), 20);
+        assert_eq!(Vouchers::<Test>::get(trace.hash(), escrow()), 30);
+        assert_eq!(VoucherSupply::get(trace.hash()), 50);
+        assert_sent(3, "transfer/channel-3/uosmo", 30);
+        assert_eq!(Balances::free_balance(SENDER), INITIAL_BALANCE - 100);
+    });
+}
+
+#[test]
+fn accepts_only_ics20_channels() {
+    new_test_ext().execute_with(|| {
+        let mut channel = ChannelEnd {
+            state: ChannelState::Init,
+            ordering: Order::Unordered,
+            counterparty: ChannelCounterparty {
+                port_id: b"transfer".to_vec(),
+                channel_id: Vec::new(),
+            },
+            connection_hops: vec![b"connection-0".to_vec()],
+            version: b"ics20-1".to_vec(),
+        };
+        assert_ok!(IbcTransfer::on_chan_open_init(b"channel-0", &channel));
+        assert_eq!(
+            IbcTransfer::on_chan_open_try(b"channel-0", &channel, b"ics20-1"),
+            Ok(b"ics20-1".to_vec())
+        );
+        assert_noop!(
+            IbcTransfer::on_chan_open_try(b"channel-0", &channel, b"ics20-2"),
+            Error::<Test>::InvalidChannel
+        );
+        assert_noop!(
+            IbcTransfer::on_chan_open_ack(b"channel-0", b"ics20-2"),
+            Error::<Test>::InvalidChannel
+        );
+
+        channel.ordering = Order::Ordered;
+        assert_noop!(
+            IbcTransfer::on_chan_open_init(b"channel-0", &channel),
+            Error::<Test>::InvalidChannel
+        );
+        assert_noop!(
+            IbcTransfer::on_chan_close_init(b"channel-0"),
+            Error::<Test>::CannotCloseChannel
+        );
+    });
+}
@@ -0,0 +1,264 @@
+//! ICS-20 packet data, acknowledgements and denomination traces.
+
+use alloc::string::{String, ToString};
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sp_std::vec::Vec;
+use tendermint_client::types::Height;
+
+/// Port the transfer module is bound to.
+pub const PORT_ID: &[u8] = b"transfer";
+
+/// Version of the channels of the transfer port.
+pub const VERSION: &[u8] = b"ics20-1";
+
+/// Data of a transfer packet, as in ibc-go. Fields are declared in alphabetical order, so that the
+/// packet data is the sorted JSON ibc-go commits to. Accounts of this chain are hex encoded SCALE
+/// encodings of their `AccountId`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FungibleTokenPacketData {
+    pub amount: String,
+    pub denom: String,
+    pub receiver: String,
+    pub sender: String,
+}
+
+impl FungibleTokenPacketData {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("packet data serializes to JSON")
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        serde_json::from_slice(data).ok()
+    }
+
+    /// Amount transferred, which must be positive.
+    pub fn amount(&self) -> Option<u128> {
+        self.amount.parse().ok().filter(|amount| *amount > 0)
+    }
+}
+
+/// Acknowledgement of a transfer packet, as in ibc-go: `{"result":"AQ=="}` or
+/// `{"error":"..."}`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Acknowledgement {
+    Result(String),
+    Error(String),
+}
+
+impl Acknowledgement {
+    pub fn success() -> Self {
+        Acknowledgement::Result("AQ==".to_string())
+    }
+
+    pub fn error(message: &str) -> Self {
+        Acknowledgement::Error(message.to_string())
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self, Acknowledgement::Result(_))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("acknowledgement serializes to JSON")
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        serde_json::from_slice(data).ok()
+    }
+}
+
+/// Trace of a denomination received over channels: the `{port}/{channel}` hops it took, most
+/// recent first, and its denomination on the chain it is native to.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DenomTrace {
+    pub path: Vec<u8>,
+    pub base_denom: Vec<u8>,
+}
+
+impl DenomTrace {
+    /// Parses a full denomination path: the base denomination is its last segment.
+    pub fn parse(full_path: &[u8]) -> Self {
+        match full_path.iter().rposition(|b| *b == b'/') {
+            Some(i) => DenomTrace {
+                path: full_path[..i].to_vec(),
+                base_denom: full_path[i + 1..].to_vec(),
+            },
+            None => DenomTrace {
+                path: Vec::new(),
+                base_denom: full_path.to_vec(),
+            },
+        }
+    }
+
+    pub fn full_path(&self) -> Vec<u8> {
+        if self.path.is_empty() {
+            return self.base_denom.clone();
+        }
+        [&self.path[..], b"/", &self.base_denom].concat()
+    }
+
+    /// Hash of the full path, by which vouchers of the denomination are identified.
+    pub fn hash(&self) -> Vec<u8> {
+        Sha256::digest(&self.full_path()).to_vec()
+    }
+
+    /// Denomination of the vouchers of the trace on this chain: `ibc/{HASH}`.
+    pub fn ibc_denom(&self) -> Vec<u8> {
+        [&b"ibc/"[..], encode_upper(&self.hash()).as_bytes()].concat()
+    }
+}
+
+/// Prefix a channel end adds to the denominations it receives: `{port}/{channel}/`.
+pub fn denom_prefix(port_id: &[u8], channel_id: &[u8]) -> Vec<u8> {
+    [port_id, b"/", channel_id, b"/"].concat()
+}
+
+/// Hash of the trace of an `ibc/{HASH}` denomination.
+pub fn parse_ibc_denom(denom: &[u8]) -> Option<Vec<u8>> {
+    if !denom.starts_with(b"ibc/") {
+        return None;
+    }
+    let hash = decode(&denom[4..])?;
+    if hash.len() != 32 {
+        return None;
+    }
+    Some(hash)
+}
+
+/// Moves `amount` of `denom` from `sender` to `receiver` over the `source_channel` of the transfer
+/// port, before the counterparty reaches `timeout_height` or `timeout_timestamp` (in nanoseconds).
+/// Zero values disable either timeout. `denom` is this chain's native denomination or the
+/// `ibc/{HASH}` denomination of vouchers.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TransferPayload {
+    pub source_channel: Vec<u8>,
+    pub denom: Vec<u8>,
+    pub amount: u128,
+    pub receiver: String,
+    #[serde(default)]
+    pub timeout_height: Height,
+    #[serde(default)]
+    pub timeout_timestamp: u64,
+}
+
+/// Upper case hex encoding of `bytes`.
+pub fn encode_upper(bytes: &[u8]) -> String {
+    const DIGITS: &[u8] = b"0123456789ABCDEF";
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        hex.push(DIGITS[(b >> 4) as usize] as char);
+        hex.push(DIGITS[(b & 0xf) as usize] as char);
+    }
+    hex
+}
+
+/// Decodes hex of either case, optionally `0x` prefixed.
+pub fn decode(hex: &[u8]) -> Option<Vec<u8>> {
+    let hex = if hex.starts_with(b"0x") {
+        &hex[2..]
+    } else {
+        hex
+    };
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    let digit = |c: u8| (c as char).to_digit(16).map(|d| d as u8);
+    hex.chunks(2)
+        .map(|pair| Some(digit(pair[0])? << 4 | digit(pair[1])?))
+        .collect()
+}
+
+/// Hex encoding of an account of this chain, as it appears in packet data.
+pub fn encode_account<A: Encode>(account: &A) -> String {
+    encode_upper(&account.encode())
+}
+
+pub fn decode_account<A: Decode>(account: &str) -> Option<A> {
+    let bytes = decode(account.as_bytes())?;
+    let mut input = &bytes[..];
+    let account = A::decode(&mut input).ok()?;
+    if !input.is_empty() {
+        return None;
+    }
+    Some(account)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_packet_data_as_ibc_go() {
+        let data = FungibleTokenPacketData {
+            amount: "100".into(),
+            denom: "transfer/channel-0/uatom".into(),
+            receiver: "0A00000000000000".into(),
+            sender: "cosmos1sender".into(),
+        };
+        assert_eq!(
+            data.to_bytes(),
+            br#"{"amount":"100","denom":"transfer/channel-0/uatom","receiver":"0A00000000000000","sender":"cosmos1sender"}"#.to_vec()
+        );
+        assert_eq!(
+            FungibleTokenPacketData::from_bytes(&data.to_bytes()),
+            Some(data.clone())
+        );
+        assert_eq!(data.amount(), Some(100));
+        let zero = FungibleTokenPacketData {
+            amount: "0".into(),
+            ..data
+        };
+        assert_eq!(zero.amount(), None);
+    }
+
+    #[test]
+    fn serializes_acknowledgements_as_ibc_go() {
+        assert_eq!(
+            Acknowledgement::success().to_bytes(),
+            br#"{"result":"AQ=="}"#.to_vec()
+        );
+        assert_eq!(
+            Acknowledgement::from_bytes(br#"{"error":"insufficient funds"}"#),
+            Some(Acknowledgement::error("insufficient funds"))
+        );
+        assert!(!Acknowledgement::error("").is_success());
+        assert_eq!(Acknowledgement::from_bytes(b"AQ=="), None);
+    }
+
+    #[test]
+    fn traces_denominations() {
+        let trace = DenomTrace::parse(b"transfer/channel-0/transfer/channel-5/uatom");
+        assert_eq!(
+            trace.path,
+            b"transfer/channel-0/transfer/channel-5".to_vec()
+        );
+        assert_eq!(trace.base_denom, b"uatom".to_vec());
+        assert_eq!(
+            trace.full_path(),
+            b"transfer/channel-0/transfer/channel-5/uatom".to_vec()
+        );
+        assert_eq!(DenomTrace::parse(b"uatom").full_path(), b"uatom".to_vec());
+
+        // As computed by ibc-go for the trace of uatom over transfer/channel-0
+        let trace = DenomTrace::parse(b"transfer/channel-0/uatom");
+        assert_eq!(
+            trace.ibc_denom(),
+            b"ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2".to_vec()
+        );
+        assert_eq!(parse_ibc_denom(&trace.ibc_denom()), Some(trace.hash()));
+        assert_eq!(parse_ibc_denom(b"ibc/27394F"), None);
+        assert_eq!(parse_ibc_denom(b"uatom"), None);
+    }
+
+    #[test]
+    fn encodes_accounts() {
+        assert_eq!(encode_account(&10u64), "0A00000000000000");
+        assert_eq!(decode_account::<u64>("0x0a00000000000000"), Some(10));
+        assert_eq!(decode_account::<u64>("0A000000000000"), None);
+        assert_eq!(decode_account::<u64>("0A0000000000000000"), None);
+        assert_eq!(decode_account::<u64>("cosmos1receiver"), None);
+    }
+}
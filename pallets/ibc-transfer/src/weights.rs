@@ -0,0 +1,37 @@
+//! Weights for ibc_transfer, over the length `l` of the submitted payload in bytes.
+//!
+//! Laid out as the benchmark CLI writes them for the benchmarks in `benchmarking.rs`. The values are estimates that
+//! have not been measured yet; regenerate them on reference hardware with
+//! `./target/release/wormhole benchmark` as described in the README.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for ibc_transfer.
+pub trait WeightInfo {
+    fn transfer(l: u32) -> Weight;
+}
+
+/// Weights for ibc_transfer using the wormhole node.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Trait> WeightInfo for SubstrateWeight<T> {
+    fn transfer(l: u32) -> Weight {
+        (70_000_000 as Weight)
+            .saturating_add((6_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(T::DbWeight::get().reads(9 as Weight))
+            .saturating_add(T::DbWeight::get().writes(5 as Weight))
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn transfer(l: u32) -> Weight {
+        (70_000_000 as Weight)
+            .saturating_add((6_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(RocksDbWeight::get().reads(9 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(5 as Weight))
+    }
+}
@@ -0,0 +1,45 @@
+//! Dispatches the extrinsics taking untrusted payloads with arbitrary bytes on the mock runtime, in
+//! which the client of the bundled 0.34 fixture exists, so that updates reach verification.
+
+#![no_main]
+
+use frame_support::assert_ok;
+use libfuzzer_sys::fuzz_target;
+use tendermint_client::mock::{new_test_ext, Origin, TemplateModule, Timestamp};
+use tendermint_client::types::TMUpdateClientPayload;
+
+const SIGNER: u64 = 1;
+const CREATE: &[u8] = include_bytes!("../../../../test_create_v034.json");
+const UPDATE: &[u8] = include_bytes!("../../../../test_update_v034.json");
+
+fuzz_target!(|data: &[u8]| {
+    new_test_ext().execute_with(|| {
+        // Within the trusting period of the fixture client, and past the time of its update
+        let update: TMUpdateClientPayload = serde_json::from_slice(UPDATE).unwrap();
+        let time = update.header.signed_header.header.time.timestamp_millis() as u64;
+        Timestamp::set_timestamp(time + 1_000);
+        assert_ok!(TemplateModule::init_client(
+            Origin::signed(SIGNER),
+            CREATE.to_vec()
+        ));
+
+        let origin = || Origin::signed(SIGNER);
+        let _ = TemplateModule::init_client(origin(), data.to_vec());
+        let _ = TemplateModule::update_client(origin(), data.to_vec());
+        let _ = TemplateModule::backfill_consensus_state(origin(), data.to_vec());
+        let _ = TemplateModule::upgrade_client(origin(), data.to_vec());
+        let _ = TemplateModule::update_client_proto(origin(), update.client_id, data.to_vec());
+        let _ = TemplateModule::conn_open_init(origin(), data.to_vec());
+        let _ = TemplateModule::conn_open_try(origin(), data.to_vec());
+        let _ = TemplateModule::conn_open_ack(origin(), data.to_vec());
+        let _ = TemplateModule::conn_open_confirm(origin(), data.to_vec());
+        let _ = TemplateModule::chan_open_init(origin(), data.to_vec());
+        let _ = TemplateModule::chan_open_try(origin(), data.to_vec());
+        let _ = TemplateModule::chan_open_ack(origin(), data.to_vec());
+        let _ = TemplateModule::chan_open_confirm(origin(), data.to_vec());
+        let _ = TemplateModule::chan_close_confirm(origin(), data.to_vec());
+        let _ = TemplateModule::recv_packet(origin(), data.to_vec());
+        let _ = TemplateModule::acknowledge_packet(origin(), data.to_vec());
+        let _ = TemplateModule::timeout_packet(origin(), data.to_vec());
+    });
+});
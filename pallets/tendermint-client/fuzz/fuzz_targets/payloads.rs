@@ -0,0 +1,52 @@
+//! Deserializes arbitrary bytes as each extrinsic payload, and runs the payloads that parse through
+//! the pallet's validation: create payloads are initialised, and update payloads are verified
+//! against the client of the bundled 0.34 fixture.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tendermint_client::types::{
+    Height, TMBackfillPayload, TMCreateClientPayload, TMUpdateClientPayload,
+    TMUpgradeClientPayload, TendermintClient,
+};
+use tendermint_client::validation::{client_from_payload, initialise_client, verify_update};
+
+const CREATE: &[u8] = include_bytes!("../../../../test_create_v034.json");
+
+/// Client of the `CREATE` fixture, and its height.
+fn fixture_client() -> (TendermintClient, Height) {
+    let payload: TMCreateClientPayload = serde_json::from_slice(CREATE).unwrap();
+    let (mut client, header) = client_from_payload(payload);
+    let now = header.signed_header.header.time;
+    let height = initialise_client(&mut client, header, now).unwrap();
+    (client, height)
+}
+
+fuzz_target!(|data: &[u8]| {
+    // Signatures are verified through host functions, which need externalities
+    sp_io::TestExternalities::default().execute_with(|| {
+        if let Ok(payload) = serde_json::from_slice::<TMCreateClientPayload>(data) {
+            let (mut client, header) = client_from_payload(payload);
+            let now = header.signed_header.header.time;
+            let _ = initialise_client(&mut client, header, now);
+        }
+
+        if let Ok(payload) = serde_json::from_slice::<TMUpdateClientPayload>(data) {
+            let (client, height) = fixture_client();
+            let trusted_state = &client.state.as_ref().unwrap().state;
+            let now = payload.header.signed_header.header.time;
+            let _ = verify_update(
+                &client,
+                trusted_state,
+                height,
+                &payload.header,
+                Some(&payload.next_validator_set),
+                None,
+                now,
+            );
+        }
+
+        let _ = serde_json::from_slice::<TMBackfillPayload>(data);
+        let _ = serde_json::from_slice::<TMUpgradeClientPayload>(data);
+    });
+});
@@ -0,0 +1,33 @@
+//! Decodes arbitrary bytes as each value the pallet keeps in storage, asserting that decoding fails
+//! rather than panics, and that decoded values encode back to decodable bytes.
+
+#![no_main]
+
+use codec::{Decode, Encode};
+use libfuzzer_sys::fuzz_target;
+use tendermint_client::channel::{ChannelEnd, Packet};
+use tendermint_client::connection::ConnectionEnd;
+use tendermint_client::types::{
+    ConsensusStateWrapper, Height, TMClientInfo, TMClientStorageWrapper,
+};
+
+fn roundtrip<T: Decode + Encode>(data: &[u8]) {
+    if let Ok(value) = T::decode(&mut &data[..]) {
+        let encoded = value.encode();
+        assert!(
+            T::decode(&mut &encoded[..]).is_ok(),
+            "encoded value does not decode"
+        );
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    roundtrip::<TMClientStorageWrapper>(data);
+    roundtrip::<ConsensusStateWrapper>(data);
+    roundtrip::<TMClientInfo>(data);
+    roundtrip::<Vec<Height>>(data);
+    roundtrip::<Vec<Vec<u8>>>(data);
+    roundtrip::<ConnectionEnd>(data);
+    roundtrip::<ChannelEnd>(data);
+    roundtrip::<Packet>(data);
+});
@@ -0,0 +1,169 @@
+//! RPC interface for the Wormhole TendermintClient pallet.
+
+use std::sync::Arc;
+
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sc_client_api::ProofProvider;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use tendermint_client::host;
+use tendermint_client::types::{Height, RetentionPolicy};
+
+pub use tendermint_client_runtime_api::TendermintClientApi as TendermintClientRuntimeApi;
+
+#[rpc]
+pub trait TendermintClientApi<BlockHash> {
+    /// Returns the client_id of the canonical client for `chain_id`.
+    #[rpc(name = "tendermintClient_canonicalClient")]
+    fn canonical_client(&self, chain_id: Bytes, at: Option<BlockHash>) -> Result<Option<Bytes>>;
+
+    /// Returns the latest height of client `client_id`.
+    #[rpc(name = "tendermintClient_latestHeight")]
+    fn latest_height(&self, client_id: Bytes, at: Option<BlockHash>) -> Result<Option<Height>>;
+
+    /// Returns the rules the consensus states of client `client_id` are pruned by.
+    #[rpc(name = "tendermintClient_retentionPolicy")]
+    fn retention_policy(
+        &self,
+        client_id: Bytes,
+        at: Option<BlockHash>,
+    ) -> Result<Option<RetentionPolicy>>;
+
+    /// Returns the value at ICS-24 `path` in the host store, with a read proof of it against the
+    /// state root of the block.
+    #[rpc(name = "tendermintClient_hostCommitment")]
+    fn host_commitment(
+        &self,
+        path: Bytes,
+        at: Option<BlockHash>,
+    ) -> Result<HostCommitment<BlockHash>>;
+}
+
+/// Value at an ICS-24 path of the host store, and the proof of it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostCommitment<Hash> {
+    /// Block the value is read and proven at.
+    pub at: Hash,
+    /// Storage key of the path.
+    pub key: Bytes,
+    /// Value at the path, if any.
+    pub value: Option<Bytes>,
+    /// Trie nodes proving the value at the key, or its absence, against the state root of `at`.
+    pub proof: Vec<Bytes>,
+}
+
+/// Error code returned when the runtime API call fails.
+const RUNTIME_ERROR: i64 = 1;
+/// Error code returned when a read proof cannot be generated.
+const PROOF_ERROR: i64 = 2;
+
+/// Implements the `TendermintClientApi` RPC trait by calling into the runtime.
+pub struct TendermintClient<C, B> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> TendermintClient<C, B> {
+    /// Create new `TendermintClient` with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        TendermintClient {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+fn runtime_error(message: &str, e: impl std::fmt::Debug) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(RUNTIME_ERROR),
+        message: message.into(),
+        data: Some(format!("{:?}", e).into()),
+    }
+}
+
+fn proof_error(message: &str, e: impl std::fmt::Debug) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(PROOF_ERROR),
+        message: message.into(),
+        data: Some(format!("{:?}", e).into()),
+    }
+}
+
+impl<C, Block> TendermintClientApi<<Block as BlockT>::Hash> for TendermintClient<C, Block>
+where
+    Block: BlockT,
+    C: Send
+        + Sync
+        + 'static
+        + ProvideRuntimeApi<Block>
+        + HeaderBackend<Block>
+        + ProofProvider<Block>,
+    C::Api: TendermintClientRuntimeApi<Block>,
+{
+    fn canonical_client(
+        &self,
+        chain_id: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<Bytes>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.canonical_client(&at, chain_id.to_vec())
+            .map(|client_id| client_id.map(Into::into))
+            .map_err(|e| runtime_error("Unable to query canonical client.", e))
+    }
+
+    fn latest_height(
+        &self,
+        client_id: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<Height>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.latest_height(&at, client_id.to_vec())
+            .map_err(|e| runtime_error("Unable to query latest height.", e))
+    }
+
+    fn retention_policy(
+        &self,
+        client_id: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<RetentionPolicy>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.retention_policy(&at, client_id.to_vec())
+            .map_err(|e| runtime_error("Unable to query retention policy.", e))
+    }
+
+    fn host_commitment(
+        &self,
+        path: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<HostCommitment<<Block as BlockT>::Hash>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        let key = host::key(&path);
+
+        let value = api
+            .host_commitment(&BlockId::hash(at), path.to_vec())
+            .map_err(|e| runtime_error("Unable to query host commitment.", e))?;
+        let proof = self
+            .client
+            .read_proof(&BlockId::hash(at), &mut std::iter::once(&key[..]))
+            .map_err(|e| proof_error("Unable to prove host commitment.", e))?;
+
+        Ok(HostCommitment {
+            at,
+            key: key.into(),
+            value: value.map(Into::into),
+            proof: proof.iter_nodes().map(Into::into).collect(),
+        })
+    }
+}
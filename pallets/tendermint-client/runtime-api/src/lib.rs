@@ -0,0 +1,30 @@
+//! Runtime API definition for the Wormhole TendermintClient pallet.
+//! Lets the node query client state without decoding raw storage.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sp_std::vec::Vec;
+use tendermint_client::self_client::{SelfClientError, SelfClientState, SelfConsensusState};
+use tendermint_client::types::{Height, RetentionPolicy};
+
+sp_api::decl_runtime_apis! {
+    pub trait TendermintClientApi {
+        /// Returns the client_id of the canonical client for `chain_id`, if governance set one.
+        fn canonical_client(chain_id: Vec<u8>) -> Option<Vec<u8>>;
+        /// Returns the latest height `client_id` has been updated to, if the client exists.
+        fn latest_height(client_id: Vec<u8>) -> Option<Height>;
+        /// Returns the rules the consensus states of `client_id` are pruned by, if the client exists.
+        fn retention_policy(client_id: Vec<u8>) -> Option<RetentionPolicy>;
+        /// Returns the value committed to at ICS-24 `path` in the host store, if any.
+        fn host_commitment(path: Vec<u8>) -> Option<Vec<u8>>;
+        /// Returns the consensus state of this chain at `height`, if still kept.
+        fn self_consensus_state(height: Height) -> Option<SelfConsensusState>;
+        /// Validates a counterparty's client of this chain: its `client_state`, and its `consensus_state` at
+        /// `consensus_height`.
+        fn validate_self_client(
+            client_state: SelfClientState,
+            consensus_state: SelfConsensusState,
+            consensus_height: Height,
+        ) -> Result<(), SelfClientError>;
+    }
+}
@@ -0,0 +1,181 @@
+//! Benchmarks of the tendermint-client pallet, over the number of validators signing headers.
+
+use super::*;
+
+use frame_benchmarking::{benchmarks, whitelisted_caller};
+use frame_system::RawOrigin;
+use sp_runtime::traits::UniqueSaturatedFrom;
+
+use crate::testing::{any, validators, TestChain, TestStore};
+
+/// Size of the active validator set of the largest Cosmos chains.
+const MAX_VALIDATORS: u32 = 150;
+
+const CHAIN_ID: &str = "benchchain-1";
+
+/// Client id of the client tracking a chain of `validators` validators.
+fn client_id(validators: u32) -> Vec<u8> {
+    [&b"benchclient-"[..], validators.to_string().as_bytes()].concat()
+}
+
+fn test_chain(validators_count: u32) -> TestChain {
+    TestChain::new(CHAIN_ID, validators(validators_count, 10))
+}
+
+fn create_payload(chain: &TestChain) -> Vec<u8> {
+    let payload = chain.create_payload(client_id(chain.validators.len() as u32), 86400);
+    serde_json::to_vec(&payload).expect("payload is serializable")
+}
+
+fn update_payload(chain: &TestChain) -> Vec<u8> {
+    let payload = chain.update_payload(client_id(chain.validators.len() as u32));
+    serde_json::to_vec(&payload).expect("payload is serializable")
+}
+
+/// Protobuf encoded client state, consensus state and header creating a client of `chain`.
+fn proto_create_args(chain: &TestChain) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    (
+        any(proto::TENDERMINT_CLIENT_STATE_TYPE_URL, &chain.proto_client_state(86400, &[])),
+        any(proto::TENDERMINT_CONSENSUS_STATE_TYPE_URL, &chain.proto_consensus_state()),
+        any(proto::TENDERMINT_HEADER_TYPE_URL, &chain.proto_header(None, None)),
+    )
+}
+
+/// Upgrade path of the clients being upgraded: store and key the upgraded states are committed under.
+const UPGRADE_PATH: &[&str] = &["ibc", "upgradedIBCState"];
+
+/// Payload upgrading the client of `chain`, created at height 1, to `upgraded`, along with the store
+/// `chain` commits to the upgraded states in.
+fn upgrade_args(chain: &TestChain, upgraded: &TestChain) -> (TestStore, TMUpgradeClientPayload) {
+    let client_state = any(proto::TENDERMINT_CLIENT_STATE_TYPE_URL, &upgraded.proto_client_state(86400, UPGRADE_PATH));
+    let consensus_state = any(proto::TENDERMINT_CONSENSUS_STATE_TYPE_URL, &upgraded.proto_consensus_state());
+    let mut store = TestStore::new();
+    store.set(b"upgradedIBCState/1/upgradedClient".to_vec(), client_state.clone());
+    store.set(b"upgradedIBCState/1/upgradedConsState".to_vec(), consensus_state.clone());
+    let payload = TMUpgradeClientPayload {
+        client_id: client_id(chain.validators.len() as u32),
+        upgraded_client_state: client_state,
+        upgraded_consensus_state: consensus_state,
+        proof_upgrade_client: store.prove(b"upgradedIBCState/1/upgradedClient"),
+        proof_upgrade_consensus_state: store.prove(b"upgradedIBCState/1/upgradedConsState"),
+        header: upgraded.header(),
+    };
+    (store, payload)
+}
+
+/// Moves the block time to just after the current header of `chain`.
+fn set_time<T: Trait>(chain: &TestChain) {
+    let millis = chain.time.timestamp_millis() as u64 + 500;
+    pallet_timestamp::Module::<T>::set_timestamp(T::Moment::unique_saturated_from(millis));
+}
+
+benchmarks! {
+    _ { }
+
+    init_client {
+        let v in 1 .. MAX_VALIDATORS;
+        let caller: T::AccountId = whitelisted_caller();
+        let chain = test_chain(v);
+        let payload = create_payload(&chain);
+        set_time::<T>(&chain);
+    }: _(RawOrigin::Signed(caller), payload)
+    verify {
+        assert_eq!(Module::<T>::latest_height(&client_id(v)), Some(chain.ibc_height()));
+    }
+
+    update_client {
+        let v in 1 .. MAX_VALIDATORS;
+        let caller: T::AccountId = whitelisted_caller();
+        let mut chain = test_chain(v);
+        set_time::<T>(&chain);
+        Module::<T>::init_client(RawOrigin::Signed(caller.clone()).into(), create_payload(&chain))?;
+        chain.advance(1, 1);
+        let payload = update_payload(&chain);
+        set_time::<T>(&chain);
+    }: _(RawOrigin::Signed(caller), payload)
+    verify {
+        assert_eq!(Module::<T>::latest_height(&client_id(v)), Some(chain.ibc_height()));
+    }
+
+    init_client_proto {
+        let v in 1 .. MAX_VALIDATORS;
+        let caller: T::AccountId = whitelisted_caller();
+        let chain = test_chain(v);
+        let (client_state, consensus_state, header) = proto_create_args(&chain);
+        set_time::<T>(&chain);
+    }: _(RawOrigin::Signed(caller), client_id(v), client_state, consensus_state, header)
+    verify {
+        assert_eq!(Module::<T>::latest_height(&client_id(v)), Some(chain.ibc_height()));
+    }
+
+    update_client_proto {
+        let v in 1 .. MAX_VALIDATORS;
+        let caller: T::AccountId = whitelisted_caller();
+        let mut chain = test_chain(v);
+        set_time::<T>(&chain);
+        Module::<T>::init_client(RawOrigin::Signed(caller.clone()).into(), create_payload(&chain))?;
+        let trusted_height = chain.ibc_height();
+        let trusted_validators = chain.validators.clone();
+        chain.advance(1, 1);
+        let header = chain.proto_header(Some(trusted_height), Some(&trusted_validators));
+        let header = any(proto::TENDERMINT_HEADER_TYPE_URL, &header);
+        set_time::<T>(&chain);
+    }: _(RawOrigin::Signed(caller), client_id(v), header)
+    verify {
+        assert_eq!(Module::<T>::latest_height(&client_id(v)), Some(chain.ibc_height()));
+    }
+
+    upgrade_client {
+        let v in 1 .. MAX_VALIDATORS;
+        let caller: T::AccountId = whitelisted_caller();
+        let mut chain = test_chain(v);
+        let upgraded = TestChain::new("benchchain-2", validators(v, 10));
+        let (store, payload) = upgrade_args(&chain, &upgraded);
+        chain.app_hash = store.root();
+        let mut create = chain.create_payload(client_id(v), 86400);
+        create.upgrade_path = UPGRADE_PATH.iter().map(|p| p.to_string()).collect();
+        set_time::<T>(&chain);
+        Module::<T>::init_client(RawOrigin::Signed(caller.clone()).into(), serde_json::to_vec(&create).expect("payload is serializable"))?;
+        let payload = serde_json::to_vec(&payload).expect("payload is serializable");
+    }: _(RawOrigin::Signed(caller), payload)
+    verify {
+        assert_eq!(Module::<T>::latest_height(&client_id(v)), Some(upgraded.ibc_height()));
+    }
+
+    // Signature checks alone, without payload decoding and storage access.
+    verify_commit {
+        let v in 1 .. MAX_VALIDATORS;
+        let header = test_chain(v).header();
+    }: {
+        validate_initial_signed_header_and_valset(&header.signed_header, &header.validator_set).map_err(|_| "invalid commit")?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_init_client::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_update_client::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_init_client_proto::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_update_client_proto::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_upgrade_client::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_verify_commit::<Test>());
+        });
+    }
+}
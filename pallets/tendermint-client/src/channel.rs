@@ -0,0 +1,1213 @@
+//! ICS-04 channels over the connections of `connection`, and the packets sent over them.
+//!
+//! As for connections, the steps are checked here without storage: each step is given the channel end
+//! it moves, the connection the channel runs over and the root the counterparty committed to at the
+//! proof height. The pallet looks up and stores the channel ends, sequences and packet commitments,
+//! and calls the module bound to the channel's port.
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sp_std::vec::Vec;
+
+use crate::commitment::{self, CommitmentError};
+use crate::connection::{self, ConnectionEnd, ConnectionState};
+use crate::proto;
+use crate::types::Height;
+
+/// Reasons a channel or packet step can be rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChannelError {
+    /// Identifier is not a valid ICS-24 identifier.
+    InvalidIdentifier,
+    /// Channel is not in the state the step moves it from.
+    InvalidState,
+    /// Connection of the channel is not open.
+    ConnectionNotOpen,
+    /// Ordering is not supported by the connection.
+    OrderingNotSupported,
+    /// Packet is not sent over the channel, or has no timeout.
+    InvalidPacket,
+    /// Timeout of the packet has passed.
+    PacketTimedOut,
+    /// Timeout of the packet has not passed on the counterparty at the proof height.
+    PacketNotTimedOut,
+    /// Sequence of the packet is not the next one of the ordered channel.
+    InvalidSequence,
+    /// Packet was already received on the unordered channel.
+    PacketAlreadyReceived,
+    /// Packet is not committed to by this chain: it was not sent, or was already acknowledged or
+    /// timed out.
+    PacketNotCommitted,
+    /// Proof is not a protobuf encoded `MerkleProof`.
+    MalformedProof,
+    /// Counterparty channel end is not the expected one.
+    InvalidChannelProof(CommitmentError),
+    /// Counterparty state of the packet is not the expected one.
+    InvalidPacketProof(CommitmentError),
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order {
+    Unordered,
+    Ordered,
+}
+
+impl Order {
+    /// Connection version feature of the ordering.
+    fn feature(self) -> &'static str {
+        match self {
+            Order::Unordered => "ORDER_UNORDERED",
+            Order::Ordered => "ORDER_ORDERED",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelState {
+    Init,
+    TryOpen,
+    Open,
+    Closed,
+}
+
+/// Other end of a channel. `channel_id` is empty until the counterparty opened its end.
+#[derive(Serialize, Deserialize, Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct ChannelCounterparty {
+    pub port_id: Vec<u8>,
+    #[serde(default)]
+    pub channel_id: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct ChannelEnd {
+    pub state: ChannelState,
+    pub ordering: Order,
+    pub counterparty: ChannelCounterparty,
+    /// Connection the channel runs over. Multi-hop channels are not supported.
+    pub connection_hops: Vec<Vec<u8>>,
+    /// Version of the application protocol, as agreed by the modules of both ends.
+    pub version: Vec<u8>,
+}
+
+impl ChannelEnd {
+    /// Connection the channel runs over.
+    pub fn connection_id(&self) -> &[u8] {
+        &self.connection_hops[0]
+    }
+
+    /// Protobuf `ibc.core.channel.v1.Channel` committed to under
+    /// `channelEnds/ports/{port}/channels/{id}`.
+    pub fn to_proto(&self) -> proto::Channel {
+        let state = match self.state {
+            ChannelState::Init => proto::ChannelState::Init,
+            ChannelState::TryOpen => proto::ChannelState::TryOpen,
+            ChannelState::Open => proto::ChannelState::Open,
+            ChannelState::Closed => proto::ChannelState::Closed,
+        };
+        let ordering = match self.ordering {
+            Order::Unordered => proto::Order::Unordered,
+            Order::Ordered => proto::Order::Ordered,
+        };
+        proto::Channel {
+            state: state as i32,
+            ordering: ordering as i32,
+            counterparty: Some(proto::ChannelCounterparty {
+                port_id: string(&self.counterparty.port_id),
+                channel_id: string(&self.counterparty.channel_id),
+            }),
+            connection_hops: self.connection_hops.iter().map(|hop| string(hop)).collect(),
+            version: string(&self.version),
+        }
+    }
+}
+
+/// Packet sent from a channel end to its counterparty. Packets time out once the receiving chain
+/// reaches `timeout_height`, or its time reaches `timeout_timestamp` (in nanoseconds since the Unix
+/// epoch). Zero values disable either timeout.
+#[derive(Serialize, Deserialize, Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct Packet {
+    pub sequence: u64,
+    pub source_port: Vec<u8>,
+    pub source_channel: Vec<u8>,
+    pub destination_port: Vec<u8>,
+    pub destination_channel: Vec<u8>,
+    pub data: Vec<u8>,
+    #[serde(default)]
+    pub timeout_height: Height,
+    #[serde(default)]
+    pub timeout_timestamp: u64,
+}
+
+impl Packet {
+    /// Commitment to the packet stored by the sending chain, as in ibc-go: the hash of its timeout
+    /// and of the hash of its data.
+    pub fn commitment(&self) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.input(&self.timeout_timestamp.to_be_bytes());
+        hasher.input(&self.timeout_height.revision_number.to_be_bytes());
+        hasher.input(&self.timeout_height.revision_height.to_be_bytes());
+        hasher.input(&Sha256::digest(&self.data));
+        hasher.result().to_vec()
+    }
+
+    /// Whether the packet timed out on a receiving chain at `height` and `timestamp`.
+    pub fn timed_out(&self, height: Height, timestamp: u64) -> bool {
+        (self.timeout_height != Height::default() && height >= self.timeout_height)
+            || (self.timeout_timestamp != 0 && timestamp >= self.timeout_timestamp)
+    }
+}
+
+/// Commitment to the acknowledgement of a packet stored by the receiving chain.
+pub fn acknowledgement_commitment(acknowledgement: &[u8]) -> Vec<u8> {
+    Sha256::digest(acknowledgement).to_vec()
+}
+
+/// Value stored under the receipt path of packets received on unordered channels.
+pub const RECEIPT: &[u8] = &[1];
+
+/// Opens a channel of `port_id` over `connection_id` to the counterparty port.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChanOpenInitPayload {
+    pub port_id: Vec<u8>,
+    pub connection_id: Vec<u8>,
+    pub ordering: Order,
+    pub counterparty_port_id: Vec<u8>,
+    pub version: Vec<u8>,
+}
+
+/// Opens a channel of `port_id` in answer to the counterparty's `ChanOpenInit`. The proof is a
+/// protobuf encoded ICS-23 `MerkleProof`, at `proof_height` of the counterparty.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChanOpenTryPayload {
+    pub port_id: Vec<u8>,
+    pub connection_id: Vec<u8>,
+    pub ordering: Order,
+    pub counterparty: ChannelCounterparty,
+    pub counterparty_version: Vec<u8>,
+    pub proof_height: Height,
+    pub proof_init: Vec<u8>,
+}
+
+/// Acknowledges that the counterparty opened its end of the channel with `counterparty_version`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChanOpenAckPayload {
+    pub port_id: Vec<u8>,
+    pub channel_id: Vec<u8>,
+    pub counterparty_channel_id: Vec<u8>,
+    pub counterparty_version: Vec<u8>,
+    pub proof_height: Height,
+    pub proof_try: Vec<u8>,
+}
+
+/// Confirms that the counterparty opened its end of the channel.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChanOpenConfirmPayload {
+    pub port_id: Vec<u8>,
+    pub channel_id: Vec<u8>,
+    pub proof_height: Height,
+    pub proof_ack: Vec<u8>,
+}
+
+/// Closes the channel once the counterparty closed its end.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChanCloseConfirmPayload {
+    pub port_id: Vec<u8>,
+    pub channel_id: Vec<u8>,
+    pub proof_height: Height,
+    pub proof_init: Vec<u8>,
+}
+
+/// Receives a packet committed to by the counterparty.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecvPacketPayload {
+    pub packet: Packet,
+    pub proof_height: Height,
+    pub proof_commitment: Vec<u8>,
+}
+
+/// Acknowledges a packet sent by this chain with the counterparty's acknowledgement.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AcknowledgePacketPayload {
+    pub packet: Packet,
+    pub acknowledgement: Vec<u8>,
+    pub proof_height: Height,
+    pub proof_acked: Vec<u8>,
+}
+
+/// Times out a packet sent by this chain that the counterparty did not receive in time. On ordered
+/// channels, `proof_unreceived` proves the counterparty's next receive sequence to be
+/// `next_sequence_recv`, and on unordered channels it proves the absence of the packet's receipt.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TimeoutPacketPayload {
+    pub packet: Packet,
+    #[serde(default)]
+    pub next_sequence_recv: u64,
+    pub proof_height: Height,
+    pub proof_unreceived: Vec<u8>,
+}
+
+/// Channel id of the channel of sequence `sequence`.
+pub fn channel_id(sequence: u64) -> Vec<u8> {
+    [&b"channel-"[..], sequence.to_string().as_bytes()].concat()
+}
+
+fn channel_prefix(port_id: &[u8], channel_id: &[u8]) -> Vec<u8> {
+    [&b"ports/"[..], port_id, b"/channels/", channel_id].concat()
+}
+
+fn sequence_path(kind: &[u8], port_id: &[u8], channel_id: &[u8], sequence: u64) -> Vec<u8> {
+    [
+        kind,
+        b"/",
+        &channel_prefix(port_id, channel_id),
+        b"/sequences/",
+        sequence.to_string().as_bytes(),
+    ]
+    .concat()
+}
+
+/// ICS-24 path of a channel end.
+pub fn channel_path(port_id: &[u8], channel_id: &[u8]) -> Vec<u8> {
+    [&b"channelEnds/"[..], &channel_prefix(port_id, channel_id)].concat()
+}
+
+/// ICS-24 path of the next sequence a channel end receives.
+pub fn next_sequence_recv_path(port_id: &[u8], channel_id: &[u8]) -> Vec<u8> {
+    [
+        &b"nextSequenceRecv/"[..],
+        &channel_prefix(port_id, channel_id),
+    ]
+    .concat()
+}
+
+/// ICS-24 path of the commitment to a packet sent from a channel end.
+pub fn packet_commitment_path(port_id: &[u8], channel_id: &[u8], sequence: u64) -> Vec<u8> {
+    sequence_path(b"commitments", port_id, channel_id, sequence)
+}
+
+/// ICS-24 path of the receipt of a packet received on an unordered channel end.
+pub fn packet_receipt_path(port_id: &[u8], channel_id: &[u8], sequence: u64) -> Vec<u8> {
+    sequence_path(b"receipts", port_id, channel_id, sequence)
+}
+
+/// ICS-24 path of the commitment to the acknowledgement of a packet received on a channel end.
+pub fn packet_acknowledgement_path(port_id: &[u8], channel_id: &[u8], sequence: u64) -> Vec<u8> {
+    sequence_path(b"acks", port_id, channel_id, sequence)
+}
+
+fn validate_port_id(id: &[u8]) -> Result<(), ChannelError> {
+    connection::validate_identifier(id, 2, 128).map_err(|_| ChannelError::InvalidIdentifier)
+}
+
+fn validate_channel_id(id: &[u8]) -> Result<(), ChannelError> {
+    connection::validate_identifier(id, 8, 64).map_err(|_| ChannelError::InvalidIdentifier)
+}
+
+fn ensure_open(connection: &ConnectionEnd) -> Result<(), ChannelError> {
+    if connection.state != ConnectionState::Open {
+        return Err(ChannelError::ConnectionNotOpen);
+    }
+    Ok(())
+}
+
+fn decode_proof(proof: &[u8]) -> Result<proto::MerkleProof, ChannelError> {
+    connection::decode_proof(proof).map_err(|_| ChannelError::MalformedProof)
+}
+
+/// Verifies that the packet state `value` is stored under `path` in the store of the connection's
+/// counterparty.
+fn verify_packet(
+    connection: &ConnectionEnd,
+    root: &[u8],
+    path: Vec<u8>,
+    value: &[u8],
+    proof: &[u8],
+) -> Result<(), ChannelError> {
+    let proof = decode_proof(proof)?;
+    connection::verify(root, &connection.counterparty.prefix, path, value, &proof)
+        .map_err(ChannelError::InvalidPacketProof)
+}
+
+fn verify_channel(
+    connection: &ConnectionEnd,
+    root: &[u8],
+    counterparty: &ChannelCounterparty,
+    expected: &ChannelEnd,
+    proof: &[u8],
+) -> Result<(), ChannelError> {
+    let proof = decode_proof(proof)?;
+    connection::verify(
+        root,
+        &connection.counterparty.prefix,
+        channel_path(&counterparty.port_id, &counterparty.channel_id),
+        &proto::encode(&expected.to_proto()),
+        &proof,
+    )
+    .map_err(ChannelError::InvalidChannelProof)
+}
+
+/// Counterparty's end of a channel of `port_id` and `channel_id` over `connection`.
+fn counterparty_end(
+    end: &ChannelEnd,
+    state: ChannelState,
+    connection: &ConnectionEnd,
+    port_id: &[u8],
+    channel_id: &[u8],
+    version: &[u8],
+) -> ChannelEnd {
+    ChannelEnd {
+        state,
+        ordering: end.ordering,
+        counterparty: ChannelCounterparty {
+            port_id: port_id.to_vec(),
+            channel_id: channel_id.to_vec(),
+        },
+        connection_hops: vec![connection.counterparty.connection_id.clone()],
+        version: version.to_vec(),
+    }
+}
+
+fn check_ordering(connection: &ConnectionEnd, ordering: Order) -> Result<(), ChannelError> {
+    let supported = connection
+        .versions
+        .iter()
+        .any(|v| v.features.iter().any(|f| f == ordering.feature()));
+    if !supported {
+        return Err(ChannelError::OrderingNotSupported);
+    }
+    Ok(())
+}
+
+/// End of a channel opened by `ChanOpenInit` over `connection`.
+pub fn open_init(
+    connection: &ConnectionEnd,
+    payload: &ChanOpenInitPayload,
+) -> Result<ChannelEnd, ChannelError> {
+    validate_port_id(&payload.port_id)?;
+    validate_port_id(&payload.counterparty_port_id)?;
+    check_ordering(connection, payload.ordering)?;
+    Ok(ChannelEnd {
+        state: ChannelState::Init,
+        ordering: payload.ordering,
+        counterparty: ChannelCounterparty {
+            port_id: payload.counterparty_port_id.clone(),
+            channel_id: Vec::new(),
+        },
+        connection_hops: vec![payload.connection_id.clone()],
+        version: payload.version.clone(),
+    })
+}
+
+/// End of a channel opened by `ChanOpenTry` over `connection`, once the counterparty's end is
+/// proven to be in `Init` under `root`. The version is the counterparty's until the module bound to
+/// the port picks its own.
+pub fn open_try(
+    connection: &ConnectionEnd,
+    payload: &ChanOpenTryPayload,
+    root: &[u8],
+) -> Result<ChannelEnd, ChannelError> {
+    validate_port_id(&payload.port_id)?;
+    validate_port_id(&payload.counterparty.port_id)?;
+    validate_channel_id(&payload.counterparty.channel_id)?;
+    ensure_open(connection)?;
+    check_ordering(connection, payload.ordering)?;
+
+    let end = ChannelEnd {
+        state: ChannelState::TryOpen,
+        ordering: payload.ordering,
+        counterparty: payload.counterparty.clone(),
+        connection_hops: vec![payload.connection_id.clone()],
+        version: payload.counterparty_version.clone(),
+    };
+    let expected = counterparty_end(
+        &end,
+        ChannelState::Init,
+        connection,
+        &payload.port_id,
+        b"",
+        &payload.counterparty_version,
+    );
+    verify_channel(
+        connection,
+        root,
+        &payload.counterparty,
+        &expected,
+        &payload.proof_init,
+    )?;
+    Ok(end)
+}
+
+/// `end` opened by `ChanOpenAck`, once the counterparty's end is proven to be in `TryOpen` under
+/// `root`.
+pub fn open_ack(
+    end: &ChannelEnd,
+    connection: &ConnectionEnd,
+    payload: &ChanOpenAckPayload,
+    root: &[u8],
+) -> Result<ChannelEnd, ChannelError> {
+    if end.state != ChannelState::Init {
+        return Err(ChannelError::InvalidState);
+    }
+    validate_channel_id(&payload.counterparty_channel_id)?;
+    ensure_open(connection)?;
+
+    let counterparty = ChannelCounterparty {
+        port_id: end.counterparty.port_id.clone(),
+        channel_id: payload.counterparty_channel_id.clone(),
+    };
+    let expected = counterparty_end(
+        end,
+        ChannelState::TryOpen,
+        connection,
+        &payload.port_id,
+        &payload.channel_id,
+        &payload.counterparty_version,
+    );
+    verify_channel(
+        connection,
+        root,
+        &counterparty,
+        &expected,
+        &payload.proof_try,
+    )?;
+
+    Ok(ChannelEnd {
+        state: ChannelState::Open,
+        counterparty,
+        version: payload.counterparty_version.clone(),
+        ..end.clone()
+    })
+}
+
+/// `end` opened by `ChanOpenConfirm`, once the counterparty's end is proven to be open under
+/// `root`.
+pub fn open_confirm(
+    end: &ChannelEnd,
+    connection: &ConnectionEnd,
+    payload: &ChanOpenConfirmPayload,
+    root: &[u8],
+) -> Result<ChannelEnd, ChannelError> {
+    if end.state != ChannelState::TryOpen {
+        return Err(ChannelError::InvalidState);
+    }
+    ensure_open(connection)?;
+
+    let expected = counterparty_end(
+        end,
+        ChannelState::Open,
+        connection,
+        &payload.port_id,
+        &payload.channel_id,
+        &end.version,
+    );
+    verify_channel(
+        connection,
+        root,
+        &end.counterparty,
+        &expected,
+        &payload.proof_ack,
+    )?;
+
+    Ok(ChannelEnd {
+        state: ChannelState::Open,
+        ..end.clone()
+    })
+}
+
+/// `end` closed by `ChanCloseInit`.
+pub fn close_init(
+    end: &ChannelEnd,
+    connection: &ConnectionEnd,
+) -> Result<ChannelEnd, ChannelError> {
+    if end.state == ChannelState::Closed {
+        return Err(ChannelError::InvalidState);
+    }
+    ensure_open(connection)?;
+    Ok(ChannelEnd {
+        state: ChannelState::Closed,
+        ..end.clone()
+    })
+}
+
+/// `end` closed by `ChanCloseConfirm`, once the counterparty's end is proven to be closed under
+/// `root`.
+pub fn close_confirm(
+    end: &ChannelEnd,
+    connection: &ConnectionEnd,
+    payload: &ChanCloseConfirmPayload,
+    root: &[u8],
+) -> Result<ChannelEnd, ChannelError> {
+    if end.state == ChannelState::Closed {
+        return Err(ChannelError::InvalidState);
+    }
+    ensure_open(connection)?;
+
+    let expected = counterparty_end(
+        end,
+        ChannelState::Closed,
+        connection,
+        &payload.port_id,
+        &payload.channel_id,
+        &end.version,
+    );
+    verify_channel(
+        connection,
+        root,
+        &end.counterparty,
+        &expected,
+        &payload.proof_init,
+    )?;
+
+    Ok(ChannelEnd {
+        state: ChannelState::Closed,
+        ..end.clone()
+    })
+}
+
+/// Checks that `packet` can be sent over `end`, to a counterparty whose latest height and time
+/// known to this chain are `latest_height` and `latest_timestamp`.
+pub fn send_packet(
+    end: &ChannelEnd,
+    packet: &Packet,
+    latest_height: Height,
+    latest_timestamp: u64,
+) -> Result<(), ChannelError> {
+    if end.state != ChannelState::Open {
+        return Err(ChannelError::InvalidState);
+    }
+    if packet.destination_port != end.counterparty.port_id
+        || packet.destination_channel != end.counterparty.channel_id
+        || (packet.timeout_height == Height::default() && packet.timeout_timestamp == 0)
+    {
+        return Err(ChannelError::InvalidPacket);
+    }
+    if packet.timed_out(latest_height, latest_timestamp) {
+        return Err(ChannelError::PacketTimedOut);
+    }
+    Ok(())
+}
+
+/// Checks that the packet of `payload` can be received on `end` at this chain's `height` and
+/// `timestamp`, and is committed to by the counterparty under `root`. On ordered channels the
+/// packet must be the next one, and on unordered channels it must not have been `received`.
+#[allow(clippy::too_many_arguments)]
+pub fn recv_packet(
+    end: &ChannelEnd,
+    connection: &ConnectionEnd,
+    payload: &RecvPacketPayload,
+    next_sequence_recv: u64,
+    received: bool,
+    height: Height,
+    timestamp: u64,
+    root: &[u8],
+) -> Result<(), ChannelError> {
+    let packet = &payload.packet;
+    if end.state != ChannelState::Open {
+        return Err(ChannelError::InvalidState);
+    }
+    ensure_open(connection)?;
+    if packet.source_port != end.counterparty.port_id
+        || packet.source_channel != end.counterparty.channel_id
+    {
+        return Err(ChannelError::InvalidPacket);
+    }
+    if packet.timed_out(height, timestamp) {
+        return Err(ChannelError::PacketTimedOut);
+    }
+
+    verify_packet(
+        connection,
+        root,
+        packet_commitment_path(&packet.source_port, &packet.source_channel, packet.sequence),
+        &packet.commitment(),
+        &payload.proof_commitment,
+    )?;
+
+    match end.ordering {
+        Order::Ordered if packet.sequence != next_sequence_recv => {
+            Err(ChannelError::InvalidSequence)
+        }
+        Order::Unordered if received => Err(ChannelError::PacketAlreadyReceived),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the packet of `payload`, sent over `end` and committed to by this chain with
+/// `commitment`, is acknowledged by the counterparty under `root`. On ordered channels the packet
+/// must be the next one to be acknowledged.
+pub fn acknowledge_packet(
+    end: &ChannelEnd,
+    connection: &ConnectionEnd,
+    payload: &AcknowledgePacketPayload,
+    commitment: Option<&[u8]>,
+    next_sequence_ack: u64,
+    root: &[u8],
+) -> Result<(), ChannelError> {
+    let packet = &payload.packet;
+    if end.state != ChannelState::Open {
+        return Err(ChannelError::InvalidState);
+    }
+    check_sent(end, packet, commitment)?;
+    ensure_open(connection)?;
+
+    verify_packet(
+        connection,
+        root,
+        packet_acknowledgement_path(
+            &packet.destination_port,
+            &packet.destination_channel,
+            packet.sequence,
+        ),
+        &acknowledgement_commitment(&payload.acknowledgement),
+        &payload.proof_acked,
+    )?;
+
+    if end.ordering == Order::Ordered && packet.sequence != next_sequence_ack {
+        return Err(ChannelError::InvalidSequence);
+    }
+    Ok(())
+}
+
+/// `end` once the packet of `payload`, sent over `end` and committed to by this chain with
+/// `commitment`, is proven under `root` not to have been received by the counterparty before its
+/// timeout passed at the proof height, whose time is `proof_timestamp`. Ordered channels are closed
+/// by a timeout, after which their remaining packets can still be timed out.
+pub fn timeout_packet(
+    end: &ChannelEnd,
+    connection: &ConnectionEnd,
+    payload: &TimeoutPacketPayload,
+    commitment: Option<&[u8]>,
+    proof_timestamp: u64,
+    root: &[u8],
+) -> Result<ChannelEnd, ChannelError> {
+    let packet = &payload.packet;
+    if end.state != ChannelState::Open && end.state != ChannelState::Closed {
+        return Err(ChannelError::InvalidState);
+    }
+    check_sent(end, packet, commitment)?;
+    if !packet.timed_out(payload.proof_height, proof_timestamp) {
+        return Err(ChannelError::PacketNotTimedOut);
+    }
+
+    match end.ordering {
+        Order::Ordered => {
+            if packet.sequence < payload.next_sequence_recv {
+                return Err(ChannelError::InvalidSequence);
+            }
+            verify_packet(
+                connection,
+                root,
+                next_sequence_recv_path(&packet.destination_port, &packet.destination_channel),
+                &payload.next_sequence_recv.to_be_bytes(),
+                &payload.proof_unreceived,
+            )?;
+            Ok(ChannelEnd {
+                state: ChannelState::Closed,
+                ..end.clone()
+            })
+        }
+        Order::Unordered => {
+            let proof = decode_proof(&payload.proof_unreceived)?;
+            let path = packet_receipt_path(
+                &packet.destination_port,
+                &packet.destination_channel,
+                packet.sequence,
+            );
+            commitment::verify_non_membership(
+                &commitment::SDK_SPECS,
+                root,
+                &proof,
+                &[connection.counterparty.prefix.clone(), path],
+            )
+            .map_err(ChannelError::InvalidPacketProof)?;
+            Ok(end.clone())
+        }
+    }
+}
+
+/// Checks that `packet` was sent over `end` and is still committed to with `commitment`.
+fn check_sent(
+    end: &ChannelEnd,
+    packet: &Packet,
+    commitment: Option<&[u8]>,
+) -> Result<(), ChannelError> {
+    if packet.destination_port != end.counterparty.port_id
+        || packet.destination_channel != end.counterparty.channel_id
+    {
+        return Err(ChannelError::InvalidPacket);
+    }
+    if commitment != Some(&packet.commitment()[..]) {
+        return Err(ChannelError::PacketNotCommitted);
+    }
+    Ok(())
+}
+
+fn string(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::{Counterparty, Version};
+    use crate::testing::TestStore;
+
+    const PORT_ID: &[u8] = b"transfer";
+    const COUNTERPARTY_PORT_ID: &[u8] = b"transfer";
+    const COUNTERPARTY_CHANNEL_ID: &[u8] = b"channel-4";
+    const VERSION: &[u8] = b"ics20-1";
+
+    fn connection() -> ConnectionEnd {
+        ConnectionEnd {
+            state: ConnectionState::Open,
+            client_id: b"07-tendermint-0".to_vec(),
+            counterparty: Counterparty {
+                client_id: b"07-tendermint-3".to_vec(),
+                connection_id: b"connection-7".to_vec(),
+                prefix: b"ibc".to_vec(),
+            },
+            versions: vec![Version::default()],
+            delay_period: 0,
+        }
+    }
+
+    /// Counterparty's end of channel `channel-0` of this chain.
+    fn counterparty_end(state: ChannelState, ordering: Order, channel_id: &[u8]) -> ChannelEnd {
+        ChannelEnd {
+            state,
+            ordering,
+            counterparty: ChannelCounterparty {
+                port_id: PORT_ID.to_vec(),
+                channel_id: channel_id.to_vec(),
+            },
+            connection_hops: vec![b"connection-7".to_vec()],
+            version: VERSION.to_vec(),
+        }
+    }
+
+    fn counterparty_store(end: &ChannelEnd) -> TestStore {
+        let mut store = TestStore::new();
+        store.set(
+            channel_path(COUNTERPARTY_PORT_ID, COUNTERPARTY_CHANNEL_ID),
+            proto::encode(&end.to_proto()),
+        );
+        store
+    }
+
+    fn open_end(ordering: Order) -> ChannelEnd {
+        ChannelEnd {
+            state: ChannelState::Open,
+            ordering,
+            counterparty: ChannelCounterparty {
+                port_id: COUNTERPARTY_PORT_ID.to_vec(),
+                channel_id: COUNTERPARTY_CHANNEL_ID.to_vec(),
+            },
+            connection_hops: vec![b"connection-0".to_vec()],
+            version: VERSION.to_vec(),
+        }
+    }
+
+    /// Packet of `sequence` sent from `channel-0` of this chain.
+    fn sent(sequence: u64) -> Packet {
+        Packet {
+            sequence,
+            source_port: PORT_ID.to_vec(),
+            source_channel: b"channel-0".to_vec(),
+            destination_port: COUNTERPARTY_PORT_ID.to_vec(),
+            destination_channel: COUNTERPARTY_CHANNEL_ID.to_vec(),
+            data: b"data".to_vec(),
+            timeout_height: Height::new(1, 100),
+            timeout_timestamp: 0,
+        }
+    }
+
+    /// Packet of `sequence` sent by the counterparty to `channel-0` of this chain.
+    fn received(sequence: u64) -> Packet {
+        Packet {
+            sequence,
+            source_port: COUNTERPARTY_PORT_ID.to_vec(),
+            source_channel: COUNTERPARTY_CHANNEL_ID.to_vec(),
+            destination_port: PORT_ID.to_vec(),
+            destination_channel: b"channel-0".to_vec(),
+            data: b"data".to_vec(),
+            timeout_height: Height::new(0, 50),
+            timeout_timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn opens_and_closes_initialised_channel() {
+        let payload = ChanOpenInitPayload {
+            port_id: PORT_ID.to_vec(),
+            connection_id: b"connection-0".to_vec(),
+            ordering: Order::Unordered,
+            counterparty_port_id: COUNTERPARTY_PORT_ID.to_vec(),
+            version: VERSION.to_vec(),
+        };
+        let end = open_init(&connection(), &payload).unwrap();
+        assert_eq!(end.state, ChannelState::Init);
+
+        let store = counterparty_store(&counterparty_end(
+            ChannelState::TryOpen,
+            Order::Unordered,
+            b"channel-0",
+        ));
+        let payload = ChanOpenAckPayload {
+            port_id: PORT_ID.to_vec(),
+            channel_id: b"channel-0".to_vec(),
+            counterparty_channel_id: COUNTERPARTY_CHANNEL_ID.to_vec(),
+            counterparty_version: VERSION.to_vec(),
+            proof_height: Height::new(1, 10),
+            proof_try: store.prove(&channel_path(COUNTERPARTY_PORT_ID, COUNTERPARTY_CHANNEL_ID)),
+        };
+        let open = open_ack(&end, &connection(), &payload, &store.root()).unwrap();
+        assert_eq!(open, open_end(Order::Unordered));
+        assert_eq!(
+            open_ack(&open, &connection(), &payload, &store.root()),
+            Err(ChannelError::InvalidState)
+        );
+
+        let closed = close_init(&open, &connection()).unwrap();
+        assert_eq!(closed.state, ChannelState::Closed);
+        assert_eq!(
+            close_init(&closed, &connection()),
+            Err(ChannelError::InvalidState)
+        );
+    }
+
+    #[test]
+    fn opens_and_closes_tried_channel() {
+        let store = counterparty_store(&counterparty_end(ChannelState::Init, Order::Ordered, b""));
+        let payload = ChanOpenTryPayload {
+            port_id: PORT_ID.to_vec(),
+            connection_id: b"connection-0".to_vec(),
+            ordering: Order::Ordered,
+            counterparty: ChannelCounterparty {
+                port_id: COUNTERPARTY_PORT_ID.to_vec(),
+                channel_id: COUNTERPARTY_CHANNEL_ID.to_vec(),
+            },
+            counterparty_version: VERSION.to_vec(),
+            proof_height: Height::new(1, 10),
+            proof_init: store.prove(&channel_path(COUNTERPARTY_PORT_ID, COUNTERPARTY_CHANNEL_ID)),
+        };
+        let end = open_try(&connection(), &payload, &store.root()).unwrap();
+        assert_eq!(end.state, ChannelState::TryOpen);
+
+        let mut other = payload.clone();
+        other.ordering = Order::Unordered;
+        assert!(matches!(
+            open_try(&connection(), &other, &store.root()),
+            Err(ChannelError::InvalidChannelProof(_))
+        ));
+        let mut init = connection();
+        init.state = ConnectionState::Init;
+        assert_eq!(
+            open_try(&init, &payload, &store.root()),
+            Err(ChannelError::ConnectionNotOpen)
+        );
+
+        let store = counterparty_store(&counterparty_end(
+            ChannelState::Open,
+            Order::Ordered,
+            b"channel-0",
+        ));
+        let payload = ChanOpenConfirmPayload {
+            port_id: PORT_ID.to_vec(),
+            channel_id: b"channel-0".to_vec(),
+            proof_height: Height::new(1, 11),
+            proof_ack: store.prove(&channel_path(COUNTERPARTY_PORT_ID, COUNTERPARTY_CHANNEL_ID)),
+        };
+        let open = open_confirm(&end, &connection(), &payload, &store.root()).unwrap();
+        assert_eq!(open, open_end(Order::Ordered));
+
+        let payload = ChanCloseConfirmPayload {
+            port_id: PORT_ID.to_vec(),
+            channel_id: b"channel-0".to_vec(),
+            proof_height: Height::new(1, 12),
+            proof_init: payload.proof_ack,
+        };
+        // The counterparty's end is still open
+        assert!(matches!(
+            close_confirm(&open, &connection(), &payload, &store.root()),
+            Err(ChannelError::InvalidChannelProof(_))
+        ));
+        let store = counterparty_store(&counterparty_end(
+            ChannelState::Closed,
+            Order::Ordered,
+            b"channel-0",
+        ));
+        let payload = ChanCloseConfirmPayload {
+            proof_init: store.prove(&channel_path(COUNTERPARTY_PORT_ID, COUNTERPARTY_CHANNEL_ID)),
+            ..payload
+        };
+        let closed = close_confirm(&open, &connection(), &payload, &store.root()).unwrap();
+        assert_eq!(closed.state, ChannelState::Closed);
+    }
+
+    #[test]
+    fn sends_packets_before_their_timeout() {
+        let end = open_end(Order::Unordered);
+        assert_eq!(send_packet(&end, &sent(1), Height::new(1, 99), 0), Ok(()));
+        assert_eq!(
+            send_packet(&end, &sent(1), Height::new(1, 100), 0),
+            Err(ChannelError::PacketTimedOut)
+        );
+
+        let mut packet = sent(1);
+        packet.timeout_height = Height::default();
+        assert_eq!(
+            send_packet(&end, &packet, Height::new(1, 99), 0),
+            Err(ChannelError::InvalidPacket)
+        );
+        packet.timeout_timestamp = 2_000;
+        assert_eq!(
+            send_packet(&end, &packet, Height::new(1, 99), 1_999),
+            Ok(())
+        );
+        assert_eq!(
+            send_packet(&end, &packet, Height::new(1, 99), 2_000),
+            Err(ChannelError::PacketTimedOut)
+        );
+
+        packet.destination_channel = b"channel-5".to_vec();
+        assert_eq!(
+            send_packet(&end, &packet, Height::new(1, 99), 0),
+            Err(ChannelError::InvalidPacket)
+        );
+    }
+
+    #[test]
+    fn receives_committed_packets() {
+        let mut store = TestStore::new();
+        for sequence in 1..=2 {
+            let packet = received(sequence);
+            store.set(
+                packet_commitment_path(COUNTERPARTY_PORT_ID, COUNTERPARTY_CHANNEL_ID, sequence),
+                packet.commitment(),
+            );
+        }
+        let payload = |sequence| RecvPacketPayload {
+            packet: received(sequence),
+            proof_height: Height::new(1, 10),
+            proof_commitment: store.prove(&packet_commitment_path(
+                COUNTERPARTY_PORT_ID,
+                COUNTERPARTY_CHANNEL_ID,
+                sequence,
+            )),
+        };
+        let recv = |end: &ChannelEnd, payload: &RecvPacketPayload, next: u64, received: bool| {
+            recv_packet(
+                end,
+                &connection(),
+                payload,
+                next,
+                received,
+                Height::new(0, 49),
+                0,
+                &store.root(),
+            )
+        };
+
+        let unordered = open_end(Order::Unordered);
+        assert_eq!(recv(&unordered, &payload(2), 1, false), Ok(()));
+        assert_eq!(
+            recv(&unordered, &payload(2), 1, true),
+            Err(ChannelError::PacketAlreadyReceived)
+        );
+
+        let ordered = open_end(Order::Ordered);
+        assert_eq!(recv(&ordered, &payload(1), 1, false), Ok(()));
+        assert_eq!(
+            recv(&ordered, &payload(2), 1, false),
+            Err(ChannelError::InvalidSequence)
+        );
+
+        let mut tampered = payload(1);
+        tampered.packet.data = b"other".to_vec();
+        assert!(matches!(
+            recv(&unordered, &tampered, 1, false),
+            Err(ChannelError::InvalidPacketProof(_))
+        ));
+
+        let late = recv_packet(
+            &unordered,
+            &connection(),
+            &payload(1),
+            1,
+            false,
+            Height::new(0, 50),
+            0,
+            &store.root(),
+        );
+        assert_eq!(late, Err(ChannelError::PacketTimedOut));
+    }
+
+    #[test]
+    fn acknowledges_sent_packets() {
+        let mut store = TestStore::new();
+        store.set(
+            packet_acknowledgement_path(COUNTERPARTY_PORT_ID, COUNTERPARTY_CHANNEL_ID, 1),
+            acknowledgement_commitment(b"ack"),
+        );
+        let payload = AcknowledgePacketPayload {
+            packet: sent(1),
+            acknowledgement: b"ack".to_vec(),
+            proof_height: Height::new(1, 10),
+            proof_acked: store.prove(&packet_acknowledgement_path(
+                COUNTERPARTY_PORT_ID,
+                COUNTERPARTY_CHANNEL_ID,
+                1,
+            )),
+        };
+        let commitment = sent(1).commitment();
+        let end = open_end(Order::Ordered);
+        let root = store.root();
+
+        assert_eq!(
+            acknowledge_packet(&end, &connection(), &payload, Some(&commitment), 1, &root),
+            Ok(())
+        );
+        assert_eq!(
+            acknowledge_packet(&end, &connection(), &payload, Some(&commitment), 2, &root),
+            Err(ChannelError::InvalidSequence)
+        );
+        assert_eq!(
+            acknowledge_packet(&end, &connection(), &payload, None, 1, &root),
+            Err(ChannelError::PacketNotCommitted)
+        );
+
+        let mut forged = payload.clone();
+        forged.acknowledgement = b"other ack".to_vec();
+        assert!(matches!(
+            acknowledge_packet(&end, &connection(), &forged, Some(&commitment), 1, &root),
+            Err(ChannelError::InvalidPacketProof(_))
+        ));
+    }
+
+    #[test]
+    fn times_out_unreceived_packets() {
+        let mut store = TestStore::new();
+        store.set(
+            next_sequence_recv_path(COUNTERPARTY_PORT_ID, COUNTERPARTY_CHANNEL_ID),
+            1u64.to_be_bytes().to_vec(),
+        );
+        store.set(
+            packet_receipt_path(COUNTERPARTY_PORT_ID, COUNTERPARTY_CHANNEL_ID, 2),
+            RECEIPT.to_vec(),
+        );
+        let root = store.root();
+        let commitment = sent(1).commitment();
+
+        // Ordered channels are closed once the counterparty is proven to wait for the packet
+        let ordered = open_end(Order::Ordered);
+        let payload = TimeoutPacketPayload {
+            packet: sent(1),
+            next_sequence_recv: 1,
+            proof_height: Height::new(1, 100),
+            proof_unreceived: store.prove(&next_sequence_recv_path(
+                COUNTERPARTY_PORT_ID,
+                COUNTERPARTY_CHANNEL_ID,
+            )),
+        };
+        let closed = timeout_packet(
+            &ordered,
+            &connection(),
+            &payload,
+            Some(&commitment),
+            0,
+            &root,
+        );
+        let closed = closed.unwrap();
+        assert_eq!(closed.state, ChannelState::Closed);
+        // The next packets time out over the closed channel
+        let next = TimeoutPacketPayload {
+            packet: sent(2),
+            ..payload.clone()
+        };
+        assert_eq!(
+            timeout_packet(
+                &closed,
+                &connection(),
+                &next,
+                Some(&sent(2).commitment()),
+                0,
+                &root
+            ),
+            Ok(closed.clone())
+        );
+
+        let early = TimeoutPacketPayload {
+            proof_height: Height::new(1, 99),
+            ..payload.clone()
+        };
+        assert_eq!(
+            timeout_packet(&ordered, &connection(), &early, Some(&commitment), 0, &root),
+            Err(ChannelError::PacketNotTimedOut)
+        );
+        let received = TimeoutPacketPayload {
+            next_sequence_recv: 2,
+            ..payload
+        };
+        assert_eq!(
+            timeout_packet(
+                &ordered,
+                &connection(),
+                &received,
+                Some(&commitment),
+                0,
+                &root
+            ),
+            Err(ChannelError::InvalidSequence)
+        );
+
+        // Unordered channels stay open once the receipt is proven absent
+        let unordered = open_end(Order::Unordered);
+        let payload = TimeoutPacketPayload {
+            packet: sent(1),
+            next_sequence_recv: 0,
+            proof_height: Height::new(1, 100),
+            proof_unreceived: store.prove_absence(&packet_receipt_path(
+                COUNTERPARTY_PORT_ID,
+                COUNTERPARTY_CHANNEL_ID,
+                1,
+            )),
+        };
+        assert_eq!(
+            timeout_packet(
+                &unordered,
+                &connection(),
+                &payload,
+                Some(&commitment),
+                0,
+                &root
+            ),
+            Ok(unordered.clone())
+        );
+
+        let payload = TimeoutPacketPayload {
+            packet: sent(2),
+            proof_unreceived: store.prove(&packet_receipt_path(
+                COUNTERPARTY_PORT_ID,
+                COUNTERPARTY_CHANNEL_ID,
+                2,
+            )),
+            ..payload
+        };
+        assert!(matches!(
+            timeout_packet(
+                &unordered,
+                &connection(),
+                &payload,
+                Some(&sent(2).commitment()),
+                0,
+                &root
+            ),
+            Err(ChannelError::InvalidPacketProof(
+                CommitmentError::NotNonExistenceProof
+            ))
+        ));
+    }
+}
@@ -0,0 +1,302 @@
+//! ICS-23 membership proof verification for Cosmos SDK multistores.
+//! Implements the subset of `cosmos/ics23` needed to check existence and
+//! non-existence proofs against the IAVL and Tendermint proof specs.
+
+use sha2::{Digest, Sha256};
+use sp_std::vec::Vec;
+
+use crate::proto::{
+    commitment_proof, CommitmentProof, ExistenceProof, HashOp, InnerOp, LeafOp, LengthOp, MerkleProof,
+    NonExistenceProof,
+};
+
+/// Reasons a membership proof can be rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommitmentError {
+    /// The number of proofs does not match the number of path segments.
+    ProofLengthMismatch,
+    /// A proof in the chain is not an existence proof.
+    NotExistenceProof,
+    /// The proof of the innermost key's absence is not a non-existence proof.
+    NotNonExistenceProof,
+    /// The neighbours of a non-existence proof are not adjacent leaves on either side of the key.
+    InvalidNeighbours,
+    /// A leaf or inner operation does not satisfy the proof spec.
+    SpecMismatch,
+    /// The proven key or value differs from the expected one.
+    KeyValueMismatch,
+    /// The calculated root differs from the trusted root.
+    RootMismatch,
+}
+
+/// Parameters of an ICS-23 `ProofSpec`, as used by the Cosmos SDK.
+pub struct ProofSpec {
+    pub leaf_prefix: &'static [u8],
+    pub child_size: usize,
+    pub child_count: usize,
+    pub min_prefix_length: usize,
+    pub max_prefix_length: usize,
+    pub iavl: bool,
+}
+
+/// Spec of the IAVL trees backing each Cosmos SDK module store.
+pub const IAVL_SPEC: ProofSpec = ProofSpec {
+    leaf_prefix: &[0],
+    child_size: 33,
+    child_count: 2,
+    min_prefix_length: 4,
+    max_prefix_length: 12,
+    iavl: true,
+};
+
+/// Spec of the simple merkle tree committing to the module store roots.
+pub const TENDERMINT_SPEC: ProofSpec = ProofSpec {
+    leaf_prefix: &[0],
+    child_size: 32,
+    child_count: 2,
+    min_prefix_length: 1,
+    max_prefix_length: 1,
+    iavl: false,
+};
+
+/// Specs of a Cosmos SDK multistore, ordered from the module store to the app hash.
+pub const SDK_SPECS: [&ProofSpec; 2] = [&IAVL_SPEC, &TENDERMINT_SPEC];
+
+/// Verifies that `value` is stored under `path` in the multistore committed to by `root`.
+/// `path` is ordered from the outermost key (store name) to the innermost one.
+pub fn verify_membership(
+    specs: &[&ProofSpec],
+    root: &[u8],
+    proof: &MerkleProof,
+    path: &[Vec<u8>],
+    value: &[u8],
+) -> Result<(), CommitmentError> {
+    if proof.proofs.len() != specs.len() || path.len() != specs.len() {
+        return Err(CommitmentError::ProofLengthMismatch);
+    }
+    verify_chain(specs, root, &proof.proofs, path, value.to_vec())
+}
+
+/// Verifies that nothing is stored under `path` in the multistore committed to by `root`: the innermost
+/// proof is a non-existence proof, and the outer ones prove the root of the store it is against.
+pub fn verify_non_membership(
+    specs: &[&ProofSpec],
+    root: &[u8],
+    proof: &MerkleProof,
+    path: &[Vec<u8>],
+) -> Result<(), CommitmentError> {
+    if proof.proofs.is_empty() || proof.proofs.len() != specs.len() || path.len() != specs.len() {
+        return Err(CommitmentError::ProofLengthMismatch);
+    }
+
+    let non_existence_proof = match &proof.proofs[0].proof {
+        Some(commitment_proof::Proof::Nonexist(p)) => p,
+        _ => return Err(CommitmentError::NotNonExistenceProof),
+    };
+    if non_existence_proof.key != path[path.len() - 1] {
+        return Err(CommitmentError::KeyValueMismatch);
+    }
+    let store_root = check_non_existence(non_existence_proof, specs[0])?;
+    verify_chain(
+        &specs[1..],
+        root,
+        &proof.proofs[1..],
+        &path[..path.len() - 1],
+        store_root,
+    )
+}
+
+/// Verifies that `value` is stored under the innermost key of `path` and each store root under the next key, up to
+/// `root`.
+fn verify_chain(
+    specs: &[&ProofSpec],
+    root: &[u8],
+    proofs: &[CommitmentProof],
+    path: &[Vec<u8>],
+    mut value: Vec<u8>,
+) -> Result<(), CommitmentError> {
+    for (i, (commitment_proof, spec)) in proofs.iter().zip(specs.iter()).enumerate() {
+        let existence_proof = match &commitment_proof.proof {
+            Some(commitment_proof::Proof::Exist(p)) => p,
+            _ => return Err(CommitmentError::NotExistenceProof),
+        };
+        let key = &path[path.len() - 1 - i];
+        if &existence_proof.key != key || existence_proof.value != value {
+            return Err(CommitmentError::KeyValueMismatch);
+        }
+        check_against_spec(existence_proof, spec)?;
+        value = calculate_root(existence_proof)?;
+    }
+
+    if value.as_slice() != root {
+        return Err(CommitmentError::RootMismatch);
+    }
+    Ok(())
+}
+
+/// Checks that the neighbours proven by `proof` are adjacent leaves on either side of its key, and returns the root
+/// they are proven against.
+fn check_non_existence(proof: &NonExistenceProof, spec: &ProofSpec) -> Result<Vec<u8>, CommitmentError> {
+    let mut root: Option<Vec<u8>> = None;
+    for neighbour in proof.left.iter().chain(proof.right.iter()) {
+        check_against_spec(neighbour, spec)?;
+        let neighbour_root = calculate_root(neighbour)?;
+        if root.as_ref().map_or(false, |root| *root != neighbour_root) {
+            return Err(CommitmentError::RootMismatch);
+        }
+        root = Some(neighbour_root);
+    }
+
+    let adjacent = match (&proof.left, &proof.right) {
+        (Some(left), None) => left.key < proof.key && is_right_most(spec, &left.path),
+        (None, Some(right)) => proof.key < right.key && is_left_most(spec, &right.path),
+        (Some(left), Some(right)) => {
+            left.key < proof.key && proof.key < right.key && is_left_neighbour(spec, &left.path, &right.path)
+        }
+        (None, None) => false,
+    };
+    if !adjacent {
+        return Err(CommitmentError::InvalidNeighbours);
+    }
+    root.ok_or(CommitmentError::InvalidNeighbours)
+}
+
+/// Minimum and maximum prefix lengths, and suffix length, of inner operations hashing the child at `branch`.
+fn padding(spec: &ProofSpec, branch: usize) -> (usize, usize, usize) {
+    let prefix = branch * spec.child_size;
+    let suffix = (spec.child_count - 1 - branch) * spec.child_size;
+    (prefix + spec.min_prefix_length, prefix + spec.max_prefix_length, suffix)
+}
+
+fn has_padding(inner: &InnerOp, (min_prefix, max_prefix, suffix): (usize, usize, usize)) -> bool {
+    inner.prefix.len() >= min_prefix && inner.prefix.len() <= max_prefix && inner.suffix.len() == suffix
+}
+
+/// Branch of the child hashed by `inner`.
+fn branch(spec: &ProofSpec, inner: &InnerOp) -> Option<usize> {
+    (0..spec.child_count).find(|branch| has_padding(inner, padding(spec, *branch)))
+}
+
+fn is_left_most(spec: &ProofSpec, path: &[InnerOp]) -> bool {
+    path.iter().all(|inner| has_padding(inner, padding(spec, 0)))
+}
+
+fn is_right_most(spec: &ProofSpec, path: &[InnerOp]) -> bool {
+    path.iter()
+        .all(|inner| has_padding(inner, padding(spec, spec.child_count - 1)))
+}
+
+/// Whether the leaves of the paths `left` and `right` are next to each other: below the node where the paths meet,
+/// `left` takes the rightmost branches and `right` the leftmost ones.
+fn is_left_neighbour(spec: &ProofSpec, mut left: &[InnerOp], mut right: &[InnerOp]) -> bool {
+    while let (Some((top_left, left_rest)), Some((top_right, right_rest))) = (left.split_last(), right.split_last()) {
+        if top_left.prefix == top_right.prefix && top_left.suffix == top_right.suffix {
+            left = left_rest;
+            right = right_rest;
+            continue;
+        }
+        let left_branch = branch(spec, top_left);
+        return left_branch.is_some()
+            && left_branch.map(|b| b + 1) == branch(spec, top_right)
+            && is_right_most(spec, left_rest)
+            && is_left_most(spec, right_rest);
+    }
+    false
+}
+
+fn check_against_spec(proof: &ExistenceProof, spec: &ProofSpec) -> Result<(), CommitmentError> {
+    let leaf = proof.leaf.as_ref().ok_or(CommitmentError::SpecMismatch)?;
+    if leaf.hash != HashOp::Sha256 as i32
+        || leaf.prehash_key != HashOp::NoHash as i32
+        || leaf.prehash_value != HashOp::Sha256 as i32
+        || leaf.length != LengthOp::VarProto as i32
+        || !leaf.prefix.starts_with(spec.leaf_prefix)
+    {
+        return Err(CommitmentError::SpecMismatch);
+    }
+    if spec.iavl {
+        check_iavl_prefix(&leaf.prefix, 0)?;
+    }
+
+    let max_prefix_length = spec.max_prefix_length + (spec.child_count - 1) * spec.child_size;
+    for (layer, inner) in proof.path.iter().enumerate() {
+        if inner.hash != HashOp::Sha256 as i32
+            || inner.prefix.starts_with(spec.leaf_prefix)
+            || inner.prefix.len() < spec.min_prefix_length
+            || inner.prefix.len() > max_prefix_length
+            || inner.suffix.len() % spec.child_size != 0
+        {
+            return Err(CommitmentError::SpecMismatch);
+        }
+        if spec.iavl {
+            check_iavl_prefix(&inner.prefix, layer + 1)?;
+        }
+    }
+    Ok(())
+}
+
+/// IAVL node prefixes start with the zigzag varints height, size and version.
+/// Leaves must consist of exactly those three values, and inner nodes must sit above their children.
+fn check_iavl_prefix(prefix: &[u8], layer: usize) -> Result<(), CommitmentError> {
+    let mut rest = prefix;
+    let height = read_varint(&mut rest)?;
+    let size = read_varint(&mut rest)?;
+    let version = read_varint(&mut rest)?;
+    if height < 0 || (height as usize) < layer || size < 0 || version < 0 {
+        return Err(CommitmentError::SpecMismatch);
+    }
+    if layer == 0 && !rest.is_empty() {
+        return Err(CommitmentError::SpecMismatch);
+    }
+    Ok(())
+}
+
+fn read_varint(input: &mut &[u8]) -> Result<i64, CommitmentError> {
+    let mut value: u64 = 0;
+    for shift in (0..64).step_by(7) {
+        let (byte, rest) = input.split_first().ok_or(CommitmentError::SpecMismatch)?;
+        *input = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(((value >> 1) as i64) ^ -((value & 1) as i64));
+        }
+    }
+    Err(CommitmentError::SpecMismatch)
+}
+
+fn calculate_root(proof: &ExistenceProof) -> Result<Vec<u8>, CommitmentError> {
+    let leaf = proof.leaf.as_ref().ok_or(CommitmentError::SpecMismatch)?;
+    let mut hash = apply_leaf(leaf, &proof.key, &proof.value);
+    for inner in proof.path.iter() {
+        hash = apply_inner(inner, &hash);
+    }
+    Ok(hash)
+}
+
+fn apply_leaf(leaf: &LeafOp, key: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.input(&leaf.prefix);
+    hasher.input(&length_prefixed(key));
+    hasher.input(&length_prefixed(&Sha256::digest(value)));
+    hasher.result().to_vec()
+}
+
+fn apply_inner(inner: &InnerOp, child: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.input(&inner.prefix);
+    hasher.input(child);
+    hasher.input(&inner.suffix);
+    hasher.result().to_vec()
+}
+
+pub(crate) fn length_prefixed(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + 10);
+    let mut len = data.len() as u64;
+    while len >= 0x80 {
+        out.push((len as u8) | 0x80);
+        len >>= 7;
+    }
+    out.push(len as u8);
+    out.extend_from_slice(data);
+    out
+}
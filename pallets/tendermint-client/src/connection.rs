@@ -0,0 +1,812 @@
+//! ICS-03 connection handshake between a client of this chain and the client of this chain kept by
+//! the counterparty.
+//!
+//! The handshake steps are checked here, without storage: each step is given the connection end it
+//! moves (if any) and the root the counterparty committed to at the proof height, which is the app
+//! hash of the client's consensus state at that height. As in ibc-go, proofs of the counterparty's
+//! state at height `H` are verified against the app hash of the header at `H`, so they are queried
+//! from the counterparty at `H - 1`.
+//!
+//! `ConnOpenTry` and `ConnOpenAck` only prove that the counterparty stores the client and
+//! consensus states of this chain they carry. Whether those states are valid ones of this chain
+//! (ICS-03 `ValidateSelfClient`) is not checked here: with the `self-client-validation` feature,
+//! the pallet validates them against its own chain with `self_client` before the step.
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use sp_std::vec::Vec;
+
+use crate::commitment::{self, CommitmentError};
+use crate::proto;
+use crate::types::Height;
+
+/// Store this chain keeps its IBC state under, as the counterparty proves it against.
+pub const COMMITMENT_PREFIX: &[u8] = b"ibc";
+
+/// Identifier of the only connection version, as in ibc-go.
+pub const VERSION_IDENTIFIER: &str = "1";
+/// Channel orderings supported by connections.
+pub const VERSION_FEATURES: [&str; 2] = ["ORDER_ORDERED", "ORDER_UNORDERED"];
+
+/// Reasons a handshake step can be rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConnectionError {
+    /// Identifier is not a valid ICS-24 identifier.
+    InvalidIdentifier,
+    /// Connection is not in the state the handshake step moves it from.
+    InvalidState,
+    /// No version is supported by both ends, or the version is not one that was proposed.
+    VersionNotSupported,
+    /// Proof is not a protobuf encoded `MerkleProof`.
+    MalformedProof,
+    /// Counterparty connection end is not the expected one.
+    InvalidConnectionProof(CommitmentError),
+    /// Client state of this chain kept by the counterparty is not the claimed one.
+    InvalidClientStateProof(CommitmentError),
+    /// Consensus state of this chain kept by the counterparty is not the claimed one.
+    InvalidConsensusStateProof(CommitmentError),
+    /// Delay period of the connection has not passed since the consensus state a proof is
+    /// verified against was stored.
+    DelayPeriodNotPassed,
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionState {
+    Init,
+    TryOpen,
+    Open,
+}
+
+/// Other end of a connection. `connection_id` is empty until the counterparty opened its end.
+#[derive(Serialize, Deserialize, Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct Counterparty {
+    pub client_id: Vec<u8>,
+    #[serde(default)]
+    pub connection_id: Vec<u8>,
+    /// Store the counterparty keeps its IBC state under.
+    pub prefix: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct Version {
+    pub identifier: String,
+    pub features: Vec<String>,
+}
+
+impl Default for Version {
+    fn default() -> Self {
+        Version {
+            identifier: VERSION_IDENTIFIER.into(),
+            features: VERSION_FEATURES.iter().map(|f| (*f).into()).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct ConnectionEnd {
+    pub state: ConnectionState,
+    pub client_id: Vec<u8>,
+    pub counterparty: Counterparty,
+    pub versions: Vec<Version>,
+    /// Time in nanoseconds packet proofs must wait for after the consensus state they are verified
+    /// against was stored. Also enforced in blocks, see `verify_delay_passed`.
+    pub delay_period: u64,
+}
+
+impl ConnectionEnd {
+    /// Delay period in blocks of this chain, which take up to `max_expected_time_per_block`
+    /// nanoseconds each: the delay period divided by that time, rounded up.
+    pub fn delay_blocks(&self, max_expected_time_per_block: u64) -> u64 {
+        if max_expected_time_per_block == 0 {
+            return 0;
+        }
+        let blocks = self.delay_period / max_expected_time_per_block;
+        if self.delay_period % max_expected_time_per_block == 0 {
+            blocks
+        } else {
+            blocks + 1
+        }
+    }
+
+    /// Protobuf `ibc.core.connection.v1.ConnectionEnd` committed to under `connections/{id}`.
+    pub fn to_proto(&self) -> proto::ConnectionEnd {
+        let state = match self.state {
+            ConnectionState::Init => proto::ConnectionState::Init,
+            ConnectionState::TryOpen => proto::ConnectionState::TryOpen,
+            ConnectionState::Open => proto::ConnectionState::Open,
+        };
+        proto::ConnectionEnd {
+            client_id: string(&self.client_id),
+            versions: self
+                .versions
+                .iter()
+                .map(|v| proto::Version {
+                    identifier: v.identifier.clone(),
+                    features: v.features.clone(),
+                })
+                .collect(),
+            state: state as i32,
+            counterparty: Some(proto::ConnectionCounterparty {
+                client_id: string(&self.counterparty.client_id),
+                connection_id: string(&self.counterparty.connection_id),
+                prefix: Some(proto::MerklePrefix {
+                    key_prefix: self.counterparty.prefix.clone(),
+                }),
+            }),
+            delay_period: self.delay_period,
+        }
+    }
+}
+
+/// Opens a connection of `client_id` to the chain `counterparty` tracks.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConnOpenInitPayload {
+    pub client_id: Vec<u8>,
+    pub counterparty: Counterparty,
+    /// Version to propose, or all supported versions if omitted.
+    #[serde(default)]
+    pub version: Option<Version>,
+    #[serde(default)]
+    pub delay_period: u64,
+}
+
+/// Opens a connection of `client_id` in answer to the counterparty's `ConnOpenInit`.
+/// `client_state` and `consensus_state` are the counterparty's client and consensus states of
+/// this chain, at its latest height and at `consensus_height` respectively, as it encodes them. The
+/// `self-client-validation` feature expects SCALE encoded `SelfClientState` and `SelfConsensusState`. The proofs are protobuf encoded ICS-23 `MerkleProof`s, at
+/// `proof_height` of the counterparty.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConnOpenTryPayload {
+    pub client_id: Vec<u8>,
+    pub counterparty: Counterparty,
+    #[serde(default)]
+    pub delay_period: u64,
+    pub counterparty_versions: Vec<Version>,
+    pub client_state: Vec<u8>,
+    pub consensus_state: Vec<u8>,
+    pub consensus_height: Height,
+    pub proof_height: Height,
+    pub proof_init: Vec<u8>,
+    pub proof_client: Vec<u8>,
+    pub proof_consensus: Vec<u8>,
+}
+
+/// Acknowledges that the counterparty opened its end of `connection_id` with `version`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConnOpenAckPayload {
+    pub connection_id: Vec<u8>,
+    pub counterparty_connection_id: Vec<u8>,
+    pub version: Version,
+    pub client_state: Vec<u8>,
+    pub consensus_state: Vec<u8>,
+    pub consensus_height: Height,
+    pub proof_height: Height,
+    pub proof_try: Vec<u8>,
+    pub proof_client: Vec<u8>,
+    pub proof_consensus: Vec<u8>,
+}
+
+/// Confirms that the counterparty opened its end of `connection_id`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConnOpenConfirmPayload {
+    pub connection_id: Vec<u8>,
+    pub proof_height: Height,
+    pub proof_ack: Vec<u8>,
+}
+
+/// Connection id of the connection of sequence `sequence`.
+pub fn connection_id(sequence: u64) -> Vec<u8> {
+    [&b"connection-"[..], sequence.to_string().as_bytes()].concat()
+}
+
+/// ICS-24 path of a connection end.
+pub fn connection_path(connection_id: &[u8]) -> Vec<u8> {
+    [&b"connections/"[..], connection_id].concat()
+}
+
+/// ICS-24 path of the client state of a client.
+pub fn client_state_path(client_id: &[u8]) -> Vec<u8> {
+    [&b"clients/"[..], client_id, b"/clientState"].concat()
+}
+
+/// ICS-24 path of the consensus state of a client at `height`.
+pub fn consensus_state_path(client_id: &[u8], height: Height) -> Vec<u8> {
+    [
+        &b"clients/"[..],
+        client_id,
+        b"/consensusStates/",
+        height.revision_number.to_string().as_bytes(),
+        b"-",
+        height.revision_height.to_string().as_bytes(),
+    ]
+    .concat()
+}
+
+/// Checks that `id` is an ICS-24 identifier of `min` to `max` characters.
+pub fn validate_identifier(id: &[u8], min: usize, max: usize) -> Result<(), ConnectionError> {
+    let valid_char = |c: &u8| c.is_ascii_alphanumeric() || b"._+-#[]<>".contains(c);
+    if id.len() < min || id.len() > max || !id.iter().all(valid_char) {
+        return Err(ConnectionError::InvalidIdentifier);
+    }
+    Ok(())
+}
+
+fn validate_client_id(id: &[u8]) -> Result<(), ConnectionError> {
+    validate_identifier(id, 9, 64)
+}
+
+fn validate_connection_id(id: &[u8]) -> Result<(), ConnectionError> {
+    validate_identifier(id, 10, 64)
+}
+
+/// Version to use among the versions proposed by the counterparty: the supported version, with the
+/// features both ends support.
+fn pick_version(proposed: &[Version]) -> Result<Version, ConnectionError> {
+    let supported = Version::default();
+    proposed
+        .iter()
+        .find(|v| v.identifier == supported.identifier)
+        .map(|v| Version {
+            identifier: v.identifier.clone(),
+            features: supported
+                .features
+                .iter()
+                .filter(|f| v.features.contains(f))
+                .cloned()
+                .collect(),
+        })
+        .filter(|v| !v.features.is_empty())
+        .ok_or(ConnectionError::VersionNotSupported)
+}
+
+/// Whether `version` is supported, with features this chain supports.
+fn is_supported(version: &Version) -> bool {
+    let supported = Version::default();
+    version.identifier == supported.identifier
+        && !version.features.is_empty()
+        && version
+            .features
+            .iter()
+            .all(|f| supported.features.contains(f))
+}
+
+pub(crate) fn decode_proof(proof: &[u8]) -> Result<proto::MerkleProof, ConnectionError> {
+    <proto::MerkleProof as prost::Message>::decode(proof)
+        .map_err(|_| ConnectionError::MalformedProof)
+}
+
+/// Verifies that `value` is stored under `path` in the counterparty's store `prefix`.
+pub(crate) fn verify(
+    root: &[u8],
+    prefix: &[u8],
+    path: Vec<u8>,
+    value: &[u8],
+    proof: &proto::MerkleProof,
+) -> Result<(), CommitmentError> {
+    commitment::verify_membership(
+        &commitment::SDK_SPECS,
+        root,
+        proof,
+        &[prefix.to_vec(), path],
+        value,
+    )
+}
+
+fn verify_connection(
+    root: &[u8],
+    counterparty: &Counterparty,
+    expected: &ConnectionEnd,
+    proof: &[u8],
+) -> Result<(), ConnectionError> {
+    verify(
+        root,
+        &counterparty.prefix,
+        connection_path(&counterparty.connection_id),
+        &proto::encode(&expected.to_proto()),
+        &decode_proof(proof)?,
+    )
+    .map_err(ConnectionError::InvalidConnectionProof)
+}
+
+/// Verifies that the counterparty stores `client_state` and `consensus_state` as its client of this
+/// chain. Their contents are not validated.
+fn verify_client(
+    root: &[u8],
+    counterparty: &Counterparty,
+    client_state: &[u8],
+    proof_client: &[u8],
+    consensus_state: &[u8],
+    consensus_height: Height,
+    proof_consensus: &[u8],
+) -> Result<(), ConnectionError> {
+    verify(
+        root,
+        &counterparty.prefix,
+        client_state_path(&counterparty.client_id),
+        client_state,
+        &decode_proof(proof_client)?,
+    )
+    .map_err(ConnectionError::InvalidClientStateProof)?;
+    verify(
+        root,
+        &counterparty.prefix,
+        consensus_state_path(&counterparty.client_id, consensus_height),
+        consensus_state,
+        &decode_proof(proof_consensus)?,
+    )
+    .map_err(ConnectionError::InvalidConsensusStateProof)
+}
+
+/// Checks that the delay period of `end` passed, both in time and in blocks, since the consensus
+/// state a packet proof is verified against was stored at `processed_time` (in nanoseconds) and
+/// block `processed_height` of this chain. `now` and `own_height` are the current time and block.
+pub fn verify_delay_passed(
+    end: &ConnectionEnd,
+    processed_time: u64,
+    processed_height: u64,
+    now: u64,
+    own_height: u64,
+    max_expected_time_per_block: u64,
+) -> Result<(), ConnectionError> {
+    let delay_blocks = end.delay_blocks(max_expected_time_per_block);
+    if now < processed_time.saturating_add(end.delay_period)
+        || own_height < processed_height.saturating_add(delay_blocks)
+    {
+        return Err(ConnectionError::DelayPeriodNotPassed);
+    }
+    Ok(())
+}
+
+/// End of a connection opened by `ConnOpenInit`.
+pub fn open_init(payload: ConnOpenInitPayload) -> Result<ConnectionEnd, ConnectionError> {
+    validate_client_id(&payload.client_id)?;
+    validate_client_id(&payload.counterparty.client_id)?;
+    if !payload.counterparty.connection_id.is_empty() {
+        return Err(ConnectionError::InvalidIdentifier);
+    }
+    let versions = match payload.version {
+        Some(version) if is_supported(&version) => vec![version],
+        Some(_) => return Err(ConnectionError::VersionNotSupported),
+        None => vec![Version::default()],
+    };
+    Ok(ConnectionEnd {
+        state: ConnectionState::Init,
+        client_id: payload.client_id,
+        counterparty: payload.counterparty,
+        versions,
+        delay_period: payload.delay_period,
+    })
+}
+
+/// End of a connection opened by `ConnOpenTry`, once the counterparty's end is proven to be in
+/// `Init` under `root`, and its client of this chain to be stored as given in `payload`. The host
+/// validates that client beforehand with the `self-client-validation` feature only.
+pub fn open_try(
+    payload: &ConnOpenTryPayload,
+    root: &[u8],
+) -> Result<ConnectionEnd, ConnectionError> {
+    validate_client_id(&payload.client_id)?;
+    validate_client_id(&payload.counterparty.client_id)?;
+    validate_connection_id(&payload.counterparty.connection_id)?;
+    let version = pick_version(&payload.counterparty_versions)?;
+
+    let expected = ConnectionEnd {
+        state: ConnectionState::Init,
+        client_id: payload.counterparty.client_id.clone(),
+        counterparty: Counterparty {
+            client_id: payload.client_id.clone(),
+            connection_id: Vec::new(),
+            prefix: COMMITMENT_PREFIX.to_vec(),
+        },
+        versions: payload.counterparty_versions.clone(),
+        delay_period: payload.delay_period,
+    };
+    verify_connection(root, &payload.counterparty, &expected, &payload.proof_init)?;
+    verify_client(
+        root,
+        &payload.counterparty,
+        &payload.client_state,
+        &payload.proof_client,
+        &payload.consensus_state,
+        payload.consensus_height,
+        &payload.proof_consensus,
+    )?;
+
+    Ok(ConnectionEnd {
+        state: ConnectionState::TryOpen,
+        client_id: payload.client_id.clone(),
+        counterparty: payload.counterparty.clone(),
+        versions: vec![version],
+        delay_period: payload.delay_period,
+    })
+}
+
+/// `end` of `connection_id` opened by `ConnOpenAck`, once the counterparty's end is proven to be in
+/// `TryOpen` under `root`, and its client of this chain to be stored as given in `payload`. The
+/// host validates that client beforehand with the `self-client-validation` feature only.
+pub fn open_ack(
+    end: &ConnectionEnd,
+    connection_id: &[u8],
+    payload: &ConnOpenAckPayload,
+    root: &[u8],
+) -> Result<ConnectionEnd, ConnectionError> {
+    if end.state != ConnectionState::Init {
+        return Err(ConnectionError::InvalidState);
+    }
+    validate_connection_id(&payload.counterparty_connection_id)?;
+    let proposed = end.versions.iter().any(|v| {
+        v.identifier == payload.version.identifier
+            && payload
+                .version
+                .features
+                .iter()
+                .all(|f| v.features.contains(f))
+    });
+    if !proposed || !is_supported(&payload.version) {
+        return Err(ConnectionError::VersionNotSupported);
+    }
+
+    let counterparty = Counterparty {
+        connection_id: payload.counterparty_connection_id.clone(),
+        ..end.counterparty.clone()
+    };
+    let expected = ConnectionEnd {
+        state: ConnectionState::TryOpen,
+        client_id: end.counterparty.client_id.clone(),
+        counterparty: Counterparty {
+            client_id: end.client_id.clone(),
+            connection_id: connection_id.to_vec(),
+            prefix: COMMITMENT_PREFIX.to_vec(),
+        },
+        versions: vec![payload.version.clone()],
+        delay_period: end.delay_period,
+    };
+    verify_connection(root, &counterparty, &expected, &payload.proof_try)?;
+    verify_client(
+        root,
+        &counterparty,
+        &payload.client_state,
+        &payload.proof_client,
+        &payload.consensus_state,
+        payload.consensus_height,
+        &payload.proof_consensus,
+    )?;
+
+    Ok(ConnectionEnd {
+        state: ConnectionState::Open,
+        counterparty,
+        versions: vec![payload.version.clone()],
+        ..end.clone()
+    })
+}
+
+/// `end` of `connection_id` opened by `ConnOpenConfirm`, once the counterparty's end is proven to
+/// be open under `root`.
+pub fn open_confirm(
+    end: &ConnectionEnd,
+    connection_id: &[u8],
+    payload: &ConnOpenConfirmPayload,
+    root: &[u8],
+) -> Result<ConnectionEnd, ConnectionError> {
+    if end.state != ConnectionState::TryOpen {
+        return Err(ConnectionError::InvalidState);
+    }
+    let expected = ConnectionEnd {
+        state: ConnectionState::Open,
+        client_id: end.counterparty.client_id.clone(),
+        counterparty: Counterparty {
+            client_id: end.client_id.clone(),
+            connection_id: connection_id.to_vec(),
+            prefix: COMMITMENT_PREFIX.to_vec(),
+        },
+        versions: end.versions.clone(),
+        delay_period: end.delay_period,
+    };
+    verify_connection(root, &end.counterparty, &expected, &payload.proof_ack)?;
+
+    Ok(ConnectionEnd {
+        state: ConnectionState::Open,
+        ..end.clone()
+    })
+}
+
+fn string(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestStore;
+
+    const CLIENT_ID: &[u8] = b"07-tendermint-0";
+    const COUNTERPARTY_CLIENT_ID: &[u8] = b"07-tendermint-3";
+    const COUNTERPARTY_CONNECTION_ID: &[u8] = b"connection-7";
+    const CLIENT_STATE: &[u8] = b"client state";
+    const CONSENSUS_STATE: &[u8] = b"consensus state";
+
+    fn consensus_height() -> Height {
+        Height::new(1, 20)
+    }
+
+    fn counterparty(connection_id: &[u8]) -> Counterparty {
+        Counterparty {
+            client_id: COUNTERPARTY_CLIENT_ID.to_vec(),
+            connection_id: connection_id.to_vec(),
+            prefix: b"ibc".to_vec(),
+        }
+    }
+
+    /// Counterparty's end of the connection `connection_id` of this chain, in `state`.
+    fn counterparty_end(state: ConnectionState, connection_id: &[u8]) -> ConnectionEnd {
+        ConnectionEnd {
+            state,
+            client_id: COUNTERPARTY_CLIENT_ID.to_vec(),
+            counterparty: Counterparty {
+                client_id: CLIENT_ID.to_vec(),
+                connection_id: connection_id.to_vec(),
+                prefix: COMMITMENT_PREFIX.to_vec(),
+            },
+            versions: vec![Version::default()],
+            delay_period: 0,
+        }
+    }
+
+    /// Store of the counterparty, holding its end of the connection and its client of this chain.
+    fn counterparty_store(end: &ConnectionEnd) -> TestStore {
+        let mut store = TestStore::new();
+        store.set(
+            connection_path(COUNTERPARTY_CONNECTION_ID),
+            proto::encode(&end.to_proto()),
+        );
+        store.set(
+            client_state_path(COUNTERPARTY_CLIENT_ID),
+            CLIENT_STATE.to_vec(),
+        );
+        store.set(
+            consensus_state_path(COUNTERPARTY_CLIENT_ID, consensus_height()),
+            CONSENSUS_STATE.to_vec(),
+        );
+        store
+    }
+
+    fn init() -> ConnectionEnd {
+        open_init(ConnOpenInitPayload {
+            client_id: CLIENT_ID.to_vec(),
+            counterparty: counterparty(b""),
+            version: None,
+            delay_period: 0,
+        })
+        .unwrap()
+    }
+
+    fn ack_payload(store: &TestStore) -> ConnOpenAckPayload {
+        ConnOpenAckPayload {
+            connection_id: connection_id(0),
+            counterparty_connection_id: COUNTERPARTY_CONNECTION_ID.to_vec(),
+            version: Version::default(),
+            client_state: CLIENT_STATE.to_vec(),
+            consensus_state: CONSENSUS_STATE.to_vec(),
+            consensus_height: consensus_height(),
+            proof_height: Height::new(1, 30),
+            proof_try: store.prove(&connection_path(COUNTERPARTY_CONNECTION_ID)),
+            proof_client: store.prove(&client_state_path(COUNTERPARTY_CLIENT_ID)),
+            proof_consensus: store.prove(&consensus_state_path(
+                COUNTERPARTY_CLIENT_ID,
+                consensus_height(),
+            )),
+        }
+    }
+
+    fn try_payload(store: &TestStore) -> ConnOpenTryPayload {
+        ConnOpenTryPayload {
+            client_id: CLIENT_ID.to_vec(),
+            counterparty: counterparty(COUNTERPARTY_CONNECTION_ID),
+            delay_period: 0,
+            counterparty_versions: vec![Version::default()],
+            client_state: CLIENT_STATE.to_vec(),
+            consensus_state: CONSENSUS_STATE.to_vec(),
+            consensus_height: consensus_height(),
+            proof_height: Height::new(1, 30),
+            proof_init: store.prove(&connection_path(COUNTERPARTY_CONNECTION_ID)),
+            proof_client: store.prove(&client_state_path(COUNTERPARTY_CLIENT_ID)),
+            proof_consensus: store.prove(&consensus_state_path(
+                COUNTERPARTY_CLIENT_ID,
+                consensus_height(),
+            )),
+        }
+    }
+
+    #[test]
+    fn opens_initialised_connection() {
+        let end = init();
+        assert_eq!(end.state, ConnectionState::Init);
+        assert_eq!(end.versions, vec![Version::default()]);
+
+        let store = counterparty_store(&counterparty_end(
+            ConnectionState::TryOpen,
+            &connection_id(0),
+        ));
+        let open = open_ack(&end, &connection_id(0), &ack_payload(&store), &store.root()).unwrap();
+        assert_eq!(open.state, ConnectionState::Open);
+        assert_eq!(open.counterparty, counterparty(COUNTERPARTY_CONNECTION_ID));
+
+        assert_eq!(
+            open_ack(
+                &open,
+                &connection_id(0),
+                &ack_payload(&store),
+                &store.root()
+            ),
+            Err(ConnectionError::InvalidState)
+        );
+    }
+
+    #[test]
+    fn opens_tried_connection() {
+        let store = counterparty_store(&counterparty_end(ConnectionState::Init, b""));
+        let end = open_try(&try_payload(&store), &store.root()).unwrap();
+        assert_eq!(end.state, ConnectionState::TryOpen);
+        assert_eq!(end.counterparty, counterparty(COUNTERPARTY_CONNECTION_ID));
+
+        let store = counterparty_store(&counterparty_end(ConnectionState::Open, &connection_id(0)));
+        let payload = ConnOpenConfirmPayload {
+            connection_id: connection_id(0),
+            proof_height: Height::new(1, 40),
+            proof_ack: store.prove(&connection_path(COUNTERPARTY_CONNECTION_ID)),
+        };
+        let open = open_confirm(&end, &connection_id(0), &payload, &store.root()).unwrap();
+        assert_eq!(open.state, ConnectionState::Open);
+
+        assert_eq!(
+            open_confirm(&open, &connection_id(0), &payload, &store.root()),
+            Err(ConnectionError::InvalidState)
+        );
+    }
+
+    #[test]
+    fn rejects_unproven_counterparty_state() {
+        let store = counterparty_store(&counterparty_end(ConnectionState::Init, b""));
+        let root = store.root();
+
+        let mut other = store.clone();
+        other.set(b"unrelated".to_vec(), Vec::new());
+        assert!(matches!(
+            open_try(&try_payload(&store), &other.root()),
+            Err(ConnectionError::InvalidConnectionProof(_))
+        ));
+
+        // Counterparty end is not in Init
+        let tried = counterparty_store(&counterparty_end(ConnectionState::TryOpen, b""));
+        assert!(matches!(
+            open_try(&try_payload(&tried), &tried.root()),
+            Err(ConnectionError::InvalidConnectionProof(_))
+        ));
+
+        let mut payload = try_payload(&store);
+        payload.client_state = b"other client state".to_vec();
+        assert!(matches!(
+            open_try(&payload, &root),
+            Err(ConnectionError::InvalidClientStateProof(_))
+        ));
+
+        let mut payload = try_payload(&store);
+        payload.consensus_height = Height::new(1, 21);
+        assert!(matches!(
+            open_try(&payload, &root),
+            Err(ConnectionError::InvalidConsensusStateProof(_))
+        ));
+
+        let mut payload = try_payload(&store);
+        payload.proof_init = vec![0xff; 4];
+        assert_eq!(
+            open_try(&payload, &root),
+            Err(ConnectionError::MalformedProof)
+        );
+    }
+
+    #[test]
+    fn enforces_delay_period() {
+        let end = ConnectionEnd {
+            delay_period: 10_000_000_000,
+            ..init()
+        };
+        // Blocks take up to 6 seconds
+        let block_time = 6_000_000_000;
+        assert_eq!(end.delay_blocks(block_time), 2);
+        assert_eq!(end.delay_blocks(5_000_000_000), 2);
+        assert_eq!(end.delay_blocks(0), 0);
+
+        let (processed_time, processed_height) = (1_000_000_000_000, 100);
+        let verify = |now, own_height| {
+            verify_delay_passed(
+                &end,
+                processed_time,
+                processed_height,
+                now,
+                own_height,
+                block_time,
+            )
+        };
+        assert_eq!(verify(processed_time + end.delay_period, 102), Ok(()));
+        assert_eq!(
+            verify(processed_time + end.delay_period - 1, 102),
+            Err(ConnectionError::DelayPeriodNotPassed)
+        );
+        assert_eq!(
+            verify(processed_time + end.delay_period, 101),
+            Err(ConnectionError::DelayPeriodNotPassed)
+        );
+
+        let end = init();
+        assert_eq!(
+            verify_delay_passed(
+                &end,
+                processed_time,
+                processed_height,
+                processed_time,
+                100,
+                block_time
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_handshakes() {
+        let payload = ConnOpenInitPayload {
+            client_id: b"client".to_vec(),
+            counterparty: counterparty(b""),
+            version: None,
+            delay_period: 0,
+        };
+        assert_eq!(open_init(payload), Err(ConnectionError::InvalidIdentifier));
+
+        let payload = ConnOpenInitPayload {
+            client_id: CLIENT_ID.to_vec(),
+            counterparty: counterparty(b""),
+            version: Some(Version {
+                identifier: "2".into(),
+                features: vec!["ORDER_ORDERED".into()],
+            }),
+            delay_period: 0,
+        };
+        assert_eq!(
+            open_init(payload),
+            Err(ConnectionError::VersionNotSupported)
+        );
+
+        let store = counterparty_store(&counterparty_end(ConnectionState::Init, b""));
+        let mut payload = try_payload(&store);
+        payload.counterparty_versions = vec![Version {
+            identifier: "1".into(),
+            features: vec!["ORDER_NONE".into()],
+        }];
+        assert_eq!(
+            open_try(&payload, &store.root()),
+            Err(ConnectionError::VersionNotSupported)
+        );
+
+        // Version the connection was not initialised with
+        let end = open_init(ConnOpenInitPayload {
+            client_id: CLIENT_ID.to_vec(),
+            counterparty: counterparty(b""),
+            version: Some(Version {
+                identifier: "1".into(),
+                features: vec!["ORDER_ORDERED".into()],
+            }),
+            delay_period: 0,
+        })
+        .unwrap();
+        let store = counterparty_store(&counterparty_end(
+            ConnectionState::TryOpen,
+            &connection_id(0),
+        ));
+        assert_eq!(
+            open_ack(&end, &connection_id(0), &ack_payload(&store), &store.root()),
+            Err(ConnectionError::VersionNotSupported)
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! Conversions from ibc-go protobuf messages into the light client types, and of the pallet's
+//! clients into the ibc-go messages it commits to in the host store.
+
+use alloc::string::String;
+use chrono::{DateTime, TimeZone, Utc};
+use sp_std::convert::TryFrom;
+use sp_std::vec::Vec;
+
+use crate::light_client::types::{
+    BlockId, Commit, CommitSig, Header, PartSetHeader, PublicKey, SignedHeader, Validator,
+    ValidatorSet, Version,
+};
+use crate::proto;
+use crate::types::{ConsensusState, Height, TMHeader, TendermintClient};
+
+/// Reasons a protobuf message cannot be converted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConvertError {
+    /// A required field is not set.
+    MissingField(&'static str),
+    /// A validator public key is not set or of an unknown type.
+    UnsupportedPublicKey,
+    /// A field is out of the range of the light client types.
+    InvalidValue(&'static str),
+}
+
+/// Converts the signed header and validator set of an ibc-go `Header` into a `TMHeader`.
+pub fn tm_header(header: &proto::Header) -> Result<TMHeader, ConvertError> {
+    let signed_header = header
+        .signed_header
+        .as_ref()
+        .ok_or(ConvertError::MissingField("signed_header"))?;
+    let validator_set = header
+        .validator_set
+        .as_ref()
+        .ok_or(ConvertError::MissingField("validator_set"))?;
+
+    Ok(TMHeader {
+        signed_header: self::signed_header(signed_header)?,
+        validator_set: self::validator_set(validator_set)?,
+    })
+}
+
+/// Converts a protobuf `ValidatorSet` into the light client's validator set type.
+pub fn validator_set(validator_set: &proto::ValidatorSet) -> Result<ValidatorSet, ConvertError> {
+    Ok(ValidatorSet {
+        validators: validator_set
+            .validators
+            .iter()
+            .map(validator)
+            .collect::<Result<Vec<_>, _>>()?,
+        proposer: validator_set.proposer.as_ref().map(validator).transpose()?,
+    })
+}
+
+fn signed_header(signed_header: &proto::SignedHeader) -> Result<SignedHeader, ConvertError> {
+    let header = signed_header
+        .header
+        .as_ref()
+        .ok_or(ConvertError::MissingField("header"))?;
+    let commit = signed_header
+        .commit
+        .as_ref()
+        .ok_or(ConvertError::MissingField("commit"))?;
+    let version = header.version.clone().unwrap_or_default();
+
+    Ok(SignedHeader {
+        header: Header {
+            version: Version {
+                block: version.block,
+                app: version.app,
+            },
+            chain_id: header.chain_id.clone(),
+            height: non_negative(header.height, "height")?,
+            time: time(header.time.as_ref())?,
+            last_block_id: block_id(header.last_block_id.as_ref()),
+            last_commit_hash: header.last_commit_hash.clone(),
+            data_hash: header.data_hash.clone(),
+            validators_hash: header.validators_hash.clone(),
+            next_validators_hash: header.next_validators_hash.clone(),
+            consensus_hash: header.consensus_hash.clone(),
+            app_hash: header.app_hash.clone(),
+            last_results_hash: header.last_results_hash.clone(),
+            evidence_hash: header.evidence_hash.clone(),
+            proposer_address: header.proposer_address.clone(),
+        },
+        commit: Commit {
+            height: non_negative(commit.height, "commit.height")?,
+            round: non_negative(commit.round.into(), "commit.round")? as u32,
+            block_id: block_id(commit.block_id.as_ref()),
+            signatures: commit
+                .signatures
+                .iter()
+                .map(commit_sig)
+                .collect::<Result<Vec<_>, _>>()?,
+        },
+    })
+}
+
+fn commit_sig(sig: &proto::CommitSig) -> Result<CommitSig, ConvertError> {
+    Ok(CommitSig {
+        block_id_flag: u8::try_from(sig.block_id_flag)
+            .map_err(|_| ConvertError::InvalidValue("block_id_flag"))?
+            .into(),
+        validator_address: sig.validator_address.clone(),
+        timestamp: time(sig.timestamp.as_ref())?,
+        signature: sig.signature.clone(),
+    })
+}
+
+fn validator(validator: &proto::Validator) -> Result<Validator, ConvertError> {
+    let pub_key = match validator.pub_key.as_ref().and_then(|k| k.sum.as_ref()) {
+        Some(proto::public_key::Sum::Ed25519(key)) => PublicKey::Ed25519(key.clone()),
+        Some(proto::public_key::Sum::Secp256k1(key)) => PublicKey::Secp256k1(key.clone()),
+        None => return Err(ConvertError::UnsupportedPublicKey),
+    };
+    Ok(Validator {
+        address: validator.address.clone(),
+        pub_key,
+        voting_power: non_negative(validator.voting_power, "voting_power")?,
+        proposer_priority: validator.proposer_priority,
+    })
+}
+
+fn block_id(block_id: Option<&proto::BlockId>) -> BlockId {
+    let block_id = block_id.cloned().unwrap_or_default();
+    let parts = block_id.part_set_header.unwrap_or_default();
+    BlockId {
+        hash: block_id.hash,
+        parts: PartSetHeader {
+            total: parts.total,
+            hash: parts.hash,
+        },
+    }
+}
+
+/// ibc-go `ClientState` of `client`, at `latest_height`.
+pub fn proto_client_state(client: &TendermintClient, latest_height: Height) -> proto::ClientState {
+    let duration = |seconds: u64| proto::Duration {
+        seconds: seconds as i64,
+        nanos: 0,
+    };
+    proto::ClientState {
+        chain_id: String::from_utf8_lossy(&client.chain_id).into(),
+        trust_level: Some(proto::Fraction {
+            numerator: client.trust_threshold.numerator,
+            denominator: client.trust_threshold.denominator,
+        }),
+        trusting_period: Some(duration(client.trusting_period)),
+        unbonding_period: Some(duration(client.unbonding_period)),
+        max_clock_drift: Some(duration(client.max_clock_drift)),
+        frozen_height: None,
+        latest_height: Some(proto::Height {
+            revision_number: latest_height.revision_number,
+            revision_height: latest_height.revision_height,
+        }),
+        upgrade_path: client
+            .upgrade_path
+            .iter()
+            .map(|key| String::from_utf8_lossy(key).into())
+            .collect(),
+        allow_update_after_expiry: false,
+        allow_update_after_misbehaviour: false,
+    }
+}
+
+/// ibc-go `ConsensusState` of the header `state` trusts.
+pub fn proto_consensus_state(state: &ConsensusState) -> proto::ConsensusState {
+    let header = state.state.header();
+    proto::ConsensusState {
+        timestamp: Some(proto_time(header.time)),
+        root: Some(proto::MerkleRoot {
+            hash: header.app_hash.clone(),
+        }),
+        next_validators_hash: header.next_validators_hash.clone(),
+    }
+}
+
+/// Protobuf `Timestamp` of `time`.
+pub fn proto_time(time: DateTime<Utc>) -> proto::Timestamp {
+    proto::Timestamp {
+        seconds: time.timestamp(),
+        nanos: time.timestamp_subsec_nanos() as i32,
+    }
+}
+
+/// Unset timestamps are Go's zero time, as in Tendermint.
+fn time(time: Option<&proto::Timestamp>) -> Result<DateTime<Utc>, ConvertError> {
+    match time {
+        Some(time) => Utc
+            .timestamp_opt(time.seconds, time.nanos as u32)
+            .single()
+            .ok_or(ConvertError::InvalidValue("timestamp")),
+        None => Ok(Utc.ymd(1, 1, 1).and_hms(0, 0, 0)),
+    }
+}
+
+fn non_negative(value: i64, field: &'static str) -> Result<u64, ConvertError> {
+    if value < 0 {
+        return Err(ConvertError::InvalidValue(field));
+    }
+    Ok(value as u64)
+}
@@ -0,0 +1,25 @@
+//! Host store: the ICS-24 paths counterparties verify this chain's state at, written to raw storage
+//! keys under the commitment prefix. A Substrate read proof of a key against the state root of a
+//! block proves the value at the path, or its absence, at that block.
+
+use frame_support::storage::unhashed;
+use sp_std::vec::Vec;
+
+use crate::connection::COMMITMENT_PREFIX;
+
+/// Storage key of the value at ICS-24 `path`: `{COMMITMENT_PREFIX}/{path}`.
+pub fn key(path: &[u8]) -> Vec<u8> {
+    [COMMITMENT_PREFIX, b"/", path].concat()
+}
+
+pub fn get(path: &[u8]) -> Option<Vec<u8>> {
+    unhashed::get_raw(&key(path))
+}
+
+pub fn set(path: &[u8], value: &[u8]) {
+    unhashed::put_raw(&key(path), value)
+}
+
+pub fn remove(path: &[u8]) {
+    unhashed::kill(&key(path))
+}
@@ -0,0 +1,183 @@
+//! Property tests of the light client's safety invariants, over random evolutions of the validator
+//! set of a generated chain.
+//!
+//! Each case creates a client of a `TestChain` and submits a sequence of updates, in which the chain
+//! moves forward, may hand over to a new validator set, and some validators do not sign. Updates
+//! are submitted shortly after their header, or late enough for the client to have expired. After
+//! every update:
+//!
+//! - the client's latest height has not decreased,
+//! - an update signed by no more than a third of the voting power of the validators the client
+//!   trusts was rejected,
+//! - an update submitted once the client expired was rejected and left the client unchanged,
+//! - the height of `ClientInfoMap` is the one of the stored header.
+
+use chrono::{DateTime, Duration, Utc};
+use codec::Encode;
+use frame_support::StorageMap;
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use crate::mock::*;
+use crate::testing::{TestChain, TestValidator, Vote};
+use crate::types::Height;
+use crate::{ClientInfoMap, ConsensusHeights, TMClientStorage};
+
+const SIGNER: u64 = 1;
+/// Trusting period of the generated clients, in seconds.
+const TRUSTING_PERIOD: u64 = 3_600;
+/// Validators are drawn from the keys of these seeds.
+const SEEDS: u32 = 8;
+
+#[derive(Clone, Debug)]
+struct Step {
+    blocks: u64,
+    secs: i64,
+    next_validators: Option<Vec<TestValidator>>,
+    absent: Vec<bool>,
+    /// Seconds between the header and its submission.
+    delay: i64,
+}
+
+/// Header the client trusts, as far as the invariants are concerned.
+struct Trusted {
+    height: Height,
+    time: DateTime<Utc>,
+    next_validators: Vec<TestValidator>,
+}
+
+fn validator_set() -> impl Strategy<Value = Vec<TestValidator>> {
+    proptest::sample::subsequence((0..SEEDS).collect::<Vec<_>>(), 1..=SEEDS as usize)
+        .prop_flat_map(|seeds| {
+            let count = seeds.len();
+            (Just(seeds), vec(1u64..100, count))
+        })
+        .prop_map(|(seeds, powers)| {
+            seeds
+                .into_iter()
+                .zip(powers)
+                .map(|(seed, power)| TestValidator::new(seed, power))
+                .collect()
+        })
+}
+
+fn step() -> impl Strategy<Value = Step> {
+    (
+        1u64..4,
+        1i64..600,
+        proptest::option::weighted(0.3, validator_set()),
+        vec(proptest::bool::weighted(0.2), SEEDS as usize),
+        prop_oneof![9 => 1i64..20, 1 => 3_000i64..5_000],
+    )
+        .prop_map(|(blocks, secs, next_validators, absent, delay)| Step {
+            blocks,
+            secs,
+            next_validators,
+            absent,
+            delay,
+        })
+}
+
+fn set_time(time: DateTime<Utc>) {
+    Timestamp::set_timestamp(time.timestamp_millis() as u64);
+}
+
+/// Encoded storage of `client_id`.
+fn client_storage(client_id: &[u8]) -> (Vec<u8>, Vec<u8>, Vec<Height>) {
+    (
+        TMClientStorage::get(client_id).encode(),
+        ClientInfoMap::get(client_id).encode(),
+        ConsensusHeights::get(client_id),
+    )
+}
+
+/// Voting power of `trusted` held by validators that sign the header of `chain`.
+fn trusted_power(trusted: &[TestValidator], chain: &TestChain, votes: &[Vote]) -> (u64, u64) {
+    let signed = trusted
+        .iter()
+        .filter(|validator| {
+            chain
+                .validators
+                .iter()
+                .zip(votes)
+                .any(|(signer, vote)| signer.seed == validator.seed && *vote == Vote::Commit)
+        })
+        .map(|validator| validator.voting_power)
+        .sum();
+    let total = trusted.iter().map(|validator| validator.voting_power).sum();
+    (signed, total)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn preserves_safety_invariants(initial in validator_set(), steps in vec(step(), 1..8)) {
+        new_test_ext().execute_with(|| -> Result<(), TestCaseError> {
+            let client_id = b"proptest".to_vec();
+            let mut chain = TestChain::new("testchain-1", initial);
+            set_time(chain.time + Duration::seconds(1));
+            let payload = chain.create_payload(client_id.clone(), TRUSTING_PERIOD);
+            TemplateModule::init_client(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap(),
+            )
+            .unwrap();
+            let mut trusted = Trusted {
+                height: chain.ibc_height(),
+                time: chain.time,
+                next_validators: chain.next_validators.clone(),
+            };
+
+            for step in steps {
+                chain.advance(step.blocks, step.secs);
+                if let Some(next_validators) = step.next_validators {
+                    chain.change_validators(next_validators);
+                }
+                let votes: Vec<Vote> = step
+                    .absent
+                    .iter()
+                    .map(|absent| if *absent { Vote::Absent } else { Vote::Commit })
+                    .collect();
+                let mut payload = chain.update_payload(client_id.clone());
+                payload.header = chain.header_with_votes(&votes);
+
+                let now = chain.time + Duration::seconds(step.delay);
+                set_time(now);
+                let before = client_storage(&client_id);
+                let result = TemplateModule::update_client(
+                    Origin::signed(SIGNER),
+                    serde_json::to_vec(&payload).unwrap(),
+                );
+                let after = client_storage(&client_id);
+
+                let latest = TemplateModule::latest_height(&client_id).unwrap();
+                prop_assert!(latest >= trusted.height);
+
+                let (signed, total) = trusted_power(&trusted.next_validators, &chain, &votes);
+                if signed * 3 <= total {
+                    prop_assert!(result.is_err(), "accepted header signed by {}/{}", signed, total);
+                }
+                if trusted.time + Duration::seconds(TRUSTING_PERIOD as i64) <= now {
+                    prop_assert!(result.is_err(), "expired client was updated");
+                }
+
+                if result.is_ok() {
+                    prop_assert_eq!(latest, chain.ibc_height());
+                    trusted = Trusted {
+                        height: latest,
+                        time: chain.time,
+                        next_validators: chain.next_validators.clone(),
+                    };
+                } else {
+                    prop_assert_eq!(&before, &after);
+                }
+
+                let stored = TMClientStorage::get(&client_id).client.state.unwrap();
+                let info = ClientInfoMap::get(&client_id);
+                prop_assert_eq!(info.last_block.revision_height, stored.state.header().height);
+            }
+            Ok(())
+        })?;
+    }
+}
@@ -1,41 +1,116 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 /// Wormhole TendermintClient Pallet. Allows verification of Tendermint block headers on the substrate chain.
-use frame_support::{decl_error, decl_event, decl_module, decl_storage, dispatch, ensure};
-use frame_system::{self as system, ensure_signed};
-
-use chrono::Utc;
-use core::time::Duration;
-use tendermint_light_client::{
-    validate_initial_signed_header_and_valset, verify_single, LightSignedHeader, LightValidator,
-    LightValidatorSet, TrustThresholdFraction, TrustedState,
-};
+use frame_support::{decl_error, decl_event, decl_module, decl_storage, dispatch::{self, DispatchResultWithPostInfo}, ensure, traits::Get, transactional, weights::Weight};
+use frame_system::{self as system, ensure_root, ensure_signed};
+
+use chrono::{DateTime, TimeZone, Utc};
+#[cfg(feature = "self-client-validation")]
+use codec::Decode;
+use prost::Message;
+use sp_runtime::traits::UniqueSaturatedInto;
 
 extern crate alloc;
 extern crate core;
 extern crate std;
+use alloc::string::ToString;
 use log::{debug, error};
 use sp_std::vec::Vec;
 
-mod types;
+pub mod channel;
+mod commitment;
+pub mod connection;
+mod convert;
+pub mod host;
+pub mod light_client;
+pub mod module;
+mod proto;
+pub mod self_client;
+pub mod types;
+pub mod validation;
+pub mod weights;
 
+use crate::channel::{
+    AcknowledgePacketPayload, ChanCloseConfirmPayload, ChanOpenAckPayload, ChanOpenConfirmPayload, ChanOpenInitPayload,
+    ChanOpenTryPayload, ChannelEnd, ChannelError, ChannelState, Order, Packet, RecvPacketPayload, TimeoutPacketPayload,
+};
+use crate::connection::{
+    ConnOpenAckPayload, ConnOpenConfirmPayload, ConnOpenInitPayload, ConnOpenTryPayload, ConnectionEnd, ConnectionError,
+};
+use crate::convert::ConvertError;
+use crate::light_client::{
+    validate_initial_signed_header_and_valset, verify_single, Header, Options, TrustThresholdFraction,
+    TrustedState, ValidatorSet, VerificationError,
+};
+use crate::module::IbcModules;
+use crate::self_client::{SelfClientError, SelfClientState, SelfConsensusState};
 use crate::types::{
-    ConsensusState, TMClientInfo, TMClientStorageWrapper, TMCreateClientPayload,
-    TMUpdateClientPayload, TendermintClient,
+    ConsensusState, ConsensusStateWrapper, ExportedClient, Height, RetentionPolicy, TMBackfillPayload, TMClientInfo, TMClientStorageWrapper,
+    TMCreateClientPayload, TMHeader, TMUpdateClientPayload, TMUpgradeClientPayload,
+    TendermintClient,
 };
+use crate::weights::WeightInfo;
 
-#[cfg(test)]
-mod mock;
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
+
+#[cfg(any(test, all(feature = "test-utils", feature = "std")))]
+pub mod mock;
 
 #[cfg(test)]
 mod tests;
 
+#[cfg(test)]
+mod replay;
+
+#[cfg(test)]
+mod invariants;
+
 /// The pallet's configuration trait.
-pub trait Trait: system::Trait {
+pub trait Trait: system::Trait + pallet_timestamp::Trait {
     // Add other types and constants required to configure this pallet.
 
     /// The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+    /// Maximum number of consensus states kept per client. Older states are pruned first.
+    type MaxConsensusStates: Get<u32>;
+
+    /// Number of steps pruning may take per block: each client checked and each consensus state removed is one step.
+    /// Must be at least 2 for any consensus state to be pruned.
+    type PruningBudget: Get<u32>;
+
+    /// Modules bound to ports, which handle the channels of their port and the packets sent over them.
+    type Modules: IbcModules;
+
+    /// Chain id the counterparty's clients of this chain must track.
+    type SelfChainId: Get<&'static [u8]>;
+
+    /// Id of the GRANDPA authority set currently finalizing this chain's blocks.
+    type AuthoritySetId: Get<u64>;
+
+    /// Number of this chain's most recent blocks whose consensus states are kept, to validate the counterparty's
+    /// clients of this chain against. The parent block's is always kept.
+    type SelfHistory: Get<u32>;
+
+    /// Maximum time a block of this chain is expected to take, in milliseconds. Converts the delay period of connections
+    /// into a number of blocks.
+    type MaxExpectedTimePerBlock: Get<u64>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+/// Least number of bytes an encoded validator takes, its public key. Bounds the number of validators of a header before
+/// it is decoded.
+const MIN_VALIDATOR_BYTES: usize = 32;
+
+/// Upper bound of the number of validators in an encoded header or payload of `len` bytes.
+fn max_validators(len: usize) -> u32 {
+    (len / MIN_VALIDATOR_BYTES) as u32
 }
 
 decl_storage! {
@@ -46,6 +121,64 @@ decl_storage! {
         ClientInfoMap get(fn client_info): map hasher(blake2_128_concat) Vec<u8> => TMClientInfo;
         /// Lists all available clients
         AvailableClients get(fn clients): Vec<Vec<u8>>;
+        /// Stores each consensus state a client has trusted, by client_id and height
+        ConsensusStates get(fn consensus_state): double_map hasher(blake2_128_concat) Vec<u8>, hasher(blake2_128_concat) Height => Option<ConsensusStateWrapper>;
+        /// Lists the heights of each client's stored consensus states, in ascending order
+        ConsensusHeights get(fn consensus_heights): map hasher(blake2_128_concat) Vec<u8> => Vec<Height>;
+        /// Stores the block number each consensus state was stored at, by client_id and height
+        ProcessedHeights get(fn processed_height): double_map hasher(blake2_128_concat) Vec<u8>, hasher(blake2_128_concat) Height => u64;
+        /// Stores the client_id of the client trusted for each chain_id, as set by governance
+        CanonicalClient get(fn canonical_client): map hasher(blake2_128_concat) Vec<u8> => Option<Vec<u8>>;
+        /// Index in `AvailableClients` of the next client to check for prunable consensus states
+        PruningCursor: u32;
+        /// Stores each connection end by its connection_id
+        Connections get(fn connection): map hasher(blake2_128_concat) Vec<u8> => Option<ConnectionEnd>;
+        /// Lists the connection_ids of each client's connections
+        ClientConnections get(fn client_connections): map hasher(blake2_128_concat) Vec<u8> => Vec<Vec<u8>>;
+        /// Sequence of the next connection_id
+        NextConnectionSequence: u64;
+        /// Stores each channel end by its port_id and channel_id
+        Channels get(fn channel): double_map hasher(blake2_128_concat) Vec<u8>, hasher(blake2_128_concat) Vec<u8> => Option<ChannelEnd>;
+        /// Sequence of the next channel_id
+        NextChannelSequence: u64;
+        /// Sequence of the next packet sent on each channel, by port_id and channel_id
+        NextSequenceSend get(fn next_sequence_send): double_map hasher(blake2_128_concat) Vec<u8>, hasher(blake2_128_concat) Vec<u8> => u64;
+        /// Sequence of the next packet received on each ordered channel, by port_id and channel_id
+        NextSequenceRecv get(fn next_sequence_recv): double_map hasher(blake2_128_concat) Vec<u8>, hasher(blake2_128_concat) Vec<u8> => u64;
+        /// Sequence of the next packet acknowledged on each ordered channel, by port_id and channel_id
+        NextSequenceAck get(fn next_sequence_ack): double_map hasher(blake2_128_concat) Vec<u8>, hasher(blake2_128_concat) Vec<u8> => u64;
+        /// Stores the commitment to each packet sent and not yet acknowledged or timed out, by (port_id, channel_id) and sequence
+        PacketCommitments get(fn packet_commitment): double_map hasher(blake2_128_concat) (Vec<u8>, Vec<u8>), hasher(blake2_128_concat) u64 => Option<Vec<u8>>;
+        /// Marks each packet received on unordered channels, by (port_id, channel_id) and sequence
+        PacketReceipts get(fn packet_receipt): double_map hasher(blake2_128_concat) (Vec<u8>, Vec<u8>), hasher(blake2_128_concat) u64 => bool;
+        /// Stores the commitment to the acknowledgement of each packet received, by (port_id, channel_id) and sequence
+        PacketAcknowledgements get(fn packet_acknowledgement): double_map hasher(blake2_128_concat) (Vec<u8>, Vec<u8>), hasher(blake2_128_concat) u64 => Option<Vec<u8>>;
+        /// Stores the consensus state of this chain at each of its `SelfHistory` most recent blocks, by block number
+        SelfConsensusStates get(fn self_consensus_state): map hasher(blake2_128_concat) u64 => Option<SelfConsensusState>;
+        /// Id of the GRANDPA authority set current at the start of this block, recorded with its consensus state once it is
+        /// the parent
+        BlockAuthoritySetId: Option<u64>;
+    }
+    add_extra_genesis {
+        /// Clients created at genesis, as the payloads `init_client` takes
+        config(clients): Vec<TMCreateClientPayload>;
+        /// Clients exported from another chain, stored as they are without verifying their consensus states
+        config(imported_clients): Vec<ExportedClient>;
+        build(|config: &GenesisConfig| {
+            for payload in &config.clients {
+                let client_id = payload.client_id.clone();
+                let (tmclient, header) = validation::client_from_payload(payload.clone());
+                if let Err(e) = Module::<T>::insert_client(tmclient, header) {
+                    panic!("Invalid genesis client {}: {:?}", std::string::String::from_utf8_lossy(&client_id), e);
+                }
+            }
+            for exported in &config.imported_clients {
+                let client_id = exported.client.client_id.clone();
+                if let Err(e) = Module::<T>::import_client(exported.clone()) {
+                    panic!("Invalid imported client {}: {}", std::string::String::from_utf8_lossy(&client_id), e);
+                }
+            }
+        });
     }
 }
 
@@ -54,12 +187,55 @@ decl_event!(
     pub enum Event<T>
     where
         AccountId = <T as system::Trait>::AccountId,
-        Height = u64,
+        Height = Height,
     {
-        /// Event `ClientCreated`/`ClientUpdated` is declared with a parameter of the type `string` (name), `string` (chainid), `u64` (height)
+        /// Event `ClientCreated`/`ClientUpdated` is declared with a parameter of the type `string` (name), `string` (chainid), `Height` (height)
         /// and is fired when a client is created/updated respectively.
         ClientCreated(AccountId, Vec<u8>, Vec<u8>, Height),
         ClientUpdated(AccountId, Vec<u8>, Vec<u8>, Height),
+        /// Event `ClientUpgraded` is declared with a parameter of the type `string` (name), `string` (new chainid), `Height` (height)
+        /// and is fired when a client is moved to an upgraded chain.
+        ClientUpgraded(AccountId, Vec<u8>, Vec<u8>, Height),
+        /// Event `ConsensusStateBackfilled` is declared with a parameter of the type `string` (name), `Height` (height)
+        /// and is fired when a consensus state is added below a client's latest height.
+        ConsensusStateBackfilled(AccountId, Vec<u8>, Height),
+        /// Event `CanonicalClientSet` is declared with a parameter of the type `string` (chainid), `string` (name)
+        /// and is fired when the canonical client of a chain is changed.
+        CanonicalClientSet(Vec<u8>, Vec<u8>),
+        /// Event `CanonicalClientCleared` is declared with a parameter of the type `string` (chainid)
+        /// and is fired when the canonical client of a chain is removed.
+        CanonicalClientCleared(Vec<u8>),
+        /// Event `ConsensusStatesPruned` is declared with a parameter of the type `string` (name), `Height` (from), `Height` (to)
+        /// and is fired when the consensus states of a client from `from` up to `to` inclusive are pruned.
+        ConsensusStatesPruned(Vec<u8>, Height, Height),
+        /// Event `ConnectionOpenInit`/`ConnectionOpenTry` is declared with a parameter of the type `string` (connection id), `string` (name)
+        /// and is fired when a connection is opened by `ConnOpenInit`/`ConnOpenTry` respectively.
+        ConnectionOpenInit(AccountId, Vec<u8>, Vec<u8>),
+        ConnectionOpenTry(AccountId, Vec<u8>, Vec<u8>),
+        /// Event `ConnectionOpenAck`/`ConnectionOpenConfirm` is declared with a parameter of the type `string` (connection id), `string` (counterparty connection id)
+        /// and is fired when a connection is opened on both ends by `ConnOpenAck`/`ConnOpenConfirm` respectively.
+        ConnectionOpenAck(AccountId, Vec<u8>, Vec<u8>),
+        ConnectionOpenConfirm(AccountId, Vec<u8>, Vec<u8>),
+        /// Event `ChannelOpenInit`/`ChannelOpenTry`/`ChannelOpenAck`/`ChannelOpenConfirm` is declared with a parameter of the type `string` (port id),
+        /// `string` (channel id) and is fired when a channel is opened by the handshake step respectively.
+        ChannelOpenInit(AccountId, Vec<u8>, Vec<u8>),
+        ChannelOpenTry(AccountId, Vec<u8>, Vec<u8>),
+        ChannelOpenAck(AccountId, Vec<u8>, Vec<u8>),
+        ChannelOpenConfirm(AccountId, Vec<u8>, Vec<u8>),
+        /// Event `ChannelCloseInit`/`ChannelCloseConfirm` is declared with a parameter of the type `string` (port id), `string` (channel id)
+        /// and is fired when a channel is closed by `ChanCloseInit`/`ChanCloseConfirm` respectively.
+        ChannelCloseInit(AccountId, Vec<u8>, Vec<u8>),
+        ChannelCloseConfirm(AccountId, Vec<u8>, Vec<u8>),
+        /// Event `SendPacket` is declared with a parameter of the type `Packet` (packet)
+        /// and is fired when a module sends a packet.
+        SendPacket(Packet),
+        /// Event `RecvPacket` is declared with a parameter of the type `Packet` (packet), `bytes` (acknowledgement)
+        /// and is fired when a packet is received and acknowledged by the module of its port.
+        RecvPacket(AccountId, Packet, Vec<u8>),
+        /// Event `AcknowledgePacket`/`TimeoutPacket` is declared with a parameter of the type `Packet` (packet)
+        /// and is fired when a packet sent is acknowledged by the counterparty/times out respectively.
+        AcknowledgePacket(AccountId, Packet),
+        TimeoutPacket(AccountId, Packet),
     }
 );
 
@@ -80,6 +256,81 @@ decl_error! {
         ParseError,
         /// Error occurred validating block.
         ValidationError,
+        /// Client tracks a different chain id.
+        ChainIdMismatch,
+        /// Client has no upgrade path to look up upgraded states under.
+        UpgradePathNotSet,
+        /// Upgraded client state is not ahead of the client's latest height.
+        InvalidUpgrade,
+        /// Proof of the upgraded client or consensus state is invalid.
+        InvalidUpgradeProof,
+        /// Client's latest consensus state is outside of the trusting period.
+        ClientExpired,
+        /// Validator public key is of a type signatures cannot be verified for.
+        UnsupportedPublicKey,
+        /// Header is of a block protocol version that is not supported, or differs from the trusted one.
+        UnsupportedBlockVersion,
+        /// Header height is not above the client's trusted height.
+        NonIncreasingHeight,
+        /// Header time is not after the client's trusted time.
+        NonIncreasingTime,
+        /// Header time is ahead of the block time by more than the client's max clock drift.
+        HeaderFromFuture,
+        /// Validator set does not match the hash committed to by the header.
+        ValidatorSetHashMismatch,
+        /// Commit is not for the submitted header.
+        CommitHeaderMismatch,
+        /// A commit signature does not verify.
+        InvalidSignature,
+        /// Validators that signed the commit do not have enough voting power.
+        InsufficientVotingPower,
+        /// Client already has a consensus state at the header's height.
+        ConsensusStateExists,
+        /// Header is not between two heights the client trusts.
+        BackfillOutOfRange,
+        /// Header is not the parent of the trusted header above it.
+        HashLinkMismatch,
+        /// Identifier is not a valid ICS-24 identifier.
+        InvalidIdentifier,
+        /// No connection exists with the connection id.
+        ConnectionNotFound,
+        /// Connection is not in the state the handshake step moves it from.
+        InvalidConnectionState,
+        /// No connection version is supported by both ends.
+        ConnectionVersionNotSupported,
+        /// Proof of the counterparty's state is invalid.
+        InvalidCounterpartyProof,
+        /// Client has no consensus state at the proof height.
+        ConsensusStateNotFound,
+        /// No module is bound to the port.
+        PortNotBound,
+        /// Connection of the channel is not open.
+        ConnectionNotOpen,
+        /// No channel exists with the port id and channel id.
+        ChannelNotFound,
+        /// Channel is not in the state the step moves it from.
+        InvalidChannelState,
+        /// Channel ordering is not supported by the connection.
+        ChannelOrderingNotSupported,
+        /// Packet is not sent over the channel, or has no timeout.
+        InvalidPacket,
+        /// Timeout of the packet has passed.
+        PacketTimedOut,
+        /// Timeout of the packet has not passed on the counterparty.
+        PacketNotTimedOut,
+        /// Sequence of the packet is not the next one of the ordered channel.
+        InvalidPacketSequence,
+        /// Packet was already received.
+        PacketAlreadyReceived,
+        /// Packet is not committed to: it was not sent, or was already acknowledged or timed out.
+        PacketNotCommitted,
+        /// Delay period of the connection has not passed since the consensus state at the proof height was stored.
+        DelayPeriodNotPassed,
+        /// Counterparty's client of this chain does not track this chain. Only checked with the `self-client-validation`
+        /// feature.
+        InvalidSelfClient,
+        /// Client state is frozen for misbehaviour.
+        ClientFrozen,
     }
 }
 
@@ -96,10 +347,24 @@ decl_module! {
         // this is needed only if you are using events in your pallet
         fn deposit_event() = default;
 
+        /// Maximum number of consensus states kept per client.
+        const MaxConsensusStates: u32 = T::MaxConsensusStates::get();
+
+        /// Number of steps pruning may take per block.
+        const PruningBudget: u32 = T::PruningBudget::get();
+
+        /// Number of this chain's most recent blocks whose consensus states are kept.
+        const SelfHistory: u32 = T::SelfHistory::get();
+
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            Self::record_self_consensus_state(n).saturating_add(Self::prune_consensus_states())
+        }
+
         /// Client initialisation entry point.
         /// takes json encoded `TMCreateClientPayload` struct.
-        #[weight = 100_000]
-        pub fn init_client(origin, payload: Vec<u8>) -> dispatch::DispatchResult {
+        /// Charged for the validators of the header, bounded by the size of the payload until it is decoded.
+        #[weight = T::WeightInfo::init_client(max_validators(payload.len()))]
+        pub fn init_client(origin, payload: Vec<u8>) -> DispatchResultWithPostInfo {
             // Check it was signed
             let signer = ensure_signed(origin)?;
 
@@ -110,56 +375,75 @@ decl_module! {
                 Error::<T>::DeserializeError
             })?;
 
-            // Validating if client already exists
-            ensure!(!TMClientStorage::contains_key(init_client_payload.client_id.as_bytes().to_vec()), Error::<T>::ClientAlreadyInitialized);
+            let (tmclient, header) = validation::client_from_payload(init_client_payload);
+            let weight = T::WeightInfo::init_client(header.validator_set.validators.len() as u32);
+            Self::create_client(signer, tmclient, header)?;
+            Ok(Some(weight).into())
+        }
 
-            let header: LightSignedHeader = init_client_payload.header.signed_header;
-            let validator_set: LightValidatorSet<LightValidator> = init_client_payload.header.validator_set;
-            let chain_id = header.header().chain_id.clone();
+        /// Client initialisation entry point for ibc-go messages.
+        /// takes protobuf encoded `Any`s of `ibc.lightclients.tendermint.v1.ClientState`, `ConsensusState`
+        /// and the `Header` the consensus state was derived from.
+        /// Charged for the validators of the header, bounded by its size until it is decoded.
+        #[weight = T::WeightInfo::init_client_proto(max_validators(header.len()))]
+        pub fn init_client_proto(origin, client_id: Vec<u8>, client_state: Vec<u8>, consensus_state: Vec<u8>, header: Vec<u8>) -> DispatchResultWithPostInfo {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
 
-            validate_initial_signed_header_and_valset(&header, &validator_set).map_err(|e| {
-              error!("Validation Error: {}", e);
-              Error::<T>::ValidationError
-            })?;
+            let client_state: proto::ClientState = Self::decode_any(&client_state, proto::TENDERMINT_CLIENT_STATE_TYPE_URL)?;
+            let consensus_state: proto::ConsensusState = Self::decode_any(&consensus_state, proto::TENDERMINT_CONSENSUS_STATE_TYPE_URL)?;
+            let header: proto::Header = Self::decode_any(&header, proto::TENDERMINT_HEADER_TYPE_URL)?;
+            debug!("Submitted protobuf client state: {:?}, consensus state: {:?}", client_state, consensus_state);
 
-            let state: ConsensusState = ConsensusState{
-                state: 	TrustedState::new(header.clone(), validator_set),
-                last_update: Utc::now(),
+            // The consensus state must be the one committed to by the header
+            let tm_header = header.signed_header.as_ref().and_then(|sh| sh.header.as_ref()).ok_or(Error::<T>::DeserializeError)?;
+            let latest_height = client_state.latest_height.as_ref().ok_or(Error::<T>::DeserializeError)?;
+            ensure!(tm_header.chain_id == client_state.chain_id, Error::<T>::ChainIdMismatch);
+            // Frozen clients are not modeled, so only unfrozen ones (a zero frozen height) are accepted
+            ensure!(
+                client_state.frozen_height.as_ref().map_or(true, |h| *h == proto::Height::default()),
+                Error::<T>::ClientFrozen
+            );
+            ensure!(
+                latest_height.revision_height == tm_header.height as u64
+                    && consensus_state.timestamp == tm_header.time
+                    && consensus_state.root.as_ref().map(|r| &r.hash) == Some(&tm_header.app_hash)
+                    && consensus_state.next_validators_hash == tm_header.next_validators_hash,
+                Error::<T>::ValidationError
+            );
+
+            let trust_threshold = match client_state.trust_level {
+                Some(level) => TrustThresholdFraction::new(level.numerator, level.denominator).ok_or_else(|| {
+                    error!("Invalid trust level: {}/{}", level.numerator, level.denominator);
+                    Error::<T>::ParseError
+                })?,
+                None => TrustThresholdFraction::default(),
             };
+            let defaults = TendermintClient::default();
+            let seconds = |d: Option<proto::Duration>, default: u64| d.map(|d| d.seconds as u64).unwrap_or(default);
 
             let tmclient: TendermintClient = TendermintClient{
-                state: Some(state.clone()),
-                trusting_period: init_client_payload.trusting_period,
-                client_id: init_client_payload.client_id.as_bytes().to_vec(),
-                max_clock_drift: init_client_payload.max_clock_drift,
-                unbonding_period: init_client_payload.unbonding_period,
-                chain_id: chain_id.as_str().as_bytes().to_vec(),
-                trust_threshold: TrustThresholdFraction::default(),
+                state: None,
+                trusting_period: seconds(client_state.trusting_period, defaults.trusting_period),
+                client_id,
+                max_clock_drift: seconds(client_state.max_clock_drift, defaults.max_clock_drift),
+                unbonding_period: seconds(client_state.unbonding_period, defaults.unbonding_period),
+                chain_id: Vec::new(),
+                trust_threshold,
+                upgrade_path: client_state.upgrade_path.into_iter().map(|p| p.into_bytes()).collect(),
             };
 
-            debug!("Storing newly created client: {:#?}", tmclient);
-
-            TMClientStorage::insert(init_client_payload.client_id.as_bytes().to_vec(), TMClientStorageWrapper{client: tmclient.clone()});
-            ClientInfoMap::insert(init_client_payload.client_id.as_bytes().to_vec(), TMClientInfo{
-                chain_id: tmclient.chain_id.clone(),
-                trusting_period: tmclient.trusting_period,
-                max_clock_drift: tmclient.max_clock_drift,
-                unbonding_period: tmclient.unbonding_period,
-                last_block: header.header().height.value()
-            });
-            let mut available_clients = AvailableClients::get();
-            available_clients.insert(available_clients.len(), init_client_payload.client_id.as_bytes().to_vec());
-            AvailableClients::put(available_clients);
-
-            // Here we are raising the ClientCreated event
-            Self::deposit_event(RawEvent::ClientCreated(signer, tmclient.client_id, tmclient.chain_id, header.header().height.value()));
-            Ok(())
+            let header = convert::tm_header(&header).map_err(Self::convert_error)?;
+            let weight = T::WeightInfo::init_client_proto(header.validator_set.validators.len() as u32);
+            Self::create_client(signer, tmclient, header)?;
+            Ok(Some(weight).into())
         }
 
-        /// Client initialisation entry point.
+        /// Client update entry point.
         /// takes json encoded `TMUpdateClientPayload` struct.
-        #[weight = 100_000]
-        pub fn update_client(origin, payload: Vec<u8>) -> dispatch::DispatchResult {
+        /// Charged for the validators of the header, bounded by the size of the payload until it is decoded.
+        #[weight = T::WeightInfo::update_client(max_validators(payload.len()))]
+        pub fn update_client(origin, payload: Vec<u8>) -> DispatchResultWithPostInfo {
             // Check it was signed
             let signer = ensure_signed(origin)?;
 
@@ -170,46 +454,1167 @@ decl_module! {
               Error::<T>::DeserializeError
             })?;
 
-            ensure!(TMClientStorage::contains_key(update_client_payload.client_id.as_bytes().to_vec()), Error::<T>::ItemNotFound);
+            let weight = T::WeightInfo::update_client(update_client_payload.header.validator_set.validators.len() as u32);
+            Self::do_update_client(
+                signer,
+                update_client_payload.client_id,
+                update_client_payload.header,
+                Some(update_client_payload.next_validator_set),
+                None,
+            )?;
+            Ok(Some(weight).into())
+        }
 
-            let mut wrapped_client: TMClientStorageWrapper = TMClientStorage::get(update_client_payload.client_id.as_bytes().to_vec());
-            debug!("Fetched existing client from storage: {:#?}", wrapped_client);
+        /// Client update entry point for ibc-go messages.
+        /// takes protobuf encoded `Any` of `ibc.lightclients.tendermint.v1.Header`, trusting the client's latest height.
+        /// Charged for the validators of the header, bounded by its size until it is decoded.
+        #[weight = T::WeightInfo::update_client_proto(max_validators(header.len()))]
+        pub fn update_client_proto(origin, client_id: Vec<u8>, header: Vec<u8>) -> DispatchResultWithPostInfo {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
 
-            let header: LightSignedHeader = update_client_payload.header.signed_header;
-            let validator_set: LightValidatorSet<LightValidator> = update_client_payload.header.validator_set;
+            let header: proto::Header = Self::decode_any(&header, proto::TENDERMINT_HEADER_TYPE_URL)?;
+            debug!("Submitted protobuf update client header: {:?}", header);
+
+            let latest_height = Self::latest_height(&client_id).ok_or(Error::<T>::ItemNotFound)?;
+            let trusted_height = header.trusted_height.as_ref().map(|h| Height::new(h.revision_number, h.revision_height));
+            ensure!(trusted_height == Some(latest_height), Error::<T>::ValidationError);
+
+            let trusted_validators = header.trusted_validators.as_ref().map(convert::validator_set).transpose().map_err(Self::convert_error)?;
+            let header = convert::tm_header(&header).map_err(Self::convert_error)?;
+            let weight = T::WeightInfo::update_client_proto(header.validator_set.validators.len() as u32);
+
+            Self::do_update_client(signer, client_id, header, None, trusted_validators)?;
+            Ok(Some(weight).into())
+        }
+
+        /// Historical consensus state entry point.
+        /// takes json encoded `TMBackfillPayload` struct. The header is verified against the client's closest trusted state
+        /// below it and, if adjacent, must be the parent of the closest trusted header above it. The latest state is left as is.
+        /// Charged as an update for the validators of the header, bounded by the size of the payload until it is decoded.
+        #[weight = T::WeightInfo::update_client(max_validators(payload.len()))]
+        pub fn backfill_consensus_state(origin, payload: Vec<u8>) -> DispatchResultWithPostInfo {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
+
+            debug!("Submitted backfill payload: {:?}", payload);
+
+            let backfill_payload: TMBackfillPayload = serde_json::from_slice(&payload[..]).map_err(|e| {
+              error!("Deserialization Error: {}", e);
+              Error::<T>::DeserializeError
+            })?;
+            let client_id = backfill_payload.client_id;
+            ensure!(TMClientStorage::contains_key(&client_id), Error::<T>::ItemNotFound);
+            let client = TMClientStorage::get(&client_id).client;
+
+            let validator_set = backfill_payload.header.validator_set;
+            let weight = T::WeightInfo::update_client(validator_set.validators.len() as u32);
+            let header = backfill_payload.header.signed_header;
+            let height = Height::from_chain_id(header.header.chain_id.as_bytes(), header.header.height);
+
+            let heights = ConsensusHeights::get(&client_id);
+            let pos = match heights.binary_search(&height) {
+                Ok(_) => return Err(Error::<T>::ConsensusStateExists.into()),
+                Err(pos) => pos,
+            };
+            let (lower, upper) = match (pos.checked_sub(1).map(|i| heights[i]), heights.get(pos)) {
+                (Some(lower), Some(upper)) if lower.revision_number == height.revision_number
+                    && upper.revision_number == height.revision_number => (lower, *upper),
+                _ => return Err(Error::<T>::BackfillOutOfRange.into()),
+            };
+            let lower_state = ConsensusStates::get(&client_id, lower).ok_or(Error::<T>::ItemNotFound)?.state.state;
+            let upper_state = ConsensusStates::get(&client_id, upper).ok_or(Error::<T>::ItemNotFound)?.state.state;
+            ensure!(header.header.time < upper_state.header().time, Error::<T>::NonIncreasingTime);
 
             let trusted_state = verify_single(
-                wrapped_client.client.state.unwrap().state.clone(),
+                &lower_state,
                 &header,
                 &validator_set,
-                &update_client_payload.next_validator_set,
-                wrapped_client.client.trust_threshold,
-                Duration::from_secs(wrapped_client.client.trusting_period+wrapped_client.client.max_clock_drift),
-                std::time::SystemTime::now(),
-            ).map_err(|e| {
-                error!("Unable to validate header: {}", e);
-                Error::<T>::ValidationError
+                None,
+                backfill_payload.trusted_validators.as_ref(),
+                Options {
+                    trust_threshold: client.trust_threshold,
+                    trusting_period: client.trusting_period,
+                    max_clock_drift: client.max_clock_drift,
+                    now: Self::now(),
+                },
+            ).map_err(Self::verification_error)?;
+
+            if upper.revision_height == height.revision_height + 1 {
+                let encoding = light_client::encoding_of(&header.header).map_err(Self::verification_error)?;
+                ensure!(
+                    upper_state.header().last_block_id.hash == light_client::encoding::header_hash(&header.header, encoding),
+                    Error::<T>::HashLinkMismatch
+                );
+            }
+
+            Self::store_consensus_state(&client_id, height, ConsensusState{
+                state: trusted_state,
+                last_update: Self::now(),
+            });
+
+            Self::deposit_event(RawEvent::ConsensusStateBackfilled(signer, client_id, height));
+            Ok(Some(weight).into())
+        }
+
+        /// Canonical client selection entry point.
+        /// marks `client_id` as the trusted client for `chain_id`. Must be called by root.
+        #[weight = 10_000]
+        pub fn set_canonical_client(origin, chain_id: Vec<u8>, client_id: Vec<u8>) -> dispatch::DispatchResult {
+            ensure_root(origin)?;
+
+            ensure!(ClientInfoMap::contains_key(&client_id), Error::<T>::ItemNotFound);
+            ensure!(ClientInfoMap::get(&client_id).chain_id == chain_id, Error::<T>::ChainIdMismatch);
+
+            CanonicalClient::insert(&chain_id, &client_id);
+
+            Self::deposit_event(RawEvent::CanonicalClientSet(chain_id, client_id));
+            Ok(())
+        }
+
+        /// Canonical client removal entry point.
+        /// forgets the trusted client of `chain_id`. Must be called by root.
+        #[weight = 10_000]
+        pub fn clear_canonical_client(origin, chain_id: Vec<u8>) -> dispatch::DispatchResult {
+            ensure_root(origin)?;
+
+            ensure!(CanonicalClient::contains_key(&chain_id), Error::<T>::ItemNotFound);
+
+            CanonicalClient::remove(&chain_id);
+
+            Self::deposit_event(RawEvent::CanonicalClientCleared(chain_id));
+            Ok(())
+        }
+
+        /// Client upgrade entry point.
+        /// takes json encoded `TMUpgradeClientPayload` struct. The upgraded client and consensus states must have been
+        /// committed by the old chain under the client's upgrade path, at the client's latest height.
+        /// Charged for the validators of the header, bounded by the size of the payload until it is decoded.
+        #[weight = T::WeightInfo::upgrade_client(max_validators(payload.len()))]
+        pub fn upgrade_client(origin, payload: Vec<u8>) -> DispatchResultWithPostInfo {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
+
+            debug!("Submitted upgrade client payload: {:?}", payload);
+
+            let upgrade_client_payload: TMUpgradeClientPayload = serde_json::from_slice(&payload[..]).map_err(|e| {
+              error!("Deserialization Error: {}", e);
+              Error::<T>::DeserializeError
+            })?;
+            let client_id = upgrade_client_payload.client_id.clone();
+            let weight = T::WeightInfo::upgrade_client(upgrade_client_payload.header.validator_set.validators.len() as u32);
+
+            ensure!(TMClientStorage::contains_key(&client_id), Error::<T>::ItemNotFound);
+
+            let mut wrapped_client: TMClientStorageWrapper = TMClientStorage::get(&client_id);
+            let (upgrade_store, upgrade_key) = match wrapped_client.client.upgrade_path.split_last() {
+                Some((key, store)) if !store.is_empty() => (store.to_vec(), key.clone()),
+                _ => return Err(Error::<T>::UpgradePathNotSet.into()),
+            };
+
+            let trusted_header = wrapped_client.client.state.as_ref().ok_or(Error::<T>::NoneValue)?.state.header().clone();
+            ensure!(
+                light_client::seconds_after(&trusted_header.time, wrapped_client.client.trusting_period) > Self::now(),
+                Error::<T>::ClientExpired
+            );
+
+            let upgraded_client: proto::ClientState = Self::decode_any(&upgrade_client_payload.upgraded_client_state, proto::TENDERMINT_CLIENT_STATE_TYPE_URL)?;
+            let upgraded_consensus: proto::ConsensusState = Self::decode_any(&upgrade_client_payload.upgraded_consensus_state, proto::TENDERMINT_CONSENSUS_STATE_TYPE_URL)?;
+            let proof_upgrade_client = proto::MerkleProof::decode(&upgrade_client_payload.proof_upgrade_client[..])
+                .map_err(|_| Error::<T>::DeserializeError)?;
+            let proof_upgrade_consensus_state = proto::MerkleProof::decode(&upgrade_client_payload.proof_upgrade_consensus_state[..])
+                .map_err(|_| Error::<T>::DeserializeError)?;
+
+            let last_height = ClientInfoMap::get(&client_id).last_block;
+            let upgraded_height = upgraded_client.latest_height.as_ref()
+                .map(|h| Height::new(h.revision_number, h.revision_height))
+                .ok_or(Error::<T>::InvalidUpgrade)?;
+            ensure!(upgraded_height > last_height, Error::<T>::InvalidUpgrade);
+
+            // Upgraded states are stored under `{upgrade key}/{last height}/upgraded{Client,ConsState}`
+            let upgrade_prefix = [&upgrade_key[..], b"/", last_height.revision_height.to_string().as_bytes(), b"/"].concat();
+            let root = &trusted_header.app_hash[..];
+
+            let client_path: Vec<Vec<u8>> = upgrade_store.iter().cloned()
+                .chain(Some([&upgrade_prefix[..], b"upgradedClient"].concat()))
+                .collect();
+            commitment::verify_membership(&commitment::SDK_SPECS, root, &proof_upgrade_client, &client_path, &upgrade_client_payload.upgraded_client_state).map_err(|e| {
+                error!("Invalid upgraded client proof: {:?}", e);
+                Error::<T>::InvalidUpgradeProof
             })?;
 
+            let consensus_path: Vec<Vec<u8>> = upgrade_store.iter().cloned()
+                .chain(Some([&upgrade_prefix[..], b"upgradedConsState"].concat()))
+                .collect();
+            commitment::verify_membership(&commitment::SDK_SPECS, root, &proof_upgrade_consensus_state, &consensus_path, &upgrade_client_payload.upgraded_consensus_state).map_err(|e| {
+                error!("Invalid upgraded consensus state proof: {:?}", e);
+                Error::<T>::InvalidUpgradeProof
+            })?;
+
+            // The first header of the upgraded chain must be signed by the validators the old chain committed to
+            let header = upgrade_client_payload.header.signed_header;
+            let validator_set = upgrade_client_payload.header.validator_set;
+            ensure!(header.header.chain_id == upgraded_client.chain_id, Error::<T>::ChainIdMismatch);
+            ensure!(header.header.validators_hash == upgraded_consensus.next_validators_hash, Error::<T>::ValidatorSetHashMismatch);
+
+            validate_initial_signed_header_and_valset(&header, &validator_set).map_err(Self::verification_error)?;
+
+            let old_chain_id = wrapped_client.client.chain_id.clone();
+            let height = Height::new(upgraded_height.revision_number, header.header.height);
+            ensure!(height >= upgraded_height, Error::<T>::InvalidUpgrade);
             let state: ConsensusState = ConsensusState{
-                state: trusted_state,
-                last_update: Utc::now(),
+                state: TrustedState::initial(header, validator_set),
+                last_update: Self::now(),
             };
 
             wrapped_client.client.state = Some(state.clone());
-            TMClientStorage::insert(update_client_payload.client_id.as_bytes().to_vec(), wrapped_client.clone());
-            debug!("Stored updated client in storage: {:#?}", wrapped_client);
+            wrapped_client.client.chain_id = upgraded_client.chain_id.into_bytes();
+            if let Some(unbonding_period) = upgraded_client.unbonding_period {
+                wrapped_client.client.unbonding_period = unbonding_period.seconds as u64;
+            }
+            wrapped_client.client.upgrade_path = upgraded_client.upgrade_path.into_iter().map(|p| p.into_bytes()).collect();
+
+            TMClientStorage::insert(&client_id, wrapped_client.clone());
+            debug!("Stored upgraded client in storage: {:#?}", wrapped_client);
+            Self::store_consensus_state(&client_id, height, state);
 
-            ClientInfoMap::insert(update_client_payload.client_id.as_bytes().to_vec(), TMClientInfo{
+            Self::store_client_info(&wrapped_client.client, TMClientInfo{
                 chain_id: wrapped_client.client.chain_id.clone(),
                 trusting_period: wrapped_client.client.trusting_period,
                 max_clock_drift: wrapped_client.client.max_clock_drift,
                 unbonding_period: wrapped_client.client.unbonding_period,
-                last_block: header.header().height.value()
+                last_block: height,
             });
 
-            Self::deposit_event(RawEvent::ClientUpdated(signer, wrapped_client.client.client_id, wrapped_client.client.chain_id, header.header().height.value()));
+            // A canonical client keeps being canonical for the chain after its upgrade
+            if CanonicalClient::get(&old_chain_id) == Some(client_id.clone()) {
+                CanonicalClient::remove(&old_chain_id);
+                CanonicalClient::insert(&wrapped_client.client.chain_id, &client_id);
+            }
+
+            Self::deposit_event(RawEvent::ClientUpgraded(signer, client_id, wrapped_client.client.chain_id, height));
+            Ok(Some(weight).into())
+        }
+
+        /// Connection handshake entry point on the initiating chain.
+        /// takes json encoded `ConnOpenInitPayload` struct.
+        #[weight = 100_000]
+        pub fn conn_open_init(origin, payload: Vec<u8>) -> dispatch::DispatchResult {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
+
+            debug!("Submitted connection open init payload: {:?}", payload);
+
+            let conn_open_init_payload: ConnOpenInitPayload = serde_json::from_slice(&payload[..]).map_err(|e| {
+              error!("Deserialization Error: {}", e);
+              Error::<T>::DeserializeError
+            })?;
+            ensure!(TMClientStorage::contains_key(&conn_open_init_payload.client_id), Error::<T>::ItemNotFound);
+
+            let end = connection::open_init(conn_open_init_payload).map_err(Self::connection_error)?;
+            let client_id = end.client_id.clone();
+            let connection_id = Self::store_new_connection(end);
+
+            Self::deposit_event(RawEvent::ConnectionOpenInit(signer, connection_id, client_id));
+            Ok(())
+        }
+
+        /// Connection handshake entry point on the chain answering `ConnOpenInit`.
+        /// takes json encoded `ConnOpenTryPayload` struct. The counterparty's end is proven to be in `Init` against the
+        /// client's consensus state at the proof height.
+        #[weight = 200_000]
+        pub fn conn_open_try(origin, payload: Vec<u8>) -> dispatch::DispatchResult {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
+
+            debug!("Submitted connection open try payload: {:?}", payload);
+
+            let conn_open_try_payload: ConnOpenTryPayload = serde_json::from_slice(&payload[..]).map_err(|e| {
+              error!("Deserialization Error: {}", e);
+              Error::<T>::DeserializeError
+            })?;
+
+            #[cfg(feature = "self-client-validation")]
+            Self::check_self_client(
+                &conn_open_try_payload.client_state,
+                &conn_open_try_payload.consensus_state,
+                conn_open_try_payload.consensus_height,
+            )?;
+            let root = Self::counterparty_root(&conn_open_try_payload.client_id, conn_open_try_payload.proof_height)?;
+            let end = connection::open_try(&conn_open_try_payload, &root).map_err(Self::connection_error)?;
+            let client_id = end.client_id.clone();
+            let connection_id = Self::store_new_connection(end);
+
+            Self::deposit_event(RawEvent::ConnectionOpenTry(signer, connection_id, client_id));
+            Ok(())
+        }
+
+        /// Connection handshake entry point on the initiating chain, once the counterparty answered.
+        /// takes json encoded `ConnOpenAckPayload` struct.
+        #[weight = 200_000]
+        pub fn conn_open_ack(origin, payload: Vec<u8>) -> dispatch::DispatchResult {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
+
+            debug!("Submitted connection open ack payload: {:?}", payload);
+
+            let conn_open_ack_payload: ConnOpenAckPayload = serde_json::from_slice(&payload[..]).map_err(|e| {
+              error!("Deserialization Error: {}", e);
+              Error::<T>::DeserializeError
+            })?;
+            let connection_id = conn_open_ack_payload.connection_id.clone();
+
+            let end = Connections::get(&connection_id).ok_or(Error::<T>::ConnectionNotFound)?;
+            #[cfg(feature = "self-client-validation")]
+            Self::check_self_client(
+                &conn_open_ack_payload.client_state,
+                &conn_open_ack_payload.consensus_state,
+                conn_open_ack_payload.consensus_height,
+            )?;
+            let root = Self::counterparty_root(&end.client_id, conn_open_ack_payload.proof_height)?;
+            let end = connection::open_ack(&end, &connection_id, &conn_open_ack_payload, &root).map_err(Self::connection_error)?;
+            let counterparty_connection_id = end.counterparty.connection_id.clone();
+            Self::store_connection(&connection_id, end);
+
+            Self::deposit_event(RawEvent::ConnectionOpenAck(signer, connection_id, counterparty_connection_id));
+            Ok(())
+        }
+
+        /// Connection handshake entry point on the answering chain, once the counterparty acknowledged.
+        /// takes json encoded `ConnOpenConfirmPayload` struct.
+        #[weight = 100_000]
+        pub fn conn_open_confirm(origin, payload: Vec<u8>) -> dispatch::DispatchResult {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
+
+            debug!("Submitted connection open confirm payload: {:?}", payload);
+
+            let conn_open_confirm_payload: ConnOpenConfirmPayload = serde_json::from_slice(&payload[..]).map_err(|e| {
+              error!("Deserialization Error: {}", e);
+              Error::<T>::DeserializeError
+            })?;
+            let connection_id = conn_open_confirm_payload.connection_id.clone();
+
+            let end = Connections::get(&connection_id).ok_or(Error::<T>::ConnectionNotFound)?;
+            let root = Self::counterparty_root(&end.client_id, conn_open_confirm_payload.proof_height)?;
+            let end = connection::open_confirm(&end, &connection_id, &conn_open_confirm_payload, &root).map_err(Self::connection_error)?;
+            let counterparty_connection_id = end.counterparty.connection_id.clone();
+            Self::store_connection(&connection_id, end);
+
+            Self::deposit_event(RawEvent::ConnectionOpenConfirm(signer, connection_id, counterparty_connection_id));
+            Ok(())
+        }
+
+        /// Channel handshake entry point on the initiating chain.
+        /// takes json encoded `ChanOpenInitPayload` struct. The module bound to the port may reject the channel.
+        #[weight = 100_000]
+        #[transactional]
+        pub fn chan_open_init(origin, payload: Vec<u8>) -> dispatch::DispatchResult {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
+
+            debug!("Submitted channel open init payload: {:?}", payload);
+
+            let chan_open_init_payload: ChanOpenInitPayload = serde_json::from_slice(&payload[..]).map_err(|e| {
+              error!("Deserialization Error: {}", e);
+              Error::<T>::DeserializeError
+            })?;
+            let port_id = chan_open_init_payload.port_id.clone();
+            ensure!(T::Modules::is_bound(&port_id), Error::<T>::PortNotBound);
+
+            let connection = Connections::get(&chan_open_init_payload.connection_id).ok_or(Error::<T>::ConnectionNotFound)?;
+            let end = channel::open_init(&connection, &chan_open_init_payload).map_err(Self::channel_error)?;
+            let channel_id = channel::channel_id(NextChannelSequence::get());
+            T::Modules::on_chan_open_init(&port_id, &channel_id, &end)?;
+            Self::store_new_channel(&port_id, &channel_id, end);
+
+            Self::deposit_event(RawEvent::ChannelOpenInit(signer, port_id, channel_id));
+            Ok(())
+        }
+
+        /// Channel handshake entry point on the chain answering `ChanOpenInit`.
+        /// takes json encoded `ChanOpenTryPayload` struct. The counterparty's end is proven to be in `Init` against the
+        /// consensus state of the connection's client at the proof height. The module bound to the port picks the version.
+        #[weight = 200_000]
+        #[transactional]
+        pub fn chan_open_try(origin, payload: Vec<u8>) -> dispatch::DispatchResult {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
+
+            debug!("Submitted channel open try payload: {:?}", payload);
+
+            let chan_open_try_payload: ChanOpenTryPayload = serde_json::from_slice(&payload[..]).map_err(|e| {
+              error!("Deserialization Error: {}", e);
+              Error::<T>::DeserializeError
+            })?;
+            let port_id = chan_open_try_payload.port_id.clone();
+            ensure!(T::Modules::is_bound(&port_id), Error::<T>::PortNotBound);
+
+            let connection = Connections::get(&chan_open_try_payload.connection_id).ok_or(Error::<T>::ConnectionNotFound)?;
+            let root = Self::counterparty_root(&connection.client_id, chan_open_try_payload.proof_height)?;
+            let mut end = channel::open_try(&connection, &chan_open_try_payload, &root).map_err(Self::channel_error)?;
+            let channel_id = channel::channel_id(NextChannelSequence::get());
+            end.version = T::Modules::on_chan_open_try(&port_id, &channel_id, &end, &chan_open_try_payload.counterparty_version)?;
+            Self::store_new_channel(&port_id, &channel_id, end);
+
+            Self::deposit_event(RawEvent::ChannelOpenTry(signer, port_id, channel_id));
+            Ok(())
+        }
+
+        /// Channel handshake entry point on the initiating chain, once the counterparty answered.
+        /// takes json encoded `ChanOpenAckPayload` struct.
+        #[weight = 200_000]
+        #[transactional]
+        pub fn chan_open_ack(origin, payload: Vec<u8>) -> dispatch::DispatchResult {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
+
+            debug!("Submitted channel open ack payload: {:?}", payload);
+
+            let chan_open_ack_payload: ChanOpenAckPayload = serde_json::from_slice(&payload[..]).map_err(|e| {
+              error!("Deserialization Error: {}", e);
+              Error::<T>::DeserializeError
+            })?;
+            let (port_id, channel_id) = (chan_open_ack_payload.port_id.clone(), chan_open_ack_payload.channel_id.clone());
+
+            let (end, connection) = Self::channel_connection(&port_id, &channel_id)?;
+            let root = Self::counterparty_root(&connection.client_id, chan_open_ack_payload.proof_height)?;
+            let end = channel::open_ack(&end, &connection, &chan_open_ack_payload, &root).map_err(Self::channel_error)?;
+            T::Modules::on_chan_open_ack(&port_id, &channel_id, &chan_open_ack_payload.counterparty_version)?;
+            Self::store_channel(&port_id, &channel_id, end);
+
+            Self::deposit_event(RawEvent::ChannelOpenAck(signer, port_id, channel_id));
+            Ok(())
+        }
+
+        /// Channel handshake entry point on the answering chain, once the counterparty acknowledged.
+        /// takes json encoded `ChanOpenConfirmPayload` struct.
+        #[weight = 100_000]
+        #[transactional]
+        pub fn chan_open_confirm(origin, payload: Vec<u8>) -> dispatch::DispatchResult {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
+
+            debug!("Submitted channel open confirm payload: {:?}", payload);
+
+            let chan_open_confirm_payload: ChanOpenConfirmPayload = serde_json::from_slice(&payload[..]).map_err(|e| {
+              error!("Deserialization Error: {}", e);
+              Error::<T>::DeserializeError
+            })?;
+            let (port_id, channel_id) = (chan_open_confirm_payload.port_id.clone(), chan_open_confirm_payload.channel_id.clone());
+
+            let (end, connection) = Self::channel_connection(&port_id, &channel_id)?;
+            let root = Self::counterparty_root(&connection.client_id, chan_open_confirm_payload.proof_height)?;
+            let end = channel::open_confirm(&end, &connection, &chan_open_confirm_payload, &root).map_err(Self::channel_error)?;
+            T::Modules::on_chan_open_confirm(&port_id, &channel_id)?;
+            Self::store_channel(&port_id, &channel_id, end);
+
+            Self::deposit_event(RawEvent::ChannelOpenConfirm(signer, port_id, channel_id));
+            Ok(())
+        }
+
+        /// Closes a channel on this end. The module bound to the port may refuse to close it.
+        #[weight = 100_000]
+        #[transactional]
+        pub fn chan_close_init(origin, port_id: Vec<u8>, channel_id: Vec<u8>) -> dispatch::DispatchResult {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
+
+            let (end, connection) = Self::channel_connection(&port_id, &channel_id)?;
+            let end = channel::close_init(&end, &connection).map_err(Self::channel_error)?;
+            T::Modules::on_chan_close_init(&port_id, &channel_id)?;
+            Self::store_channel(&port_id, &channel_id, end);
+
+            Self::deposit_event(RawEvent::ChannelCloseInit(signer, port_id, channel_id));
             Ok(())
         }
+
+        /// Closes a channel once the counterparty closed its end.
+        /// takes json encoded `ChanCloseConfirmPayload` struct.
+        #[weight = 100_000]
+        #[transactional]
+        pub fn chan_close_confirm(origin, payload: Vec<u8>) -> dispatch::DispatchResult {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
+
+            debug!("Submitted channel close confirm payload: {:?}", payload);
+
+            let chan_close_confirm_payload: ChanCloseConfirmPayload = serde_json::from_slice(&payload[..]).map_err(|e| {
+              error!("Deserialization Error: {}", e);
+              Error::<T>::DeserializeError
+            })?;
+            let (port_id, channel_id) = (chan_close_confirm_payload.port_id.clone(), chan_close_confirm_payload.channel_id.clone());
+
+            let (end, connection) = Self::channel_connection(&port_id, &channel_id)?;
+            let root = Self::counterparty_root(&connection.client_id, chan_close_confirm_payload.proof_height)?;
+            let end = channel::close_confirm(&end, &connection, &chan_close_confirm_payload, &root).map_err(Self::channel_error)?;
+            T::Modules::on_chan_close_confirm(&port_id, &channel_id)?;
+            Self::store_channel(&port_id, &channel_id, end);
+
+            Self::deposit_event(RawEvent::ChannelCloseConfirm(signer, port_id, channel_id));
+            Ok(())
+        }
+
+        /// Packet relay entry point for packets sent by the counterparty.
+        /// takes json encoded `RecvPacketPayload` struct. The packet is handed to the module bound to its destination port,
+        /// whose acknowledgement is committed to.
+        #[weight = 200_000]
+        #[transactional]
+        pub fn recv_packet(origin, payload: Vec<u8>) -> dispatch::DispatchResult {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
+
+            debug!("Submitted receive packet payload: {:?}", payload);
+
+            let recv_packet_payload: RecvPacketPayload = serde_json::from_slice(&payload[..]).map_err(|e| {
+              error!("Deserialization Error: {}", e);
+              Error::<T>::DeserializeError
+            })?;
+            let packet = recv_packet_payload.packet.clone();
+            let (port_id, channel_id) = (packet.destination_port.clone(), packet.destination_channel.clone());
+            let channel_key = (port_id.clone(), channel_id.clone());
+
+            let (end, connection) = Self::channel_connection(&port_id, &channel_id)?;
+            let root = Self::packet_proof_header(&connection, recv_packet_payload.proof_height)?.app_hash;
+            let next_sequence_recv = NextSequenceRecv::get(&port_id, &channel_id);
+            channel::recv_packet(
+                &end,
+                &connection,
+                &recv_packet_payload,
+                next_sequence_recv,
+                PacketReceipts::get(&channel_key, packet.sequence),
+                Self::own_height(),
+                Self::now().timestamp_nanos() as u64,
+                &root,
+            ).map_err(Self::channel_error)?;
+
+            match end.ordering {
+                Order::Ordered => Self::store_next_sequence_recv(&port_id, &channel_id, next_sequence_recv + 1),
+                Order::Unordered => {
+                    host::set(&channel::packet_receipt_path(&port_id, &channel_id, packet.sequence), channel::RECEIPT);
+                    PacketReceipts::insert(&channel_key, packet.sequence, true);
+                }
+            }
+            let acknowledgement = T::Modules::on_recv_packet(&packet).ok_or(Error::<T>::PortNotBound)?;
+            let acknowledgement_commitment = channel::acknowledgement_commitment(&acknowledgement);
+            host::set(&channel::packet_acknowledgement_path(&port_id, &channel_id, packet.sequence), &acknowledgement_commitment);
+            PacketAcknowledgements::insert(&channel_key, packet.sequence, acknowledgement_commitment);
+
+            Self::deposit_event(RawEvent::RecvPacket(signer, packet, acknowledgement));
+            Ok(())
+        }
+
+        /// Packet relay entry point for acknowledgements of packets sent by this chain.
+        /// takes json encoded `AcknowledgePacketPayload` struct. The acknowledgement is handed to the module bound to the
+        /// packet's source port.
+        #[weight = 200_000]
+        #[transactional]
+        pub fn acknowledge_packet(origin, payload: Vec<u8>) -> dispatch::DispatchResult {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
+
+            debug!("Submitted acknowledge packet payload: {:?}", payload);
+
+            let acknowledge_packet_payload: AcknowledgePacketPayload = serde_json::from_slice(&payload[..]).map_err(|e| {
+              error!("Deserialization Error: {}", e);
+              Error::<T>::DeserializeError
+            })?;
+            let packet = acknowledge_packet_payload.packet.clone();
+            let (port_id, channel_id) = (packet.source_port.clone(), packet.source_channel.clone());
+            let channel_key = (port_id.clone(), channel_id.clone());
+
+            let (end, connection) = Self::channel_connection(&port_id, &channel_id)?;
+            let root = Self::packet_proof_header(&connection, acknowledge_packet_payload.proof_height)?.app_hash;
+            let next_sequence_ack = NextSequenceAck::get(&port_id, &channel_id);
+            channel::acknowledge_packet(
+                &end,
+                &connection,
+                &acknowledge_packet_payload,
+                PacketCommitments::get(&channel_key, packet.sequence).as_deref(),
+                next_sequence_ack,
+                &root,
+            ).map_err(Self::channel_error)?;
+
+            host::remove(&channel::packet_commitment_path(&port_id, &channel_id, packet.sequence));
+            PacketCommitments::remove(&channel_key, packet.sequence);
+            if end.ordering == Order::Ordered {
+                NextSequenceAck::insert(&port_id, &channel_id, next_sequence_ack + 1);
+            }
+            T::Modules::on_acknowledgement_packet(&packet, &acknowledge_packet_payload.acknowledgement)?;
+
+            Self::deposit_event(RawEvent::AcknowledgePacket(signer, packet));
+            Ok(())
+        }
+
+        /// Packet relay entry point for packets sent by this chain that the counterparty did not receive in time.
+        /// takes json encoded `TimeoutPacketPayload` struct. The timeout is handed to the module bound to the packet's
+        /// source port, and closes ordered channels.
+        #[weight = 200_000]
+        #[transactional]
+        pub fn timeout_packet(origin, payload: Vec<u8>) -> dispatch::DispatchResult {
+            // Check it was signed
+            let signer = ensure_signed(origin)?;
+
+            debug!("Submitted timeout packet payload: {:?}", payload);
+
+            let timeout_packet_payload: TimeoutPacketPayload = serde_json::from_slice(&payload[..]).map_err(|e| {
+              error!("Deserialization Error: {}", e);
+              Error::<T>::DeserializeError
+            })?;
+            let packet = timeout_packet_payload.packet.clone();
+            let (port_id, channel_id) = (packet.source_port.clone(), packet.source_channel.clone());
+            let channel_key = (port_id.clone(), channel_id.clone());
+
+            let (end, connection) = Self::channel_connection(&port_id, &channel_id)?;
+            let header = Self::packet_proof_header(&connection, timeout_packet_payload.proof_height)?;
+            let end = channel::timeout_packet(
+                &end,
+                &connection,
+                &timeout_packet_payload,
+                PacketCommitments::get(&channel_key, packet.sequence).as_deref(),
+                header.time.timestamp_nanos() as u64,
+                &header.app_hash,
+            ).map_err(Self::channel_error)?;
+
+            host::remove(&channel::packet_commitment_path(&port_id, &channel_id, packet.sequence));
+            PacketCommitments::remove(&channel_key, packet.sequence);
+            if end.state == ChannelState::Closed {
+                Self::store_channel(&port_id, &channel_id, end);
+            }
+            T::Modules::on_timeout_packet(&packet)?;
+
+            Self::deposit_event(RawEvent::TimeoutPacket(signer, packet));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Validates the initial header of `tmclient` and stores the client.
+    fn create_client(signer: T::AccountId, tmclient: TendermintClient, header: TMHeader) -> dispatch::DispatchResult {
+        let client_id = tmclient.client_id.clone();
+        let (chain_id, height) = Self::insert_client(tmclient, header)?;
+
+        // Here we are raising the ClientCreated event
+        Self::deposit_event(RawEvent::ClientCreated(signer, client_id, chain_id, height));
+        Ok(())
+    }
+
+    /// Validates the initial header of `tmclient` and stores the client, returning its chain id and height.
+    fn insert_client(mut tmclient: TendermintClient, header: TMHeader) -> Result<(Vec<u8>, Height), dispatch::DispatchError> {
+        // Validating if client already exists
+        ensure!(!TMClientStorage::contains_key(&tmclient.client_id), Error::<T>::ClientAlreadyInitialized);
+
+        let height = validation::initialise_client(&mut tmclient, header, Self::now()).map_err(Self::verification_error)?;
+        let state = tmclient.state.clone().ok_or(Error::<T>::NoneValue)?;
+
+        debug!("Storing newly created client: {:#?}", tmclient);
+
+        let info = TMClientInfo{
+            chain_id: tmclient.chain_id.clone(),
+            trusting_period: tmclient.trusting_period,
+            max_clock_drift: tmclient.max_clock_drift,
+            unbonding_period: tmclient.unbonding_period,
+            last_block: height,
+        };
+        let chain_id = tmclient.chain_id.clone();
+        Self::store_new_client(tmclient, info, state);
+
+        Ok((chain_id, height))
+    }
+
+    /// Stores a client exported from another chain as it is. Its consensus state is trusted without being verified.
+    fn import_client(exported: ExportedClient) -> Result<(), &'static str> {
+        let ExportedClient { client, info } = exported;
+        ensure!(!TMClientStorage::contains_key(&client.client_id), "client id is already in use");
+        let state = client.state.clone().ok_or("client has no consensus state")?;
+        ensure!(info.chain_id == client.chain_id, "client info is of another chain than the client");
+        ensure!(
+            info.last_block == Height::from_chain_id(&info.chain_id, state.state.header().height),
+            "client info is at another height than the client's consensus state"
+        );
+
+        debug!("Storing imported client: {:#?}", client);
+        Self::store_new_client(client, info, state);
+        Ok(())
+    }
+
+    /// Stores `tmclient`, its info and its initial consensus state, and lists it as available.
+    fn store_new_client(tmclient: TendermintClient, info: TMClientInfo, state: ConsensusState) {
+        let height = info.last_block;
+        TMClientStorage::insert(&tmclient.client_id, TMClientStorageWrapper{client: tmclient.clone()});
+        Self::store_client_info(&tmclient, info);
+        Self::store_consensus_state(&tmclient.client_id, height, state);
+        let mut available_clients = AvailableClients::get();
+        available_clients.insert(available_clients.len(), tmclient.client_id);
+        AvailableClients::put(available_clients);
+    }
+
+    /// Verifies `header` against the latest trusted state of `client_id` and moves the client to it.
+    fn do_update_client(
+        signer: T::AccountId,
+        client_id: Vec<u8>,
+        header: TMHeader,
+        next_validator_set: Option<ValidatorSet>,
+        trusted_validators: Option<ValidatorSet>,
+    ) -> dispatch::DispatchResult {
+        ensure!(TMClientStorage::contains_key(&client_id), Error::<T>::ItemNotFound);
+
+        let mut wrapped_client: TMClientStorageWrapper = TMClientStorage::get(&client_id);
+        debug!("Fetched existing client from storage: {:#?}", wrapped_client);
+
+        let trusted_state = &wrapped_client.client.state.as_ref().ok_or(Error::<T>::NoneValue)?.state;
+
+        let (height, trusted_state) = validation::verify_update(
+            &wrapped_client.client,
+            trusted_state,
+            ClientInfoMap::get(&client_id).last_block,
+            &header,
+            next_validator_set.as_ref(),
+            trusted_validators.as_ref(),
+            Self::now(),
+        ).map_err(Self::verification_error)?;
+
+        let state: ConsensusState = ConsensusState{
+            state: trusted_state,
+            last_update: Self::now(),
+        };
+
+        wrapped_client.client.state = Some(state.clone());
+        TMClientStorage::insert(&client_id, wrapped_client.clone());
+        debug!("Stored updated client in storage: {:#?}", wrapped_client);
+        Self::store_consensus_state(&client_id, height, state);
+
+        Self::store_client_info(&wrapped_client.client, TMClientInfo{
+            chain_id: wrapped_client.client.chain_id.clone(),
+            trusting_period: wrapped_client.client.trusting_period,
+            max_clock_drift: wrapped_client.client.max_clock_drift,
+            unbonding_period: wrapped_client.client.unbonding_period,
+            last_block: height,
+        });
+
+        Self::deposit_event(RawEvent::ClientUpdated(signer, client_id, wrapped_client.client.chain_id, height));
+        Ok(())
+    }
+
+    /// Current time, as set by the block author through the timestamp inherent.
+    fn now() -> DateTime<Utc> {
+        let millis: u64 = <pallet_timestamp::Module<T>>::get().unique_saturated_into();
+        Utc.timestamp_millis(millis.min(i64::MAX as u64) as i64)
+    }
+
+    /// Maps light client verification failures to the pallet's errors.
+    fn verification_error(e: VerificationError) -> Error<T> {
+        error!("Validation Error: {:?}", e);
+        match e {
+            VerificationError::UnsupportedBlockVersion(_) => Error::<T>::UnsupportedBlockVersion,
+            VerificationError::UnsupportedPublicKey => Error::<T>::UnsupportedPublicKey,
+            VerificationError::ChainIdMismatch => Error::<T>::ChainIdMismatch,
+            VerificationError::NonIncreasingHeight => Error::<T>::NonIncreasingHeight,
+            VerificationError::NonIncreasingTime => Error::<T>::NonIncreasingTime,
+            VerificationError::HeaderFromFuture => Error::<T>::HeaderFromFuture,
+            VerificationError::TrustingPeriodExpired => Error::<T>::ClientExpired,
+            VerificationError::InvalidValidatorSet | VerificationError::InvalidNextValidatorSet => {
+                Error::<T>::ValidatorSetHashMismatch
+            }
+            VerificationError::CommitHeaderMismatch => Error::<T>::CommitHeaderMismatch,
+            VerificationError::InvalidSignature => Error::<T>::InvalidSignature,
+            VerificationError::InsufficientVotingPower => Error::<T>::InsufficientVotingPower,
+            VerificationError::InvalidValidator
+            | VerificationError::InvalidCommit
+            | VerificationError::MissingTrustedValidators => Error::<T>::ValidationError,
+        }
+    }
+
+    /// Maps protobuf conversion failures to the pallet's errors.
+    fn convert_error(e: ConvertError) -> Error<T> {
+        error!("Unable to convert protobuf message: {:?}", e);
+        match e {
+            ConvertError::UnsupportedPublicKey => Error::<T>::UnsupportedPublicKey,
+            _ => Error::<T>::DeserializeError,
+        }
+    }
+
+    /// Maps connection handshake failures to the pallet's errors.
+    fn connection_error(e: ConnectionError) -> Error<T> {
+        error!("Connection handshake Error: {:?}", e);
+        match e {
+            ConnectionError::InvalidIdentifier => Error::<T>::InvalidIdentifier,
+            ConnectionError::InvalidState => Error::<T>::InvalidConnectionState,
+            ConnectionError::VersionNotSupported => Error::<T>::ConnectionVersionNotSupported,
+            ConnectionError::MalformedProof => Error::<T>::DeserializeError,
+            ConnectionError::InvalidConnectionProof(_)
+            | ConnectionError::InvalidClientStateProof(_)
+            | ConnectionError::InvalidConsensusStateProof(_) => Error::<T>::InvalidCounterpartyProof,
+            ConnectionError::DelayPeriodNotPassed => Error::<T>::DelayPeriodNotPassed,
+        }
+    }
+
+    /// Maps channel and packet failures to the pallet's errors.
+    fn channel_error(e: ChannelError) -> Error<T> {
+        error!("Channel Error: {:?}", e);
+        match e {
+            ChannelError::InvalidIdentifier => Error::<T>::InvalidIdentifier,
+            ChannelError::InvalidState => Error::<T>::InvalidChannelState,
+            ChannelError::ConnectionNotOpen => Error::<T>::ConnectionNotOpen,
+            ChannelError::OrderingNotSupported => Error::<T>::ChannelOrderingNotSupported,
+            ChannelError::InvalidPacket => Error::<T>::InvalidPacket,
+            ChannelError::PacketTimedOut => Error::<T>::PacketTimedOut,
+            ChannelError::PacketNotTimedOut => Error::<T>::PacketNotTimedOut,
+            ChannelError::InvalidSequence => Error::<T>::InvalidPacketSequence,
+            ChannelError::PacketAlreadyReceived => Error::<T>::PacketAlreadyReceived,
+            ChannelError::PacketNotCommitted => Error::<T>::PacketNotCommitted,
+            ChannelError::MalformedProof => Error::<T>::DeserializeError,
+            ChannelError::InvalidChannelProof(_) | ChannelError::InvalidPacketProof(_) => {
+                Error::<T>::InvalidCounterpartyProof
+            }
+        }
+    }
+
+    /// Returns the latest height of `client_id` and the header the client trusts at that height. The client must not
+    /// have expired.
+    fn active_client(client_id: &[u8]) -> Result<(Height, Header), Error<T>> {
+        ensure!(TMClientStorage::contains_key(client_id), Error::<T>::ItemNotFound);
+        let info = ClientInfoMap::get(client_id);
+        let latest = ConsensusStates::get(client_id, info.last_block).ok_or(Error::<T>::NoneValue)?;
+        let header = latest.state.state.header().clone();
+        ensure!(
+            light_client::seconds_after(&header.time, info.trusting_period) > Self::now(),
+            Error::<T>::ClientExpired
+        );
+        Ok((info.last_block, header))
+    }
+
+    /// Returns the header of the counterparty tracked by `client_id` at `proof_height`, whose app hash is the root the
+    /// counterparty committed to. The client must not have expired.
+    fn counterparty_header(client_id: &[u8], proof_height: Height) -> Result<Header, Error<T>> {
+        Ok(Self::counterparty_consensus_state(client_id, proof_height)?.state.header().clone())
+    }
+
+    /// Returns the consensus state of `client_id` at `proof_height`. The client must not have expired.
+    fn counterparty_consensus_state(client_id: &[u8], proof_height: Height) -> Result<ConsensusState, Error<T>> {
+        Self::active_client(client_id)?;
+        let state = ConsensusStates::get(client_id, proof_height).ok_or(Error::<T>::ConsensusStateNotFound)?;
+        Ok(state.state)
+    }
+
+    /// Returns the header of the counterparty at `proof_height` to verify packet proofs over `connection` against, as
+    /// `counterparty_header`. The delay period of the connection must have passed since the client stored it.
+    fn packet_proof_header(connection: &ConnectionEnd, proof_height: Height) -> Result<Header, Error<T>> {
+        let state = Self::counterparty_consensus_state(&connection.client_id, proof_height)?;
+        connection::verify_delay_passed(
+            connection,
+            state.last_update.timestamp_nanos() as u64,
+            ProcessedHeights::get(&connection.client_id, proof_height),
+            Self::now().timestamp_nanos() as u64,
+            Self::own_height().revision_height,
+            T::MaxExpectedTimePerBlock::get().saturating_mul(1_000_000),
+        ).map_err(Self::connection_error)?;
+        Ok(state.state.header().clone())
+    }
+
+    /// Returns the root the counterparty tracked by `client_id` committed to at `proof_height`: the app hash of the
+    /// client's consensus state at that height. The client must not have expired.
+    fn counterparty_root(client_id: &[u8], proof_height: Height) -> Result<Vec<u8>, Error<T>> {
+        Ok(Self::counterparty_header(client_id, proof_height)?.app_hash)
+    }
+
+    /// Height of the current block, as counterparties see this chain.
+    fn own_height() -> Height {
+        Height::new(0, <system::Module<T>>::block_number().unique_saturated_into())
+    }
+
+    /// Returns the end of the channel of `port_id` and `channel_id`, and the connection it runs over.
+    fn channel_connection(port_id: &[u8], channel_id: &[u8]) -> Result<(ChannelEnd, ConnectionEnd), Error<T>> {
+        let end = Channels::get(port_id, channel_id).ok_or(Error::<T>::ChannelNotFound)?;
+        let connection = Connections::get(end.connection_id()).ok_or(Error::<T>::ConnectionNotFound)?;
+        Ok((end, connection))
+    }
+
+    /// Stores `end` as the channel of `port_id` and `channel_id`, whose packets are numbered from 1.
+    fn store_new_channel(port_id: &[u8], channel_id: &[u8], end: ChannelEnd) {
+        NextChannelSequence::mutate(|sequence| *sequence += 1);
+        NextSequenceSend::insert(port_id, channel_id, 1);
+        Self::store_next_sequence_recv(port_id, channel_id, 1);
+        NextSequenceAck::insert(port_id, channel_id, 1);
+        debug!("Stored new channel {:?}/{:?}: {:#?}", port_id, channel_id, end);
+        Self::store_channel(port_id, channel_id, end);
+    }
+
+    /// Sends `data` from the channel of `port_id` and `channel_id` to its counterparty, and returns the sequence of the
+    /// packet. Called by the module bound to the port. The packet must time out after the latest height or time of the
+    /// counterparty this chain trusts, given as `timeout_height` or `timeout_timestamp` (in nanoseconds).
+    pub fn send_packet(
+        port_id: Vec<u8>,
+        channel_id: Vec<u8>,
+        data: Vec<u8>,
+        timeout_height: Height,
+        timeout_timestamp: u64,
+    ) -> Result<u64, dispatch::DispatchError> {
+        let (end, connection) = Self::channel_connection(&port_id, &channel_id)?;
+        let (latest_height, latest_header) = Self::active_client(&connection.client_id)?;
+        let sequence = NextSequenceSend::get(&port_id, &channel_id);
+        let packet = Packet {
+            sequence,
+            source_port: port_id.clone(),
+            source_channel: channel_id.clone(),
+            destination_port: end.counterparty.port_id.clone(),
+            destination_channel: end.counterparty.channel_id.clone(),
+            data,
+            timeout_height,
+            timeout_timestamp,
+        };
+        channel::send_packet(&end, &packet, latest_height, latest_header.time.timestamp_nanos() as u64)
+            .map_err(Self::channel_error)?;
+
+        NextSequenceSend::insert(&port_id, &channel_id, sequence + 1);
+        host::set(&channel::packet_commitment_path(&port_id, &channel_id, sequence), &packet.commitment());
+        PacketCommitments::insert((port_id, channel_id), sequence, packet.commitment());
+        Self::deposit_event(RawEvent::SendPacket(packet));
+        Ok(sequence)
+    }
+
+    /// Stores `end` under the next connection id, and lists it as a connection of its client.
+    fn store_new_connection(end: ConnectionEnd) -> Vec<u8> {
+        let sequence = NextConnectionSequence::get();
+        NextConnectionSequence::put(sequence + 1);
+        let connection_id = connection::connection_id(sequence);
+        ClientConnections::mutate(&end.client_id, |connections| connections.push(connection_id.clone()));
+        debug!("Stored new connection {:?}: {:#?}", connection_id, end);
+        Self::store_connection(&connection_id, end);
+        connection_id
+    }
+
+    /// Stores `end` as connection `connection_id`, and commits to it in the host store.
+    fn store_connection(connection_id: &[u8], end: ConnectionEnd) {
+        host::set(&connection::connection_path(connection_id), &proto::encode(&end.to_proto()));
+        Connections::insert(connection_id, end);
+    }
+
+    /// Stores `end` as the channel of `port_id` and `channel_id`, and commits to it in the host store.
+    fn store_channel(port_id: &[u8], channel_id: &[u8], end: ChannelEnd) {
+        host::set(&channel::channel_path(port_id, channel_id), &proto::encode(&end.to_proto()));
+        Channels::insert(port_id, channel_id, end);
+    }
+
+    /// Stores the next sequence the channel of `port_id` and `channel_id` receives, and commits to it in the host
+    /// store.
+    fn store_next_sequence_recv(port_id: &[u8], channel_id: &[u8], sequence: u64) {
+        host::set(&channel::next_sequence_recv_path(port_id, channel_id), &sequence.to_be_bytes());
+        NextSequenceRecv::insert(port_id, channel_id, sequence);
+    }
+
+    /// Stores the info of `client`, and commits to its ibc-go `ClientState` at the info's latest height in the host store,
+    /// as the counterparty verifies it.
+    fn store_client_info(client: &TendermintClient, info: TMClientInfo) {
+        let client_state = convert::proto_client_state(client, info.last_block);
+        host::set(
+            &connection::client_state_path(&client.client_id),
+            &proto::encode(&proto::Any::pack(proto::TENDERMINT_CLIENT_STATE_TYPE_URL, &client_state)),
+        );
+        ClientInfoMap::insert(&client.client_id, info);
+    }
+
+    /// Returns the value committed to at ICS-24 `path` in the host store.
+    pub fn host_commitment(path: &[u8]) -> Option<Vec<u8>> {
+        host::get(path)
+    }
+
+    /// Records the consensus state of this chain at the parent of block `n`, and forgets the one that falls out of the
+    /// `SelfHistory` most recent blocks.
+    fn record_self_consensus_state(n: T::BlockNumber) -> Weight {
+        let n: u64 = n.unique_saturated_into();
+        if n == 0 {
+            return 0;
+        }
+        let height = n - 1;
+        // A set change enacted by the parent already shows in the current set id, so the parent's is the one read
+        // at its start.
+        let authority_set_id = T::AuthoritySetId::get();
+        let parent_authority_set_id = BlockAuthoritySetId::get().unwrap_or(authority_set_id);
+        BlockAuthoritySetId::put(authority_set_id);
+        // The timestamp inherent of block `n` is not applied yet, so the stored time is the parent's.
+        let state = SelfConsensusState {
+            block_hash: <system::Module<T>>::parent_hash().as_ref().to_vec(),
+            timestamp: <pallet_timestamp::Module<T>>::get().unique_saturated_into(),
+            authority_set_id: parent_authority_set_id,
+        };
+        SelfConsensusStates::insert(height, state);
+        let history = T::SelfHistory::get().max(1) as u64;
+        if height >= history {
+            SelfConsensusStates::remove(height - history);
+        }
+        T::DbWeight::get().reads_writes(4, 3)
+    }
+
+    /// Consensus state of this chain at `height`, if still kept.
+    pub fn recorded_self_consensus_state(height: Height) -> Option<SelfConsensusState> {
+        if height.revision_number != Self::own_height().revision_number {
+            return None;
+        }
+        SelfConsensusStates::get(height.revision_height)
+    }
+
+    /// Validates the counterparty's client of this chain: that `client_state` holds this chain's parameters, and that
+    /// `consensus_state` is this chain's consensus state at `consensus_height`.
+    pub fn validate_self_client(
+        client_state: &SelfClientState,
+        consensus_state: &SelfConsensusState,
+        consensus_height: Height,
+    ) -> Result<(), SelfClientError> {
+        let own_height = Self::own_height();
+        self_client::validate_client_state(
+            client_state,
+            T::SelfChainId::get(),
+            own_height,
+            T::AuthoritySetId::get(),
+            Self::recorded_self_consensus_state(client_state.latest_height).as_ref(),
+        )?;
+        self_client::validate_consensus_state(
+            consensus_state,
+            consensus_height,
+            client_state.latest_height,
+            own_height,
+            Self::recorded_self_consensus_state(consensus_height).as_ref(),
+        )
+    }
+
+    /// Decodes the SCALE encoded client and consensus states of this chain carried by a connection handshake payload,
+    /// and validates them with `validate_self_client`. Counterparties encode their client of this chain their own way, so
+    /// this is only done with the `self-client-validation` feature.
+    #[cfg(feature = "self-client-validation")]
+    fn check_self_client(client_state: &[u8], consensus_state: &[u8], consensus_height: Height) -> Result<(), Error<T>> {
+        let client_state = SelfClientState::decode(&mut &client_state[..]).map_err(|e| {
+            error!("Self client state Deserialization Error: {:?}", e);
+            Error::<T>::DeserializeError
+        })?;
+        let consensus_state = SelfConsensusState::decode(&mut &consensus_state[..]).map_err(|e| {
+            error!("Self consensus state Deserialization Error: {:?}", e);
+            Error::<T>::DeserializeError
+        })?;
+        Self::validate_self_client(&client_state, &consensus_state, consensus_height).map_err(|e| {
+            error!("Self client Error: {:?}", e);
+            Error::<T>::InvalidSelfClient
+        })
+    }
+
+    /// Decodes a protobuf `Any` wrapping a message of type `type_url`.
+    fn decode_any<M: Message + Default>(bytes: &[u8], type_url: &str) -> Result<M, Error<T>> {
+        proto::Any::decode(bytes)
+            .ok()
+            .and_then(|any| any.unpack(type_url))
+            .ok_or_else(|| {
+                error!("Unable to decode protobuf message of type {}", type_url);
+                Error::<T>::DeserializeError
+            })
+    }
+
+    /// Returns the latest height `client_id` has been updated to, if the client exists.
+    pub fn latest_height(client_id: &[u8]) -> Option<Height> {
+        if ClientInfoMap::contains_key(client_id) {
+            Some(ClientInfoMap::get(client_id).last_block)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the rules consensus states of `client_id` are pruned by, if the client exists.
+    pub fn retention_policy(client_id: &[u8]) -> Option<RetentionPolicy> {
+        if ClientInfoMap::contains_key(client_id) {
+            Some(RetentionPolicy {
+                trusting_period: ClientInfoMap::get(client_id).trusting_period,
+                max_consensus_states: T::MaxConsensusStates::get(),
+                pruning_budget: T::PruningBudget::get(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Removes consensus states that are outside of their client's trusting period, or beyond the client's
+    /// `MaxConsensusStates` most recent ones, oldest first. The latest consensus state of a client is never removed.
+    /// Clients are checked round robin from `PruningCursor`, within `PruningBudget` steps per call.
+    fn prune_consensus_states() -> Weight {
+        let clients = AvailableClients::get();
+        let mut reads: Weight = 2;
+        let mut writes: Weight = 0;
+        if clients.is_empty() {
+            return T::DbWeight::get().reads(1);
+        }
+
+        // Time of the previous block, as this block's timestamp inherent is not applied yet
+        let now = Self::now();
+        let max_consensus_states = T::MaxConsensusStates::get().max(1) as usize;
+        let mut budget = T::PruningBudget::get();
+        let mut cursor = PruningCursor::get() as usize % clients.len();
+        reads += 1;
+
+        for _ in 0..clients.len() {
+            if budget == 0 {
+                break;
+            }
+            budget -= 1;
+            let client_id = &clients[cursor];
+            let trusting_period = ClientInfoMap::get(client_id).trusting_period;
+            let mut heights = ConsensusHeights::get(client_id);
+            reads += 2;
+
+            let mut pruned = 0;
+            while budget > 0 && pruned + 1 < heights.len() {
+                let height = heights[pruned];
+                if heights.len() - pruned <= max_consensus_states {
+                    reads += 1;
+                    let expired = ConsensusStates::get(client_id, height)
+                        .map_or(true, |s| light_client::seconds_after(&s.state.state.header().time, trusting_period) <= now);
+                    if !expired {
+                        break;
+                    }
+                }
+                ConsensusStates::remove(client_id, height);
+                ProcessedHeights::remove(client_id, height);
+                host::remove(&connection::consensus_state_path(client_id, height));
+                writes += 1;
+                pruned += 1;
+                budget -= 1;
+            }
+
+            // A client is checked again by the next call if the budget ran out before it was done
+            let unfinished = budget == 0 && pruned + 1 < heights.len();
+            if pruned > 0 {
+                let (from, to) = (heights[0], heights[pruned - 1]);
+                heights.drain(..pruned);
+                ConsensusHeights::insert(client_id, heights);
+                writes += 1;
+                debug!("Pruned consensus states of client {:?} from {:?} to {:?}", client_id, from, to);
+                Self::deposit_event(RawEvent::ConsensusStatesPruned(client_id.clone(), from, to));
+            }
+
+            if unfinished {
+                break;
+            }
+            cursor = (cursor + 1) % clients.len();
+        }
+
+        PruningCursor::put(cursor as u32);
+        writes += 1;
+        T::DbWeight::get().reads_writes(reads, writes)
+    }
+
+    /// Records the consensus state of `client_id` at `height` in the historical index, along with the current block, and
+    /// commits to its ibc-go `ConsensusState` in the host store.
+    fn store_consensus_state(client_id: &[u8], height: Height, state: ConsensusState) {
+        let consensus_state = convert::proto_consensus_state(&state);
+        host::set(
+            &connection::consensus_state_path(client_id, height),
+            &proto::encode(&proto::Any::pack(proto::TENDERMINT_CONSENSUS_STATE_TYPE_URL, &consensus_state)),
+        );
+        let wrapper = ConsensusStateWrapper { state };
+        ConsensusStates::insert(client_id, height, wrapper);
+        ProcessedHeights::insert(client_id, height, Self::own_height().revision_height);
+        ConsensusHeights::mutate(client_id, |heights| {
+            if let Err(pos) = heights.binary_search(&height) {
+                heights.insert(pos, height);
+            }
+        });
     }
 }
@@ -0,0 +1,353 @@
+//! Canonical encodings of Tendermint headers, validators and votes.
+//!
+//! Tendermint 0.33 hashes and signs amino encoded structures, while Tendermint 0.34 and CometBFT
+//! moved to protobuf. Both are built on the same wire format, so a single writer is used and the
+//! differences in field layout are spelled out per `Encoding`.
+
+use sha2::{Digest, Sha256};
+use sp_std::vec::Vec;
+
+use super::types::{BlockId, Commit, CommitSig, Header, PublicKey, Validator, ValidatorSet};
+use chrono::{DateTime, Utc};
+
+/// Block protocol version of Tendermint 0.33.
+pub const BLOCK_VERSION_AMINO: u64 = 10;
+/// Block protocol version of Tendermint 0.34 and CometBFT 0.37/0.38.
+pub const BLOCK_VERSION_PROTOBUF: u64 = 11;
+
+/// Amino prefix of registered ed25519 public keys (`tendermint/PubKeyEd25519`).
+const AMINO_PREFIX_ED25519: [u8; 4] = [0x16, 0x24, 0xDE, 0x64];
+/// Amino prefix of registered secp256k1 public keys (`tendermint/PubKeySecp256k1`).
+const AMINO_PREFIX_SECP256K1: [u8; 4] = [0xEB, 0x5A, 0xE9, 0x87];
+
+/// `SignedMsgType` of precommit votes.
+const PRECOMMIT_TYPE: u64 = 2;
+
+/// Encoding used by a chain for hashing and signing, determined by its block protocol version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// Tendermint 0.33
+    Amino,
+    /// Tendermint 0.34+, CometBFT
+    Protobuf,
+}
+
+impl Encoding {
+    pub fn from_block_version(block_version: u64) -> Option<Self> {
+        match block_version {
+            BLOCK_VERSION_AMINO => Some(Encoding::Amino),
+            BLOCK_VERSION_PROTOBUF => Some(Encoding::Protobuf),
+            _ => None,
+        }
+    }
+}
+
+/// Hash of `header`, as referenced by the block id of its commit.
+pub fn header_hash(header: &Header, encoding: Encoding) -> Vec<u8> {
+    let version = {
+        let mut buf = Vec::new();
+        field_varint(&mut buf, 1, header.version.block);
+        field_varint(&mut buf, 2, header.version.app);
+        buf
+    };
+    let (chain_id, height) = match encoding {
+        Encoding::Amino => (
+            amino_bytes(header.chain_id.as_bytes()),
+            amino_varint(header.height),
+        ),
+        Encoding::Protobuf => (
+            wrapped_bytes(header.chain_id.as_bytes()),
+            wrapped_varint(header.height),
+        ),
+    };
+    let hash_field = |bytes: &[u8]| match encoding {
+        Encoding::Amino => amino_bytes(bytes),
+        Encoding::Protobuf => wrapped_bytes(bytes),
+    };
+
+    let leaves = [
+        version,
+        chain_id,
+        height,
+        timestamp(&header.time),
+        block_id(&header.last_block_id, encoding),
+        hash_field(&header.last_commit_hash),
+        hash_field(&header.data_hash),
+        hash_field(&header.validators_hash),
+        hash_field(&header.next_validators_hash),
+        hash_field(&header.consensus_hash),
+        hash_field(&header.app_hash),
+        hash_field(&header.last_results_hash),
+        hash_field(&header.evidence_hash),
+        hash_field(&header.proposer_address),
+    ];
+    merkle_root(&leaves, encoding)
+}
+
+/// Hash of `validator_set`, as referenced by `validators_hash` of headers.
+pub fn validator_set_hash(validator_set: &ValidatorSet, encoding: Encoding) -> Vec<u8> {
+    let leaves: Vec<Vec<u8>> = validator_set
+        .validators
+        .iter()
+        .map(|v| validator_bytes(v, encoding))
+        .collect();
+    merkle_root(&leaves, encoding)
+}
+
+/// Bytes signed by the validator of `sig` when precommitting the block of `commit`.
+pub fn vote_sign_bytes(
+    chain_id: &str,
+    commit: &Commit,
+    sig: &CommitSig,
+    encoding: Encoding,
+) -> Vec<u8> {
+    let mut vote = Vec::new();
+    field_varint(&mut vote, 1, PRECOMMIT_TYPE);
+    field_sfixed64(&mut vote, 2, commit.height);
+    field_sfixed64(&mut vote, 3, commit.round as u64);
+    if !commit.block_id.is_empty() {
+        field_message(
+            &mut vote,
+            4,
+            &canonical_block_id(&commit.block_id, encoding),
+        );
+    }
+    field_message(&mut vote, 5, &timestamp(&sig.timestamp));
+    field_bytes(&mut vote, 6, chain_id.as_bytes());
+
+    let mut buf = Vec::with_capacity(vote.len() + 2);
+    uvarint(&mut buf, vote.len() as u64);
+    buf.extend_from_slice(&vote);
+    buf
+}
+
+fn validator_bytes(validator: &Validator, encoding: Encoding) -> Vec<u8> {
+    let (amino_prefix, proto_field, key) = match &validator.pub_key {
+        PublicKey::Ed25519(key) => (AMINO_PREFIX_ED25519, 1, key),
+        PublicKey::Secp256k1(key) => (AMINO_PREFIX_SECP256K1, 2, key),
+        // Validator sets with unsupported keys are rejected before being hashed
+        PublicKey::Unsupported { value, .. } => ([0; 4], 0, value),
+    };
+    let pub_key = match encoding {
+        Encoding::Amino => {
+            let mut buf = amino_prefix.to_vec();
+            uvarint(&mut buf, key.len() as u64);
+            buf.extend_from_slice(key);
+            buf
+        }
+        Encoding::Protobuf => {
+            let mut buf = Vec::new();
+            field_bytes(&mut buf, proto_field, key);
+            buf
+        }
+    };
+    let mut buf = Vec::new();
+    field_message(&mut buf, 1, &pub_key);
+    field_varint(&mut buf, 2, validator.voting_power);
+    buf
+}
+
+fn block_id(block_id: &BlockId, encoding: Encoding) -> Vec<u8> {
+    let mut parts = Vec::new();
+    field_varint(&mut parts, 1, block_id.parts.total as u64);
+    field_bytes(&mut parts, 2, &block_id.parts.hash);
+
+    let mut buf = Vec::new();
+    field_bytes(&mut buf, 1, &block_id.hash);
+    match encoding {
+        Encoding::Amino if block_id.is_empty() => {}
+        _ => field_message(&mut buf, 2, &parts),
+    }
+    buf
+}
+
+/// Canonical block ids order the part set header fields differently in amino.
+fn canonical_block_id(block_id: &BlockId, encoding: Encoding) -> Vec<u8> {
+    let mut parts = Vec::new();
+    match encoding {
+        Encoding::Amino => {
+            field_bytes(&mut parts, 1, &block_id.parts.hash);
+            field_varint(&mut parts, 2, block_id.parts.total as u64);
+        }
+        Encoding::Protobuf => {
+            field_varint(&mut parts, 1, block_id.parts.total as u64);
+            field_bytes(&mut parts, 2, &block_id.parts.hash);
+        }
+    }
+    let mut buf = Vec::new();
+    field_bytes(&mut buf, 1, &block_id.hash);
+    field_message(&mut buf, 2, &parts);
+    buf
+}
+
+fn timestamp(time: &DateTime<Utc>) -> Vec<u8> {
+    let mut buf = Vec::new();
+    field_varint(&mut buf, 1, time.timestamp() as u64);
+    field_varint(&mut buf, 2, time.timestamp_subsec_nanos() as u64);
+    buf
+}
+
+/// Amino encoding of a byte slice or string; empty values are not encoded at all.
+fn amino_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    if !bytes.is_empty() {
+        uvarint(&mut buf, bytes.len() as u64);
+        buf.extend_from_slice(bytes);
+    }
+    buf
+}
+
+fn amino_varint(value: u64) -> Vec<u8> {
+    let mut buf = Vec::new();
+    if value != 0 {
+        uvarint(&mut buf, value);
+    }
+    buf
+}
+
+/// Protobuf `BytesValue`/`StringValue` wrapper; empty values are not encoded at all.
+fn wrapped_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    field_bytes(&mut buf, 1, bytes);
+    buf
+}
+
+/// Protobuf `Int64Value` wrapper; zero is not encoded at all.
+fn wrapped_varint(value: u64) -> Vec<u8> {
+    let mut buf = Vec::new();
+    field_varint(&mut buf, 1, value);
+    buf
+}
+
+/// RFC 6962 merkle root, as computed by Tendermint's `SimpleHashFromByteSlices`.
+pub fn merkle_root<T: AsRef<[u8]>>(leaves: &[T], encoding: Encoding) -> Vec<u8> {
+    match leaves.len() {
+        0 => match encoding {
+            Encoding::Amino => Vec::new(),
+            Encoding::Protobuf => Sha256::digest(&[]).to_vec(),
+        },
+        1 => {
+            let mut hasher = Sha256::new();
+            hasher.input(&[0u8]);
+            hasher.input(leaves[0].as_ref());
+            hasher.result().to_vec()
+        }
+        n => {
+            let split = n.next_power_of_two() / 2;
+            let mut hasher = Sha256::new();
+            hasher.input(&[1u8]);
+            hasher.input(&merkle_root(&leaves[..split], encoding));
+            hasher.input(&merkle_root(&leaves[split..], encoding));
+            hasher.result().to_vec()
+        }
+    }
+}
+
+fn uvarint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn field_varint(buf: &mut Vec<u8>, tag: u64, value: u64) {
+    if value != 0 {
+        uvarint(buf, tag << 3);
+        uvarint(buf, value);
+    }
+}
+
+fn field_sfixed64(buf: &mut Vec<u8>, tag: u64, value: u64) {
+    if value != 0 {
+        uvarint(buf, tag << 3 | 1);
+        buf.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn field_bytes(buf: &mut Vec<u8>, tag: u64, bytes: &[u8]) {
+    if !bytes.is_empty() {
+        field_message(buf, tag, bytes);
+    }
+}
+
+/// Embedded messages are always encoded, even when empty.
+fn field_message(buf: &mut Vec<u8>, tag: u64, message: &[u8]) {
+    uvarint(buf, tag << 3 | 2);
+    uvarint(buf, message.len() as u64);
+    buf.extend_from_slice(message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::light_client::types::{BlockIdFlag, PartSetHeader};
+    use chrono::TimeZone;
+
+    fn commit(
+        height: u64,
+        round: u32,
+        block_id: BlockId,
+        timestamp: DateTime<Utc>,
+    ) -> (Commit, CommitSig) {
+        let sig = CommitSig {
+            block_id_flag: BlockIdFlag::Commit,
+            validator_address: Vec::new(),
+            timestamp,
+            signature: Vec::new(),
+        };
+        let commit = Commit {
+            height,
+            round,
+            block_id,
+            signatures: vec![sig.clone()],
+        };
+        (commit, sig)
+    }
+
+    /// Precommit vector of CometBFT's `TestVoteSignBytesTestVectors`: go's zero time, no block id
+    /// and no chain id.
+    #[test]
+    fn encodes_vote_sign_bytes_test_vector() {
+        let (commit, sig) = commit(1, 1, BlockId::default(), Utc.ymd(1, 1, 1).and_hms(0, 0, 0));
+        assert_eq!(
+            vote_sign_bytes("", &commit, &sig, Encoding::Protobuf),
+            vec![
+                0x21, // length
+                0x08, 0x02, // type: precommit
+                0x11, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // height
+                0x19, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // round
+                0x2a, 0x0b, 0x08, 0x80, 0x92, 0xb8, 0xc3, 0x98, 0xfe, 0xff, 0xff, 0xff,
+                0x01, // timestamp
+            ]
+        );
+    }
+
+    #[test]
+    fn encodes_vote_sign_bytes_with_block_id_and_chain_id() {
+        let block_id = BlockId {
+            hash: vec![0x01, 0x02],
+            parts: PartSetHeader {
+                total: 1,
+                hash: vec![0x03],
+            },
+        };
+        let timestamp = Utc.ymd(2017, 12, 25).and_hms_milli(3, 0, 1, 234);
+        let (commit, sig) = commit(1, 1, block_id, timestamp);
+        assert_eq!(
+            vote_sign_bytes("test_chain_id", &commit, &sig, Encoding::Protobuf),
+            vec![
+                0x3d, // length
+                0x08, 0x02, // type: precommit
+                0x11, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // height
+                0x19, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // round
+                0x22, 0x0b, 0x0a, 0x02, 0x01, 0x02, 0x12, 0x05, 0x08, 0x01, 0x12, 0x01,
+                0x03, // block id: hash, then total and hash of the part set header
+                0x2a, 0x0b, 0x08, 0xb1, 0xd3, 0x81, 0xd2, 0x05, 0x10, 0x80, 0x9d, 0xca,
+                0x6f, // timestamp
+                0x32, 0x0d, 0x74, 0x65, 0x73, 0x74, 0x5f, 0x63, 0x68, 0x61, 0x69, 0x6e, 0x5f, 0x69,
+                0x64, // chain id
+            ]
+        );
+    }
+}
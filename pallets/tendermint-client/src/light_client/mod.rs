@@ -0,0 +1,641 @@
+//! Tendermint light client verification, following the Tendermint light client specification.
+//!
+//! Supports Tendermint 0.33 (block version 10) and Tendermint 0.34+/CometBFT (block version 11),
+//! selecting the hashing and vote sign bytes encoding from the block version of each header.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sp_std::collections::btree_set::BTreeSet;
+
+pub mod encoding;
+mod serializers;
+pub mod types;
+
+pub use encoding::Encoding;
+pub use types::{
+    BlockId, BlockIdFlag, Commit, CommitSig, Header, PublicKey, SignedHeader,
+    TrustThresholdFraction, Validator, ValidatorSet,
+};
+
+/// Reasons a header can fail verification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerificationError {
+    /// Block protocol version is not supported, or differs from the trusted one.
+    UnsupportedBlockVersion(u64),
+    /// Validator set does not hash to the header's `validators_hash`.
+    InvalidValidatorSet,
+    /// Next validator set does not hash to the header's `next_validators_hash`.
+    InvalidNextValidatorSet,
+    /// Validator address does not match its public key, or appears twice.
+    InvalidValidator,
+    /// Validator public key is of a type signatures cannot be verified for.
+    UnsupportedPublicKey,
+    /// Commit is for another height or block than the header.
+    CommitHeaderMismatch,
+    /// Commit signatures do not line up with the validator set.
+    InvalidCommit,
+    /// A commit signature does not verify.
+    InvalidSignature,
+    /// Less than the required voting power signed the commit.
+    InsufficientVotingPower,
+    /// Header is for another chain than the trusted header.
+    ChainIdMismatch,
+    /// Header is not above the trusted height.
+    NonIncreasingHeight,
+    /// Header time is not after the trusted time.
+    NonIncreasingTime,
+    /// Header time is ahead of the current time by more than the allowed clock drift.
+    HeaderFromFuture,
+    /// Trusted header is older than the trusting period.
+    TrustingPeriodExpired,
+    /// Validator set of the trusted header's next height is unknown.
+    MissingTrustedValidators,
+}
+
+/// Latest verified header, along with the validator set of the following height when known.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TrustedState {
+    pub signed_header: SignedHeader,
+    pub next_validators: Option<ValidatorSet>,
+}
+
+impl TrustedState {
+    pub fn new(signed_header: SignedHeader, next_validators: Option<ValidatorSet>) -> Self {
+        TrustedState {
+            signed_header,
+            next_validators,
+        }
+    }
+
+    /// Trusted state of an initial header signed by `validator_set`, which is kept as the next
+    /// validator set if the header commits to it staying unchanged.
+    pub fn initial(signed_header: SignedHeader, validator_set: ValidatorSet) -> Self {
+        let next_validators =
+            if signed_header.header.next_validators_hash == signed_header.header.validators_hash {
+                Some(validator_set)
+            } else {
+                None
+            };
+        TrustedState::new(signed_header, next_validators)
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.signed_header.header
+    }
+}
+
+/// Parameters of the verification of a header against a trusted state.
+#[derive(Clone, Copy, Debug)]
+pub struct Options {
+    pub trust_threshold: TrustThresholdFraction,
+    /// Seconds after which a trusted header can no longer be used to verify new headers.
+    pub trusting_period: u64,
+    /// Seconds a header time may be ahead of `now`.
+    pub max_clock_drift: u64,
+    pub now: DateTime<Utc>,
+}
+
+/// Returns the encoding for headers of `header`'s block version.
+pub fn encoding_of(header: &Header) -> Result<Encoding, VerificationError> {
+    Encoding::from_block_version(header.version.block).ok_or(
+        VerificationError::UnsupportedBlockVersion(header.version.block),
+    )
+}
+
+/// Validates that `signed_header` is signed by more than 2/3 of `validator_set`,
+/// which it must commit to. Used to trust the initial header of a client.
+pub fn validate_initial_signed_header_and_valset(
+    signed_header: &SignedHeader,
+    validator_set: &ValidatorSet,
+) -> Result<(), VerificationError> {
+    let encoding = encoding_of(&signed_header.header)?;
+    validate_signed_header(signed_header, validator_set, encoding)?;
+    verify_commit(signed_header, validator_set, encoding)
+}
+
+/// Verifies `untrusted` against `trusted` and returns the new trusted state.
+///
+/// Adjacent headers must be signed by the validator set the trusted header committed to.
+/// Non adjacent headers must be signed by more than `trust_threshold` of the trusted validators,
+/// given in `trusted_validators` unless known to the trusted state.
+pub fn verify_single(
+    trusted: &TrustedState,
+    untrusted: &SignedHeader,
+    validator_set: &ValidatorSet,
+    next_validator_set: Option<&ValidatorSet>,
+    trusted_validators: Option<&ValidatorSet>,
+    options: Options,
+) -> Result<TrustedState, VerificationError> {
+    let trusted_header = trusted.header();
+    let header = &untrusted.header;
+
+    let encoding = encoding_of(header)?;
+    if encoding_of(trusted_header)? != encoding {
+        return Err(VerificationError::UnsupportedBlockVersion(
+            header.version.block,
+        ));
+    }
+
+    if seconds_after(&trusted_header.time, options.trusting_period) <= options.now {
+        return Err(VerificationError::TrustingPeriodExpired);
+    }
+    if header.chain_id != trusted_header.chain_id {
+        return Err(VerificationError::ChainIdMismatch);
+    }
+    if header.height <= trusted_header.height {
+        return Err(VerificationError::NonIncreasingHeight);
+    }
+    if header.time <= trusted_header.time {
+        return Err(VerificationError::NonIncreasingTime);
+    }
+    if header.time >= seconds_after(&options.now, options.max_clock_drift) {
+        return Err(VerificationError::HeaderFromFuture);
+    }
+
+    validate_signed_header(untrusted, validator_set, encoding)?;
+    if let Some(next_validator_set) = next_validator_set {
+        validate_validators(next_validator_set)?;
+        if encoding::validator_set_hash(next_validator_set, encoding) != header.next_validators_hash
+        {
+            return Err(VerificationError::InvalidNextValidatorSet);
+        }
+    }
+
+    if header.height == trusted_header.height + 1 {
+        if header.validators_hash != trusted_header.next_validators_hash {
+            return Err(VerificationError::InvalidValidatorSet);
+        }
+    } else {
+        let trusted_validators = trusted_validators
+            .or_else(|| trusted.next_validators.as_ref())
+            .ok_or(VerificationError::MissingTrustedValidators)?;
+        validate_validators(trusted_validators)?;
+        if encoding::validator_set_hash(trusted_validators, encoding)
+            != trusted_header.next_validators_hash
+        {
+            return Err(VerificationError::MissingTrustedValidators);
+        }
+        verify_commit_trusting(
+            untrusted,
+            trusted_validators,
+            options.trust_threshold,
+            encoding,
+        )?;
+    }
+    verify_commit(untrusted, validator_set, encoding)?;
+
+    let next_validators = match next_validator_set {
+        Some(next_validator_set) => Some(next_validator_set.clone()),
+        None if header.next_validators_hash == header.validators_hash => {
+            Some(validator_set.clone())
+        }
+        None => None,
+    };
+    Ok(TrustedState::new(untrusted.clone(), next_validators))
+}
+
+/// Checks that `validator_set` is the one of `signed_header`, and that the commit is for its block.
+fn validate_signed_header(
+    signed_header: &SignedHeader,
+    validator_set: &ValidatorSet,
+    encoding: Encoding,
+) -> Result<(), VerificationError> {
+    let header = &signed_header.header;
+    let commit = &signed_header.commit;
+
+    validate_validators(validator_set)?;
+    if encoding::validator_set_hash(validator_set, encoding) != header.validators_hash {
+        return Err(VerificationError::InvalidValidatorSet);
+    }
+
+    if commit.height != header.height
+        || commit.block_id.hash != encoding::header_hash(header, encoding)
+    {
+        return Err(VerificationError::CommitHeaderMismatch);
+    }
+    Ok(())
+}
+
+/// Validator addresses are not covered by the validator set hash, so they must be derived from the keys.
+fn validate_validators(validator_set: &ValidatorSet) -> Result<(), VerificationError> {
+    let mut addresses = BTreeSet::new();
+    for validator in validator_set.validators.iter() {
+        let address = validator
+            .pub_key
+            .address()
+            .ok_or(VerificationError::UnsupportedPublicKey)?;
+        if validator.address != address || !addresses.insert(address) {
+            return Err(VerificationError::InvalidValidator);
+        }
+    }
+    Ok(())
+}
+
+/// Verifies that more than 2/3 of `validator_set` signed the commit of `signed_header`.
+/// Signatures are expected in validator set order, as produced by Tendermint.
+fn verify_commit(
+    signed_header: &SignedHeader,
+    validator_set: &ValidatorSet,
+    encoding: Encoding,
+) -> Result<(), VerificationError> {
+    let commit = &signed_header.commit;
+    if commit.signatures.len() != validator_set.validators.len() {
+        return Err(VerificationError::InvalidCommit);
+    }
+
+    let mut tallied: u64 = 0;
+    for (sig, validator) in commit
+        .signatures
+        .iter()
+        .zip(validator_set.validators.iter())
+    {
+        if sig.block_id_flag != BlockIdFlag::Commit {
+            continue;
+        }
+        if sig.validator_address != validator.address {
+            return Err(VerificationError::InvalidCommit);
+        }
+        verify_signature(
+            &signed_header.header.chain_id,
+            commit,
+            sig,
+            validator,
+            encoding,
+        )?;
+        tallied = tallied.saturating_add(validator.voting_power);
+    }
+
+    if !TrustThresholdFraction::TWO_THIRDS
+        .is_exceeded_by(tallied, validator_set.total_voting_power())
+    {
+        return Err(VerificationError::InsufficientVotingPower);
+    }
+    Ok(())
+}
+
+/// Verifies that more than `trust_threshold` of `trusted_validators` signed the commit of `signed_header`.
+fn verify_commit_trusting(
+    signed_header: &SignedHeader,
+    trusted_validators: &ValidatorSet,
+    trust_threshold: TrustThresholdFraction,
+    encoding: Encoding,
+) -> Result<(), VerificationError> {
+    let commit = &signed_header.commit;
+    let total = trusted_validators.total_voting_power();
+
+    let mut seen: BTreeSet<&[u8]> = BTreeSet::new();
+    let mut tallied: u64 = 0;
+    for sig in commit.signatures.iter() {
+        if sig.block_id_flag != BlockIdFlag::Commit {
+            continue;
+        }
+        let validator = match trusted_validators.validator(&sig.validator_address) {
+            Some(validator) => validator,
+            None => continue,
+        };
+        if !seen.insert(&validator.address) {
+            return Err(VerificationError::InvalidCommit);
+        }
+        verify_signature(
+            &signed_header.header.chain_id,
+            commit,
+            sig,
+            validator,
+            encoding,
+        )?;
+        tallied = tallied.saturating_add(validator.voting_power);
+
+        if trust_threshold.is_exceeded_by(tallied, total) {
+            return Ok(());
+        }
+    }
+    Err(VerificationError::InsufficientVotingPower)
+}
+
+fn verify_signature(
+    chain_id: &str,
+    commit: &Commit,
+    sig: &CommitSig,
+    validator: &Validator,
+    encoding: Encoding,
+) -> Result<(), VerificationError> {
+    let sign_bytes = encoding::vote_sign_bytes(chain_id, commit, sig, encoding);
+    let valid = match &validator.pub_key {
+        PublicKey::Ed25519(key) => verify_ed25519(key, &sig.signature, &sign_bytes),
+        PublicKey::Secp256k1(key) => verify_secp256k1(key, &sig.signature, &sign_bytes),
+        PublicKey::Unsupported { .. } => return Err(VerificationError::UnsupportedPublicKey),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(VerificationError::InvalidSignature)
+    }
+}
+
+/// Verifies an ed25519 signature through the `ed25519_verify` host function, so that commits of large
+/// validator sets are verified natively rather than in Wasm.
+fn verify_ed25519(key: &[u8], signature: &[u8], message: &[u8]) -> bool {
+    use core::convert::TryFrom;
+    use sp_core::ed25519;
+
+    match (
+        ed25519::Public::try_from(key),
+        ed25519::Signature::try_from(signature),
+    ) {
+        (Ok(key), Ok(signature)) => sp_io::crypto::ed25519_verify(&signature, message, &key),
+        _ => false,
+    }
+}
+
+/// Verifies a secp256k1 signature over the SHA-256 digest of `message`. As in Tendermint, signatures are
+/// 64 byte `r || s` values, and malleable high `s` values are rejected.
+fn verify_secp256k1(key: &[u8], signature: &[u8], message: &[u8]) -> bool {
+    let key = match secp256k1::PublicKey::parse_slice(
+        key,
+        Some(secp256k1::PublicKeyFormat::Compressed),
+    ) {
+        Ok(key) => key,
+        Err(_) => return false,
+    };
+    let signature = match secp256k1::Signature::parse_slice(signature) {
+        Ok(signature) if !signature.s.is_high() => signature,
+        _ => return false,
+    };
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(&Sha256::digest(message));
+    secp256k1::verify(&secp256k1::Message::parse(&digest), &signature, &key)
+}
+
+/// Duration of `secs` seconds, saturating at the bounds of chrono durations.
+pub fn seconds(secs: u64) -> Duration {
+    Duration::seconds(secs.min(i64::MAX as u64 / 1_000) as i64)
+}
+
+/// `time` moved `secs` seconds ahead, saturating at the latest time chrono can represent.
+pub fn seconds_after(time: &DateTime<Utc>, secs: u64) -> DateTime<Utc> {
+    time.checked_add_signed(seconds(secs))
+        .unwrap_or(chrono::MAX_DATETIME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[derive(Deserialize)]
+    struct Fixture {
+        signed_header: SignedHeader,
+        validator_set: ValidatorSet,
+    }
+
+    #[derive(Deserialize)]
+    struct Payload {
+        header: Fixture,
+    }
+
+    fn fixture(json: &str) -> Fixture {
+        serde_json::from_str::<Payload>(json).unwrap().header
+    }
+
+    fn options(now: DateTime<Utc>) -> Options {
+        Options {
+            trust_threshold: TrustThresholdFraction::default(),
+            trusting_period: 86400 * 14,
+            max_clock_drift: 30,
+            now,
+        }
+    }
+
+    #[test]
+    fn validates_tendermint_0_33_fixtures() {
+        for json in [
+            include_str!("../../../../test_create.json"),
+            include_str!("../../../../test_update.json"),
+            include_str!("../../../../test_update2.json"),
+        ]
+        .iter()
+        {
+            let f = fixture(json);
+            assert_eq!(encoding_of(&f.signed_header.header), Ok(Encoding::Amino));
+            assert_eq!(
+                validate_initial_signed_header_and_valset(&f.signed_header, &f.validator_set),
+                Ok(())
+            );
+        }
+    }
+
+    #[test]
+    fn validates_tendermint_0_34_fixtures() {
+        for json in [
+            include_str!("../../../../test_create_v034.json"),
+            include_str!("../../../../test_update_v034.json"),
+        ]
+        .iter()
+        {
+            let f = fixture(json);
+            assert_eq!(encoding_of(&f.signed_header.header), Ok(Encoding::Protobuf));
+            assert_eq!(
+                validate_initial_signed_header_and_valset(&f.signed_header, &f.validator_set),
+                Ok(())
+            );
+        }
+    }
+
+    #[test]
+    fn validates_secp256k1_fixture() {
+        let f = fixture(include_str!("../../../../test_create_secp256k1.json"));
+        assert!(f
+            .validator_set
+            .validators
+            .iter()
+            .all(|v| matches!(v.pub_key, PublicKey::Secp256k1(_))));
+        assert_eq!(
+            validate_initial_signed_header_and_valset(&f.signed_header, &f.validator_set),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_high_s_signature() {
+        let mut f = fixture(include_str!("../../../../test_create_secp256k1.json"));
+        let signature = &mut f.signed_header.commit.signatures[0].signature;
+        let mut malleated = secp256k1::Signature::parse_slice(signature).unwrap();
+        // (r, n - s) verifies as well, but is not the canonical signature
+        malleated.s = -malleated.s;
+        assert!(malleated.s.is_high());
+        *signature = malleated.serialize().to_vec();
+        assert_eq!(
+            validate_initial_signed_header_and_valset(&f.signed_header, &f.validator_set),
+            Err(VerificationError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn derives_secp256k1_addresses() {
+        // Key and address of Tendermint's `TestPubKeySecp256k1Address` vector, whose bitcoin address
+        // 1CKZ9Nx4zgds8tU7nJHotKSDr4a9bYJCa3 encodes RIPEMD160(SHA256(key))
+        let key = PublicKey::Secp256k1(
+            serializers::decode(
+                "02950e1cdfcb133d6024109fd489f734eeb4502418e538c28481f22bce276f248c",
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            key.address(),
+            Some(serializers::decode("7c2bb42a8be69791ec763e51f5a49bcd41e82237").unwrap())
+        );
+
+        let f = fixture(include_str!("../../../../test_create_secp256k1.json"));
+        for validator in f.validator_set.validators.iter() {
+            assert_eq!(validator.pub_key.address(), Some(validator.address.clone()));
+        }
+    }
+
+    #[test]
+    fn rejects_unsupported_public_key() {
+        let mut f = fixture(include_str!("../../../../test_create.json"));
+        f.validator_set.validators[0].pub_key = serde_json::from_str(
+            r#"{"type":"tendermint/PubKeySr25519","value":"KeW+psSC/QpZgSbuPcD9wtDgxTgSepjqqh2wvV7dd1Y="}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            validate_initial_signed_header_and_valset(&f.signed_header, &f.validator_set),
+            Err(VerificationError::UnsupportedPublicKey)
+        );
+    }
+
+    #[test]
+    fn rejects_tampered_signature() {
+        let mut f = fixture(include_str!("../../../../test_create.json"));
+        f.signed_header.commit.signatures[0].signature[0] ^= 1;
+        assert_eq!(
+            validate_initial_signed_header_and_valset(&f.signed_header, &f.validator_set),
+            Err(VerificationError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn rejects_mismatched_encoding() {
+        let mut f = fixture(include_str!("../../../../test_create_v034.json"));
+        f.signed_header.header.version.block = encoding::BLOCK_VERSION_AMINO;
+        assert_eq!(
+            validate_initial_signed_header_and_valset(&f.signed_header, &f.validator_set),
+            Err(VerificationError::InvalidValidatorSet)
+        );
+    }
+
+    #[test]
+    fn saturates_periods_beyond_representable_times() {
+        let created = fixture(include_str!("../../../../test_create.json"));
+        let updated = fixture(include_str!("../../../../test_update.json"));
+        let trusted = TrustedState::new(created.signed_header, Some(created.validator_set));
+        let options = Options {
+            trusting_period: 1_800_000_000_000_000,
+            max_clock_drift: u64::MAX,
+            ..options(updated.signed_header.header.time + Duration::seconds(1))
+        };
+        assert!(verify_single(
+            &trusted,
+            &updated.signed_header,
+            &updated.validator_set,
+            None,
+            None,
+            options,
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_updates() {
+        let created = fixture(include_str!("../../../../test_create.json"));
+        let updated = fixture(include_str!("../../../../test_update.json"));
+        let trusted = TrustedState::new(updated.signed_header, Some(updated.validator_set));
+        let now = trusted.header().time + Duration::days(2);
+        let verify = |untrusted: &SignedHeader, now| {
+            verify_single(
+                &trusted,
+                untrusted,
+                &created.validator_set,
+                None,
+                None,
+                options(now),
+            )
+        };
+
+        assert_eq!(
+            verify(&created.signed_header, now),
+            Err(VerificationError::NonIncreasingHeight)
+        );
+
+        let mut other_chain = created.signed_header.clone();
+        other_chain.header.chain_id = "fedzone-4".into();
+        assert_eq!(
+            verify(&other_chain, now),
+            Err(VerificationError::ChainIdMismatch)
+        );
+
+        let mut later = created.signed_header.clone();
+        later.header.height = trusted.header().height + 1;
+        assert_eq!(
+            verify(&later, now),
+            Err(VerificationError::NonIncreasingTime)
+        );
+
+        later.header.time = now + Duration::minutes(1);
+        assert_eq!(
+            verify(&later, now),
+            Err(VerificationError::HeaderFromFuture)
+        );
+
+        later.header.time = now;
+        assert_eq!(
+            verify(&later, now),
+            Err(VerificationError::CommitHeaderMismatch)
+        );
+    }
+
+    #[test]
+    fn verifies_non_adjacent_update() {
+        for (create, update) in [
+            (
+                include_str!("../../../../test_create.json"),
+                include_str!("../../../../test_update.json"),
+            ),
+            (
+                include_str!("../../../../test_create_v034.json"),
+                include_str!("../../../../test_update_v034.json"),
+            ),
+        ]
+        .iter()
+        {
+            let created = fixture(create);
+            let updated = fixture(update);
+            let trusted = TrustedState::new(created.signed_header, Some(created.validator_set));
+            let now = trusted.header().time + Duration::days(2);
+
+            let verified = verify_single(
+                &trusted,
+                &updated.signed_header,
+                &updated.validator_set,
+                Some(&updated.validator_set),
+                None,
+                options(now),
+            )
+            .unwrap();
+            assert_eq!(verified.signed_header, updated.signed_header);
+
+            assert_eq!(
+                verify_single(
+                    &trusted,
+                    &updated.signed_header,
+                    &updated.validator_set,
+                    None,
+                    None,
+                    options(Utc.timestamp(i32::MAX as i64, 0)),
+                ),
+                Err(VerificationError::TrustingPeriodExpired)
+            );
+        }
+    }
+}
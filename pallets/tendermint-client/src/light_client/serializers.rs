@@ -0,0 +1,114 @@
+//! Serde helpers for Tendermint's RPC JSON layout: integers as strings,
+//! hashes and addresses as upper case hex, keys and signatures as base64.
+
+use alloc::string::{String, ToString};
+use core::str::FromStr;
+use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+use sp_std::{fmt::Display, vec::Vec};
+
+/// Integers encoded as JSON strings. Plain JSON numbers, as emitted by Tendermint 0.34+ for
+/// 32 bit fields, are accepted as well.
+pub mod from_str {
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Unsigned(u64),
+        Signed(i64),
+    }
+
+    pub fn serialize<S: Serializer, T: Display>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: FromStr,
+        T::Err: Display,
+    {
+        match StringOrNumber::deserialize(deserializer)? {
+            StringOrNumber::String(s) => s,
+            StringOrNumber::Unsigned(n) => n.to_string(),
+            StringOrNumber::Signed(n) => n.to_string(),
+        }
+        .parse()
+        .map_err(D::Error::custom)
+    }
+}
+
+/// Bytes encoded as upper case hex strings.
+pub mod hex_bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode_upper(value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = Option::<String>::deserialize(deserializer)?.unwrap_or_default();
+        decode(&s).ok_or_else(|| D::Error::custom("invalid hex string"))
+    }
+}
+
+/// Bytes encoded as base64 strings.
+pub mod base64_bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64::encode(value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = Option::<String>::deserialize(deserializer)?.unwrap_or_default();
+        base64::decode(&s).map_err(D::Error::custom)
+    }
+}
+
+/// Bytes encoded as base64 strings, with empty bytes encoded as `null`.
+pub mod nullable_base64_bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        if value.is_empty() {
+            serializer.serialize_none()
+        } else {
+            base64_bytes::serialize(value, serializer)
+        }
+    }
+
+    pub use super::base64_bytes::deserialize;
+}
+
+pub fn encode_upper(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        s.push(DIGITS[(b >> 4) as usize] as char);
+        s.push(DIGITS[(b & 0xf) as usize] as char);
+    }
+    s
+}
+
+pub fn decode(s: &str) -> Option<Vec<u8>> {
+    fn nibble(c: u8) -> Option<u8> {
+        match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            b'A'..=b'F' => Some(c - b'A' + 10),
+            _ => None,
+        }
+    }
+    let s = s.as_bytes();
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    s.chunks(2)
+        .map(|pair| Some(nibble(pair[0])? << 4 | nibble(pair[1])?))
+        .collect()
+}
@@ -0,0 +1,266 @@
+//! Tendermint block, commit and validator types, (de)serialized from Tendermint's RPC JSON layout.
+
+use alloc::string::String;
+use chrono::{DateTime, Utc};
+use ripemd160::Ripemd160;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sp_std::vec::Vec;
+
+use super::serializers::{base64_bytes, from_str, hex_bytes, nullable_base64_bytes};
+
+/// Length of validator addresses.
+pub const ADDRESS_LENGTH: usize = 20;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Version {
+    #[serde(with = "from_str")]
+    pub block: u64,
+    #[serde(with = "from_str", default)]
+    pub app: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PartSetHeader {
+    #[serde(with = "from_str")]
+    pub total: u32,
+    #[serde(with = "hex_bytes")]
+    pub hash: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockId {
+    #[serde(with = "hex_bytes")]
+    pub hash: Vec<u8>,
+    #[serde(default)]
+    pub parts: PartSetHeader,
+}
+
+impl BlockId {
+    pub fn is_empty(&self) -> bool {
+        self.hash.is_empty() && self.parts.hash.is_empty() && self.parts.total == 0
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub version: Version,
+    pub chain_id: String,
+    #[serde(with = "from_str")]
+    pub height: u64,
+    pub time: DateTime<Utc>,
+    #[serde(default)]
+    pub last_block_id: BlockId,
+    #[serde(with = "hex_bytes")]
+    pub last_commit_hash: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub data_hash: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub validators_hash: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub next_validators_hash: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub consensus_hash: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub app_hash: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub last_results_hash: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub evidence_hash: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub proposer_address: Vec<u8>,
+}
+
+/// Whether a validator voted for the committed block.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(from = "u8", into = "u8")]
+pub enum BlockIdFlag {
+    Absent,
+    Commit,
+    Nil,
+    Unknown(u8),
+}
+
+impl From<u8> for BlockIdFlag {
+    fn from(flag: u8) -> Self {
+        match flag {
+            1 => BlockIdFlag::Absent,
+            2 => BlockIdFlag::Commit,
+            3 => BlockIdFlag::Nil,
+            other => BlockIdFlag::Unknown(other),
+        }
+    }
+}
+
+impl From<BlockIdFlag> for u8 {
+    fn from(flag: BlockIdFlag) -> Self {
+        match flag {
+            BlockIdFlag::Absent => 1,
+            BlockIdFlag::Commit => 2,
+            BlockIdFlag::Nil => 3,
+            BlockIdFlag::Unknown(other) => other,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CommitSig {
+    pub block_id_flag: BlockIdFlag,
+    #[serde(with = "hex_bytes")]
+    pub validator_address: Vec<u8>,
+    pub timestamp: DateTime<Utc>,
+    #[serde(with = "nullable_base64_bytes")]
+    pub signature: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Commit {
+    #[serde(with = "from_str")]
+    pub height: u64,
+    #[serde(with = "from_str")]
+    pub round: u32,
+    pub block_id: BlockId,
+    pub signatures: Vec<CommitSig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SignedHeader {
+    pub header: Header,
+    pub commit: Commit,
+}
+
+/// Type name of ed25519 public keys.
+pub const ED25519_KEY_TYPE: &str = "tendermint/PubKeyEd25519";
+/// Type name of secp256k1 public keys.
+pub const SECP256K1_KEY_TYPE: &str = "tendermint/PubKeySecp256k1";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(from = "RawPublicKey", into = "RawPublicKey")]
+pub enum PublicKey {
+    Ed25519(Vec<u8>),
+    /// Compressed secp256k1 point, as used by Ethermint-style validators.
+    Secp256k1(Vec<u8>),
+    /// Key of a type signatures cannot be verified for. Kept so that it can be rejected explicitly.
+    Unsupported {
+        key_type: String,
+        value: Vec<u8>,
+    },
+}
+
+impl PublicKey {
+    /// Address of the validator holding this key, if of a supported type.
+    pub fn address(&self) -> Option<Vec<u8>> {
+        match self {
+            PublicKey::Ed25519(key) => Some(Sha256::digest(key)[..ADDRESS_LENGTH].to_vec()),
+            PublicKey::Secp256k1(key) => Some(Ripemd160::digest(&Sha256::digest(key)).to_vec()),
+            PublicKey::Unsupported { .. } => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawPublicKey {
+    #[serde(rename = "type")]
+    key_type: String,
+    #[serde(with = "base64_bytes")]
+    value: Vec<u8>,
+}
+
+impl From<RawPublicKey> for PublicKey {
+    fn from(raw: RawPublicKey) -> Self {
+        match raw.key_type.as_str() {
+            ED25519_KEY_TYPE => PublicKey::Ed25519(raw.value),
+            SECP256K1_KEY_TYPE => PublicKey::Secp256k1(raw.value),
+            _ => PublicKey::Unsupported {
+                key_type: raw.key_type,
+                value: raw.value,
+            },
+        }
+    }
+}
+
+impl From<PublicKey> for RawPublicKey {
+    fn from(key: PublicKey) -> Self {
+        let (key_type, value) = match key {
+            PublicKey::Ed25519(value) => (ED25519_KEY_TYPE.into(), value),
+            PublicKey::Secp256k1(value) => (SECP256K1_KEY_TYPE.into(), value),
+            PublicKey::Unsupported { key_type, value } => (key_type, value),
+        };
+        RawPublicKey { key_type, value }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Validator {
+    #[serde(with = "hex_bytes")]
+    pub address: Vec<u8>,
+    pub pub_key: PublicKey,
+    #[serde(with = "from_str")]
+    pub voting_power: u64,
+    #[serde(with = "from_str", default)]
+    pub proposer_priority: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorSet {
+    pub validators: Vec<Validator>,
+    #[serde(default)]
+    pub proposer: Option<Validator>,
+}
+
+impl ValidatorSet {
+    pub fn total_voting_power(&self) -> u64 {
+        self.validators
+            .iter()
+            .fold(0u64, |total, v| total.saturating_add(v.voting_power))
+    }
+
+    pub fn validator(&self, address: &[u8]) -> Option<&Validator> {
+        self.validators.iter().find(|v| v.address == address)
+    }
+}
+
+/// Fraction of a trusted validator set's voting power that must sign a header for it to be trusted.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrustThresholdFraction {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+impl TrustThresholdFraction {
+    /// Tendermint's default of 1/3, the minimum voting power guaranteed to include an honest validator.
+    pub const ONE_THIRD: Self = TrustThresholdFraction {
+        numerator: 1,
+        denominator: 3,
+    };
+    /// Voting power needed for a commit to be valid.
+    pub const TWO_THIRDS: Self = TrustThresholdFraction {
+        numerator: 2,
+        denominator: 3,
+    };
+
+    /// Returns a threshold of `numerator / denominator`, which must lie within [1/3, 1].
+    pub fn new(numerator: u64, denominator: u64) -> Option<Self> {
+        if denominator == 0
+            || numerator > denominator
+            || (numerator as u128) * 3 < (denominator as u128)
+        {
+            return None;
+        }
+        Some(TrustThresholdFraction {
+            numerator,
+            denominator,
+        })
+    }
+
+    /// Whether `signed` out of `total` voting power is strictly above the threshold.
+    pub fn is_exceeded_by(&self, signed: u64, total: u64) -> bool {
+        (signed as u128) * (self.denominator as u128) > (total as u128) * (self.numerator as u128)
+    }
+}
+
+impl Default for TrustThresholdFraction {
+    fn default() -> Self {
+        Self::ONE_THIRD
+    }
+}
@@ -1,7 +1,12 @@
 // Creating mock runtime here
 
+use crate::channel::Packet;
+use crate::module::IbcModule;
 use crate::{Module, Trait};
-use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+use frame_support::{
+    dispatch::DispatchResult, ensure, impl_outer_event, impl_outer_origin, parameter_types,
+    traits::Get, weights::Weight,
+};
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
@@ -9,11 +14,23 @@ use sp_runtime::{
     traits::{BlakeTwo256, IdentityLookup},
     Perbill,
 };
+use std::cell::RefCell;
 
 impl_outer_origin! {
     pub enum Origin for Test {}
 }
 
+mod tendermint_client {
+    pub use crate::Event;
+}
+
+impl_outer_event! {
+    pub enum TestEvent for Test {
+        system<T>,
+        tendermint_client<T>,
+    }
+}
+
 // For testing the pallet, we construct most of a mock runtime. This means
 // first constructing a configuration type (`Test`) which `impl`s each of the
 // configuration traits of pallets we want to use.
@@ -24,6 +41,11 @@ parameter_types! {
     pub const MaximumBlockWeight: Weight = 1024;
     pub const MaximumBlockLength: u32 = 2 * 1024;
     pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+    pub const MinimumPeriod: u64 = 1;
+    pub const MaxConsensusStates: u32 = 3;
+    pub const PruningBudget: u32 = 10;
+    pub const SelfChainId: &'static [u8] = b"wormhole";
+    pub const MaxExpectedTimePerBlock: u64 = 6_000;
 }
 impl system::Trait for Test {
     type BaseCallFilter = ();
@@ -36,7 +58,7 @@ impl system::Trait for Test {
     type AccountId = u64;
     type Lookup = IdentityLookup<Self::AccountId>;
     type Header = Header;
-    type Event = ();
+    type Event = TestEvent;
     type BlockHashCount = BlockHashCount;
     type MaximumBlockWeight = MaximumBlockWeight;
     type DbWeight = ();
@@ -52,16 +74,115 @@ impl system::Trait for Test {
     type OnKilledAccount = ();
     type SystemWeightInfo = ();
 }
+impl pallet_timestamp::Trait for Test {
+    type Moment = u64;
+    type OnTimestampSet = ();
+    type MinimumPeriod = MinimumPeriod;
+    type WeightInfo = ();
+}
 impl Trait for Test {
-    type Event = ();
+    type Event = TestEvent;
+    type MaxConsensusStates = MaxConsensusStates;
+    type PruningBudget = PruningBudget;
+    type Modules = (MockModule,);
+    type SelfChainId = SelfChainId;
+    type AuthoritySetId = AuthoritySetId;
+    type SelfHistory = SelfHistory;
+    type MaxExpectedTimePerBlock = MaxExpectedTimePerBlock;
+    type WeightInfo = ();
+}
+
+thread_local! {
+    static AUTHORITY_SET_ID: RefCell<u64> = RefCell::new(1);
+    static SELF_HISTORY: RefCell<u32> = RefCell::new(3);
+    static PACKETS: RefCell<Vec<(&'static str, u64)>> = RefCell::new(Vec::new());
 }
+
+/// Current GRANDPA authority set id, 1 unless a test changes it.
+pub struct AuthoritySetId;
+
+impl AuthoritySetId {
+    pub fn set(id: u64) {
+        AUTHORITY_SET_ID.with(|set_id| *set_id.borrow_mut() = id);
+    }
+}
+
+impl Get<u64> for AuthoritySetId {
+    fn get() -> u64 {
+        AUTHORITY_SET_ID.with(|set_id| *set_id.borrow())
+    }
+}
+
+/// Number of recent blocks whose consensus states are kept, 3 unless a test changes it.
+pub struct SelfHistory;
+
+impl SelfHistory {
+    pub fn set(history: u32) {
+        SELF_HISTORY.with(|blocks| *blocks.borrow_mut() = history);
+    }
+}
+
+impl Get<u32> for SelfHistory {
+    fn get() -> u32 {
+        SELF_HISTORY.with(|blocks| *blocks.borrow())
+    }
+}
+
+/// Acknowledgement the mock module fails to process.
+pub const FAILING_ACKNOWLEDGEMENT: &[u8] = b"fail";
+
+/// Module bound to the `mock` port, which acknowledges received packets with their data reversed
+/// and records the packets it is handed.
+pub struct MockModule;
+
+impl MockModule {
+    /// Callbacks the module was handed packets in, with the sequence of the packets.
+    pub fn packets() -> Vec<(&'static str, u64)> {
+        PACKETS.with(|packets| packets.borrow().clone())
+    }
+
+    fn record(callback: &'static str, packet: &Packet) {
+        PACKETS.with(|packets| packets.borrow_mut().push((callback, packet.sequence)));
+    }
+}
+
+impl IbcModule for MockModule {
+    fn port_id() -> &'static [u8] {
+        b"mock"
+    }
+
+    fn on_recv_packet(packet: &Packet) -> Vec<u8> {
+        Self::record("recv", packet);
+        packet.data.iter().rev().cloned().collect()
+    }
+
+    fn on_acknowledgement_packet(packet: &Packet, acknowledgement: &[u8]) -> DispatchResult {
+        Self::record("acknowledgement", packet);
+        ensure!(
+            acknowledgement != FAILING_ACKNOWLEDGEMENT,
+            "acknowledgement failed"
+        );
+        Ok(())
+    }
+
+    fn on_timeout_packet(packet: &Packet) -> DispatchResult {
+        Self::record("timeout", packet);
+        Ok(())
+    }
+}
+
 pub type TemplateModule = Module<Test>;
+pub type System = system::Module<Test>;
+pub type Timestamp = pallet_timestamp::Module<Test>;
 
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-    system::GenesisConfig::default()
+    let mut ext: sp_io::TestExternalities = system::GenesisConfig::default()
         .build_storage::<Test>()
         .unwrap()
-        .into()
+        .into();
+    // Events are not recorded in the genesis block
+    ext.execute_with(|| System::set_block_number(1));
+    ext
 }
@@ -0,0 +1,207 @@
+//! Callbacks of the pallets bound to IBC ports.
+//!
+//! A pallet binds a port by implementing `IbcModule` and being listed in the runtime's `Modules`.
+//! The handshakes of the port's channels, and the packets received, acknowledged or timed out on
+//! them, are handed to the module, which may reject them. Modules send packets with
+//! `Module::send_packet`.
+
+use frame_support::dispatch::{DispatchError, DispatchResult};
+use sp_std::vec::Vec;
+
+use crate::channel::{ChannelEnd, Packet};
+
+/// Callbacks of a pallet bound to a port. Returning an error from a callback aborts the step, and
+/// reverts any storage written by the callback or the pallet.
+pub trait IbcModule {
+    /// Port the module is bound to.
+    fn port_id() -> &'static [u8];
+
+    /// Called when a channel of the port is opened by `ChanOpenInit`.
+    fn on_chan_open_init(_channel_id: &[u8], _channel: &ChannelEnd) -> DispatchResult {
+        Ok(())
+    }
+
+    /// Called when a channel of the port is opened by `ChanOpenTry`, and returns the version of the
+    /// channel given the counterparty's.
+    fn on_chan_open_try(
+        _channel_id: &[u8],
+        _channel: &ChannelEnd,
+        counterparty_version: &[u8],
+    ) -> Result<Vec<u8>, DispatchError> {
+        Ok(counterparty_version.to_vec())
+    }
+
+    /// Called when a channel of the port is opened on both ends by `ChanOpenAck`.
+    fn on_chan_open_ack(_channel_id: &[u8], _counterparty_version: &[u8]) -> DispatchResult {
+        Ok(())
+    }
+
+    /// Called when a channel of the port is opened on both ends by `ChanOpenConfirm`.
+    fn on_chan_open_confirm(_channel_id: &[u8]) -> DispatchResult {
+        Ok(())
+    }
+
+    /// Called when a channel of the port is closed by `ChanCloseInit`.
+    fn on_chan_close_init(_channel_id: &[u8]) -> DispatchResult {
+        Ok(())
+    }
+
+    /// Called when a channel of the port is closed by `ChanCloseConfirm`.
+    fn on_chan_close_confirm(_channel_id: &[u8]) -> DispatchResult {
+        Ok(())
+    }
+
+    /// Handles a packet received on a channel of the port, and returns its acknowledgement. Failures
+    /// to handle the packet are reported to the sender in the acknowledgement.
+    fn on_recv_packet(packet: &Packet) -> Vec<u8>;
+
+    /// Called when the counterparty acknowledged a packet sent from the port.
+    fn on_acknowledgement_packet(packet: &Packet, acknowledgement: &[u8]) -> DispatchResult;
+
+    /// Called when a packet sent from the port timed out before the counterparty received it.
+    fn on_timeout_packet(packet: &Packet) -> DispatchResult;
+}
+
+/// Modules bound to ports, to whose module of the port callbacks are routed. Implemented for tuples
+/// of `IbcModule`s, the first module bound to a port taking it.
+pub trait IbcModules {
+    /// Whether a module is bound to `port_id`.
+    fn is_bound(port_id: &[u8]) -> bool;
+
+    fn on_chan_open_init(port_id: &[u8], channel_id: &[u8], channel: &ChannelEnd)
+        -> DispatchResult;
+
+    fn on_chan_open_try(
+        port_id: &[u8],
+        channel_id: &[u8],
+        channel: &ChannelEnd,
+        counterparty_version: &[u8],
+    ) -> Result<Vec<u8>, DispatchError>;
+
+    fn on_chan_open_ack(
+        port_id: &[u8],
+        channel_id: &[u8],
+        counterparty_version: &[u8],
+    ) -> DispatchResult;
+
+    fn on_chan_open_confirm(port_id: &[u8], channel_id: &[u8]) -> DispatchResult;
+
+    fn on_chan_close_init(port_id: &[u8], channel_id: &[u8]) -> DispatchResult;
+
+    fn on_chan_close_confirm(port_id: &[u8], channel_id: &[u8]) -> DispatchResult;
+
+    /// Acknowledgement of `packet` by the module of its destination port, if one is bound.
+    fn on_recv_packet(packet: &Packet) -> Option<Vec<u8>>;
+
+    fn on_acknowledgement_packet(packet: &Packet, acknowledgement: &[u8]) -> DispatchResult;
+
+    fn on_timeout_packet(packet: &Packet) -> DispatchResult;
+}
+
+const PORT_NOT_BOUND: DispatchError = DispatchError::Other("no module is bound to the port");
+
+macro_rules! impl_ibc_modules {
+    ($($module:ident),*) => {
+        impl<$($module: IbcModule),*> IbcModules for ($($module,)*) {
+            fn is_bound(port_id: &[u8]) -> bool {
+                let _ = port_id;
+                false $(|| $module::port_id() == port_id)*
+            }
+
+            fn on_chan_open_init(
+                port_id: &[u8],
+                channel_id: &[u8],
+                channel: &ChannelEnd,
+            ) -> DispatchResult {
+                $(if $module::port_id() == port_id {
+                    return $module::on_chan_open_init(channel_id, channel);
+                })*
+                let _ = (port_id, channel_id, channel);
+                Err(PORT_NOT_BOUND)
+            }
+
+            fn on_chan_open_try(
+                port_id: &[u8],
+                channel_id: &[u8],
+                channel: &ChannelEnd,
+                counterparty_version: &[u8],
+            ) -> Result<Vec<u8>, DispatchError> {
+                $(if $module::port_id() == port_id {
+                    return $module::on_chan_open_try(channel_id, channel, counterparty_version);
+                })*
+                let _ = (port_id, channel_id, channel, counterparty_version);
+                Err(PORT_NOT_BOUND)
+            }
+
+            fn on_chan_open_ack(
+                port_id: &[u8],
+                channel_id: &[u8],
+                counterparty_version: &[u8],
+            ) -> DispatchResult {
+                $(if $module::port_id() == port_id {
+                    return $module::on_chan_open_ack(channel_id, counterparty_version);
+                })*
+                let _ = (port_id, channel_id, counterparty_version);
+                Err(PORT_NOT_BOUND)
+            }
+
+            fn on_chan_open_confirm(port_id: &[u8], channel_id: &[u8]) -> DispatchResult {
+                $(if $module::port_id() == port_id {
+                    return $module::on_chan_open_confirm(channel_id);
+                })*
+                let _ = (port_id, channel_id);
+                Err(PORT_NOT_BOUND)
+            }
+
+            fn on_chan_close_init(port_id: &[u8], channel_id: &[u8]) -> DispatchResult {
+                $(if $module::port_id() == port_id {
+                    return $module::on_chan_close_init(channel_id);
+                })*
+                let _ = (port_id, channel_id);
+                Err(PORT_NOT_BOUND)
+            }
+
+            fn on_chan_close_confirm(port_id: &[u8], channel_id: &[u8]) -> DispatchResult {
+                $(if $module::port_id() == port_id {
+                    return $module::on_chan_close_confirm(channel_id);
+                })*
+                let _ = (port_id, channel_id);
+                Err(PORT_NOT_BOUND)
+            }
+
+            fn on_recv_packet(packet: &Packet) -> Option<Vec<u8>> {
+                $(if $module::port_id() == &packet.destination_port[..] {
+                    return Some($module::on_recv_packet(packet));
+                })*
+                let _ = packet;
+                None
+            }
+
+            fn on_acknowledgement_packet(packet: &Packet, acknowledgement: &[u8]) -> DispatchResult {
+                $(if $module::port_id() == &packet.source_port[..] {
+                    return $module::on_acknowledgement_packet(packet, acknowledgement);
+                })*
+                let _ = (packet, acknowledgement);
+                Err(PORT_NOT_BOUND)
+            }
+
+            fn on_timeout_packet(packet: &Packet) -> DispatchResult {
+                $(if $module::port_id() == &packet.source_port[..] {
+                    return $module::on_timeout_packet(packet);
+                })*
+                let _ = packet;
+                Err(PORT_NOT_BOUND)
+            }
+        }
+    };
+}
+
+impl_ibc_modules!();
+impl_ibc_modules!(A);
+impl_ibc_modules!(A, B);
+impl_ibc_modules!(A, B, C);
+impl_ibc_modules!(A, B, C, D);
+impl_ibc_modules!(A, B, C, D, E);
+impl_ibc_modules!(A, B, C, D, E, F);
+impl_ibc_modules!(A, B, C, D, E, F, G);
+impl_ibc_modules!(A, B, C, D, E, F, G, H);
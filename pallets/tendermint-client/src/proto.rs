@@ -0,0 +1,487 @@
+//! Protobuf messages exchanged with Cosmos SDK / ibc-go chains.
+//! Only the messages and fields the pallet reads are declared; prost skips unknown fields when decoding.
+
+use alloc::string::String;
+use prost::Message;
+use sp_std::vec::Vec;
+
+pub const TENDERMINT_CLIENT_STATE_TYPE_URL: &str = "/ibc.lightclients.tendermint.v1.ClientState";
+pub const TENDERMINT_CONSENSUS_STATE_TYPE_URL: &str =
+    "/ibc.lightclients.tendermint.v1.ConsensusState";
+pub const TENDERMINT_HEADER_TYPE_URL: &str = "/ibc.lightclients.tendermint.v1.Header";
+
+/// Protobuf encoding of `message`.
+pub fn encode<M: Message>(message: &M) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(message.encoded_len());
+    message
+        .encode(&mut bytes)
+        .expect("vec has the capacity of the encoded message");
+    bytes
+}
+
+/// `google.protobuf.Any`
+#[derive(Clone, PartialEq, Message)]
+pub struct Any {
+    #[prost(string, tag = "1")]
+    pub type_url: String,
+    #[prost(bytes, tag = "2")]
+    pub value: Vec<u8>,
+}
+
+impl Any {
+    /// Wraps `message` as `type_url`.
+    pub fn pack<M: Message>(type_url: &str, message: &M) -> Self {
+        Any {
+            type_url: type_url.into(),
+            value: encode(message),
+        }
+    }
+
+    /// Decodes the wrapped message, provided `type_url` matches `expected_type_url`.
+    pub fn unpack<M: Message + Default>(&self, expected_type_url: &str) -> Option<M> {
+        if self.type_url != expected_type_url {
+            return None;
+        }
+        M::decode(&self.value[..]).ok()
+    }
+}
+
+/// `google.protobuf.Timestamp`
+#[derive(Clone, PartialEq, Message)]
+pub struct Timestamp {
+    #[prost(int64, tag = "1")]
+    pub seconds: i64,
+    #[prost(int32, tag = "2")]
+    pub nanos: i32,
+}
+
+/// `google.protobuf.Duration`
+#[derive(Clone, PartialEq, Message)]
+pub struct Duration {
+    #[prost(int64, tag = "1")]
+    pub seconds: i64,
+    #[prost(int32, tag = "2")]
+    pub nanos: i32,
+}
+
+/// `ibc.core.client.v1.Height`
+#[derive(Clone, PartialEq, Message)]
+pub struct Height {
+    #[prost(uint64, tag = "1")]
+    pub revision_number: u64,
+    #[prost(uint64, tag = "2")]
+    pub revision_height: u64,
+}
+
+/// `ibc.lightclients.tendermint.v1.Fraction`
+#[derive(Clone, PartialEq, Message)]
+pub struct Fraction {
+    #[prost(uint64, tag = "1")]
+    pub numerator: u64,
+    #[prost(uint64, tag = "2")]
+    pub denominator: u64,
+}
+
+/// `ibc.lightclients.tendermint.v1.ClientState`, without `proof_specs`.
+#[derive(Clone, PartialEq, Message)]
+pub struct ClientState {
+    #[prost(string, tag = "1")]
+    pub chain_id: String,
+    #[prost(message, optional, tag = "2")]
+    pub trust_level: Option<Fraction>,
+    #[prost(message, optional, tag = "3")]
+    pub trusting_period: Option<Duration>,
+    #[prost(message, optional, tag = "4")]
+    pub unbonding_period: Option<Duration>,
+    #[prost(message, optional, tag = "5")]
+    pub max_clock_drift: Option<Duration>,
+    #[prost(message, optional, tag = "6")]
+    pub frozen_height: Option<Height>,
+    #[prost(message, optional, tag = "7")]
+    pub latest_height: Option<Height>,
+    #[prost(string, repeated, tag = "9")]
+    pub upgrade_path: Vec<String>,
+    #[prost(bool, tag = "10")]
+    pub allow_update_after_expiry: bool,
+    #[prost(bool, tag = "11")]
+    pub allow_update_after_misbehaviour: bool,
+}
+
+/// `ibc.core.commitment.v1.MerkleRoot`
+#[derive(Clone, PartialEq, Message)]
+pub struct MerkleRoot {
+    #[prost(bytes, tag = "1")]
+    pub hash: Vec<u8>,
+}
+
+/// `ibc.lightclients.tendermint.v1.ConsensusState`
+#[derive(Clone, PartialEq, Message)]
+pub struct ConsensusState {
+    #[prost(message, optional, tag = "1")]
+    pub timestamp: Option<Timestamp>,
+    #[prost(message, optional, tag = "2")]
+    pub root: Option<MerkleRoot>,
+    #[prost(bytes, tag = "3")]
+    pub next_validators_hash: Vec<u8>,
+}
+
+/// `ibc.lightclients.tendermint.v1.Header`
+#[derive(Clone, PartialEq, Message)]
+pub struct Header {
+    #[prost(message, optional, tag = "1")]
+    pub signed_header: Option<SignedHeader>,
+    #[prost(message, optional, tag = "2")]
+    pub validator_set: Option<ValidatorSet>,
+    #[prost(message, optional, tag = "3")]
+    pub trusted_height: Option<Height>,
+    #[prost(message, optional, tag = "4")]
+    pub trusted_validators: Option<ValidatorSet>,
+}
+
+/// `tendermint.types.SignedHeader`
+#[derive(Clone, PartialEq, Message)]
+pub struct SignedHeader {
+    #[prost(message, optional, tag = "1")]
+    pub header: Option<TmHeader>,
+    #[prost(message, optional, tag = "2")]
+    pub commit: Option<Commit>,
+}
+
+/// `tendermint.version.Consensus`
+#[derive(Clone, PartialEq, Message)]
+pub struct Consensus {
+    #[prost(uint64, tag = "1")]
+    pub block: u64,
+    #[prost(uint64, tag = "2")]
+    pub app: u64,
+}
+
+/// `tendermint.types.Header`
+#[derive(Clone, PartialEq, Message)]
+pub struct TmHeader {
+    #[prost(message, optional, tag = "1")]
+    pub version: Option<Consensus>,
+    #[prost(string, tag = "2")]
+    pub chain_id: String,
+    #[prost(int64, tag = "3")]
+    pub height: i64,
+    #[prost(message, optional, tag = "4")]
+    pub time: Option<Timestamp>,
+    #[prost(message, optional, tag = "5")]
+    pub last_block_id: Option<BlockId>,
+    #[prost(bytes, tag = "6")]
+    pub last_commit_hash: Vec<u8>,
+    #[prost(bytes, tag = "7")]
+    pub data_hash: Vec<u8>,
+    #[prost(bytes, tag = "8")]
+    pub validators_hash: Vec<u8>,
+    #[prost(bytes, tag = "9")]
+    pub next_validators_hash: Vec<u8>,
+    #[prost(bytes, tag = "10")]
+    pub consensus_hash: Vec<u8>,
+    #[prost(bytes, tag = "11")]
+    pub app_hash: Vec<u8>,
+    #[prost(bytes, tag = "12")]
+    pub last_results_hash: Vec<u8>,
+    #[prost(bytes, tag = "13")]
+    pub evidence_hash: Vec<u8>,
+    #[prost(bytes, tag = "14")]
+    pub proposer_address: Vec<u8>,
+}
+
+/// `tendermint.types.PartSetHeader`
+#[derive(Clone, PartialEq, Message)]
+pub struct PartSetHeader {
+    #[prost(uint32, tag = "1")]
+    pub total: u32,
+    #[prost(bytes, tag = "2")]
+    pub hash: Vec<u8>,
+}
+
+/// `tendermint.types.BlockID`
+#[derive(Clone, PartialEq, Message)]
+pub struct BlockId {
+    #[prost(bytes, tag = "1")]
+    pub hash: Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    pub part_set_header: Option<PartSetHeader>,
+}
+
+/// `tendermint.types.Commit`
+#[derive(Clone, PartialEq, Message)]
+pub struct Commit {
+    #[prost(int64, tag = "1")]
+    pub height: i64,
+    #[prost(int32, tag = "2")]
+    pub round: i32,
+    #[prost(message, optional, tag = "3")]
+    pub block_id: Option<BlockId>,
+    #[prost(message, repeated, tag = "4")]
+    pub signatures: Vec<CommitSig>,
+}
+
+/// `tendermint.types.CommitSig`
+#[derive(Clone, PartialEq, Message)]
+pub struct CommitSig {
+    #[prost(enumeration = "BlockIdFlag", tag = "1")]
+    pub block_id_flag: i32,
+    #[prost(bytes, tag = "2")]
+    pub validator_address: Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    pub timestamp: Option<Timestamp>,
+    #[prost(bytes, tag = "4")]
+    pub signature: Vec<u8>,
+}
+
+/// `tendermint.types.BlockIDFlag`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum BlockIdFlag {
+    Unknown = 0,
+    Absent = 1,
+    Commit = 2,
+    Nil = 3,
+}
+
+/// `tendermint.types.ValidatorSet`
+#[derive(Clone, PartialEq, Message)]
+pub struct ValidatorSet {
+    #[prost(message, repeated, tag = "1")]
+    pub validators: Vec<Validator>,
+    #[prost(message, optional, tag = "2")]
+    pub proposer: Option<Validator>,
+    #[prost(int64, tag = "3")]
+    pub total_voting_power: i64,
+}
+
+/// `tendermint.types.Validator`
+#[derive(Clone, PartialEq, Message)]
+pub struct Validator {
+    #[prost(bytes, tag = "1")]
+    pub address: Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    pub pub_key: Option<PublicKey>,
+    #[prost(int64, tag = "3")]
+    pub voting_power: i64,
+    #[prost(int64, tag = "4")]
+    pub proposer_priority: i64,
+}
+
+/// `tendermint.crypto.PublicKey`
+#[derive(Clone, PartialEq, Message)]
+pub struct PublicKey {
+    #[prost(oneof = "public_key::Sum", tags = "1, 2")]
+    pub sum: Option<public_key::Sum>,
+}
+
+pub mod public_key {
+    use prost::Oneof;
+    use sp_std::vec::Vec;
+
+    #[derive(Clone, PartialEq, Oneof)]
+    pub enum Sum {
+        #[prost(bytes, tag = "1")]
+        Ed25519(Vec<u8>),
+        #[prost(bytes, tag = "2")]
+        Secp256k1(Vec<u8>),
+    }
+}
+
+/// `ibc.core.commitment.v1.MerklePrefix`
+#[derive(Clone, PartialEq, Message)]
+pub struct MerklePrefix {
+    #[prost(bytes, tag = "1")]
+    pub key_prefix: Vec<u8>,
+}
+
+/// `ibc.core.connection.v1.ConnectionEnd`
+#[derive(Clone, PartialEq, Message)]
+pub struct ConnectionEnd {
+    #[prost(string, tag = "1")]
+    pub client_id: String,
+    #[prost(message, repeated, tag = "2")]
+    pub versions: Vec<Version>,
+    #[prost(enumeration = "ConnectionState", tag = "3")]
+    pub state: i32,
+    #[prost(message, optional, tag = "4")]
+    pub counterparty: Option<ConnectionCounterparty>,
+    #[prost(uint64, tag = "5")]
+    pub delay_period: u64,
+}
+
+/// `ibc.core.connection.v1.State`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum ConnectionState {
+    UninitializedUnspecified = 0,
+    Init = 1,
+    TryOpen = 2,
+    Open = 3,
+}
+
+/// `ibc.core.connection.v1.Counterparty`
+#[derive(Clone, PartialEq, Message)]
+pub struct ConnectionCounterparty {
+    #[prost(string, tag = "1")]
+    pub client_id: String,
+    #[prost(string, tag = "2")]
+    pub connection_id: String,
+    #[prost(message, optional, tag = "3")]
+    pub prefix: Option<MerklePrefix>,
+}
+
+/// `ibc.core.connection.v1.Version`
+#[derive(Clone, PartialEq, Message)]
+pub struct Version {
+    #[prost(string, tag = "1")]
+    pub identifier: String,
+    #[prost(string, repeated, tag = "2")]
+    pub features: Vec<String>,
+}
+
+/// `ibc.core.channel.v1.Channel`
+#[derive(Clone, PartialEq, Message)]
+pub struct Channel {
+    #[prost(enumeration = "ChannelState", tag = "1")]
+    pub state: i32,
+    #[prost(enumeration = "Order", tag = "2")]
+    pub ordering: i32,
+    #[prost(message, optional, tag = "3")]
+    pub counterparty: Option<ChannelCounterparty>,
+    #[prost(string, repeated, tag = "4")]
+    pub connection_hops: Vec<String>,
+    #[prost(string, tag = "5")]
+    pub version: String,
+}
+
+/// `ibc.core.channel.v1.State`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum ChannelState {
+    UninitializedUnspecified = 0,
+    Init = 1,
+    TryOpen = 2,
+    Open = 3,
+    Closed = 4,
+}
+
+/// `ibc.core.channel.v1.Order`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Order {
+    NoneUnspecified = 0,
+    Unordered = 1,
+    Ordered = 2,
+}
+
+/// `ibc.core.channel.v1.Counterparty`
+#[derive(Clone, PartialEq, Message)]
+pub struct ChannelCounterparty {
+    #[prost(string, tag = "1")]
+    pub port_id: String,
+    #[prost(string, tag = "2")]
+    pub channel_id: String,
+}
+
+/// `ibc.core.commitment.v1.MerkleProof`, ordered from the innermost store to the root.
+#[derive(Clone, PartialEq, Message)]
+pub struct MerkleProof {
+    #[prost(message, repeated, tag = "1")]
+    pub proofs: Vec<CommitmentProof>,
+}
+
+/// `ics23.CommitmentProof`, restricted to existence and non-existence proofs.
+#[derive(Clone, PartialEq, Message)]
+pub struct CommitmentProof {
+    #[prost(oneof = "commitment_proof::Proof", tags = "1, 2")]
+    pub proof: Option<commitment_proof::Proof>,
+}
+
+pub mod commitment_proof {
+    use prost::Oneof;
+
+    #[derive(Clone, PartialEq, Oneof)]
+    pub enum Proof {
+        #[prost(message, tag = "1")]
+        Exist(super::ExistenceProof),
+        #[prost(message, tag = "2")]
+        Nonexist(super::NonExistenceProof),
+    }
+}
+
+/// `ics23.ExistenceProof`
+#[derive(Clone, PartialEq, Message)]
+pub struct ExistenceProof {
+    #[prost(bytes, tag = "1")]
+    pub key: Vec<u8>,
+    #[prost(bytes, tag = "2")]
+    pub value: Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    pub leaf: Option<LeafOp>,
+    #[prost(message, repeated, tag = "4")]
+    pub path: Vec<InnerOp>,
+}
+
+/// `ics23.NonExistenceProof`: existence proofs of the neighbours of `key`.
+#[derive(Clone, PartialEq, Message)]
+pub struct NonExistenceProof {
+    #[prost(bytes, tag = "1")]
+    pub key: Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    pub left: Option<ExistenceProof>,
+    #[prost(message, optional, tag = "3")]
+    pub right: Option<ExistenceProof>,
+}
+
+/// `ics23.LeafOp`
+#[derive(Clone, PartialEq, Message)]
+pub struct LeafOp {
+    #[prost(enumeration = "HashOp", tag = "1")]
+    pub hash: i32,
+    #[prost(enumeration = "HashOp", tag = "2")]
+    pub prehash_key: i32,
+    #[prost(enumeration = "HashOp", tag = "3")]
+    pub prehash_value: i32,
+    #[prost(enumeration = "LengthOp", tag = "4")]
+    pub length: i32,
+    #[prost(bytes, tag = "5")]
+    pub prefix: Vec<u8>,
+}
+
+/// `ics23.InnerOp`
+#[derive(Clone, PartialEq, Message)]
+pub struct InnerOp {
+    #[prost(enumeration = "HashOp", tag = "1")]
+    pub hash: i32,
+    #[prost(bytes, tag = "2")]
+    pub prefix: Vec<u8>,
+    #[prost(bytes, tag = "3")]
+    pub suffix: Vec<u8>,
+}
+
+/// `ics23.HashOp`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum HashOp {
+    NoHash = 0,
+    Sha256 = 1,
+    Sha512 = 2,
+    Keccak = 3,
+    Ripemd160 = 4,
+    Bitcoin = 5,
+}
+
+/// `ics23.LengthOp`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum LengthOp {
+    NoPrefix = 0,
+    VarProto = 1,
+    VarRlp = 2,
+    Fixed32Big = 3,
+    Fixed32Little = 4,
+    Fixed64Big = 5,
+    Fixed64Little = 6,
+    Require32Bytes = 7,
+    Require64Bytes = 8,
+}
@@ -0,0 +1,195 @@
+//! Replays recorded header streams through the pallet on the mock runtime.
+//!
+//! A stream is a create client payload, followed by a JSONL file of update client payloads in the
+//! order they were submitted, one per line. Each update is applied at a block time one second after
+//! its header, and must move the client to the header's height and emit `ClientUpdated`. The time
+//! each update takes is reported per step.
+//!
+//! Besides the streams of the repository's fixtures, a recorded stream is replayed when
+//! `TM_REPLAY_CREATE` and `TM_REPLAY_UPDATES` point to its files:
+//!
+//! ```text
+//! TM_REPLAY_CREATE=create.json TM_REPLAY_UPDATES=updates.jsonl \
+//!     cargo test -p tendermint-client replay -- --nocapture
+//! ```
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use frame_support::assert_ok;
+
+use crate::mock::{new_test_ext, Origin, System, TemplateModule, TestEvent, Timestamp};
+use crate::types::{Height, TMCreateClientPayload, TMUpdateClientPayload};
+use crate::RawEvent;
+
+const SIGNER: u64 = 1;
+
+/// Outcome of one update of a replayed stream.
+#[derive(Debug)]
+pub struct Step {
+    pub height: Height,
+    /// Last event deposited by the update.
+    pub event: TestEvent,
+    pub elapsed: Duration,
+}
+
+/// Moves the block time to one second after `time`, starting a new block.
+fn set_time(time: DateTime<Utc>) {
+    System::set_block_number(System::block_number() + 1);
+    Timestamp::set_timestamp((time.timestamp_millis() + 1_000) as u64);
+}
+
+fn last_event() -> TestEvent {
+    System::events()
+        .pop()
+        .expect("an event was deposited")
+        .event
+}
+
+/// Creates a client from the json encoded `create` payload, then applies the json encoded update
+/// payloads of the `updates` lines in order.
+pub fn replay(create: &str, updates: &str) -> Vec<Step> {
+    new_test_ext().execute_with(|| {
+        let payload: TMCreateClientPayload =
+            serde_json::from_str(create).expect("create payload is valid json");
+        let client_id = payload.client_id.clone();
+        let chain_id = payload.header.signed_header.header.chain_id.clone();
+        let height = Height::from_chain_id(
+            chain_id.as_bytes(),
+            payload.header.signed_header.header.height,
+        );
+
+        set_time(payload.header.signed_header.header.time);
+        assert_ok!(TemplateModule::init_client(
+            Origin::signed(SIGNER),
+            create.as_bytes().to_vec()
+        ));
+        assert_eq!(TemplateModule::latest_height(&client_id), Some(height));
+
+        let mut steps = Vec::new();
+        for (line, update) in updates.lines().enumerate() {
+            if update.trim().is_empty() {
+                continue;
+            }
+            let payload: TMUpdateClientPayload = serde_json::from_str(update)
+                .unwrap_or_else(|e| panic!("line {}: invalid update payload: {}", line + 1, e));
+            assert_eq!(
+                payload.client_id,
+                client_id,
+                "line {}: update of another client",
+                line + 1
+            );
+            let header = &payload.header.signed_header.header;
+            let height = Height::from_chain_id(chain_id.as_bytes(), header.height);
+
+            set_time(header.time);
+            let start = Instant::now();
+            let result =
+                TemplateModule::update_client(Origin::signed(SIGNER), update.as_bytes().to_vec());
+            let elapsed = start.elapsed();
+
+            assert_eq!(
+                result,
+                Ok(()),
+                "line {}: update to {:?} rejected",
+                line + 1,
+                height
+            );
+            assert_eq!(TemplateModule::latest_height(&client_id), Some(height));
+            let event = last_event();
+            assert_eq!(
+                event,
+                TestEvent::tendermint_client(RawEvent::ClientUpdated(
+                    SIGNER,
+                    client_id.clone(),
+                    chain_id.as_bytes().to_vec(),
+                    height
+                ))
+            );
+            steps.push(Step {
+                height,
+                event,
+                elapsed,
+            });
+        }
+        steps
+    })
+}
+
+/// Prints the verification time of each step.
+fn report(name: &str, steps: &[Step]) {
+    println!("replay {}: {} updates", name, steps.len());
+    for step in steps {
+        println!(
+            "  {}-{}\t{:?}",
+            step.height.revision_number, step.height.revision_height, step.elapsed
+        );
+    }
+}
+
+/// One update payload per line, as recorded streams are laid out.
+fn jsonl(payloads: &[&str]) -> String {
+    payloads
+        .iter()
+        .map(|p| {
+            serde_json::from_str::<serde_json::Value>(p)
+                .unwrap()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn replays_tendermint_0_33_stream() {
+    // `test_update.json` and `test_update2.json`, as recorded from fedzone-3
+    let steps = replay(
+        include_str!("../../../test_create.json"),
+        include_str!("../../../test_updates.jsonl"),
+    );
+    report("fedzone-3", &steps);
+
+    let client_id = vec![245, 123, 213];
+    let expected = [Height::new(3, 708422), Height::new(3, 723038)];
+    assert_eq!(steps.len(), expected.len());
+    for (step, height) in steps.iter().zip(expected.iter()) {
+        assert_eq!(step.height, *height);
+        assert_eq!(
+            step.event,
+            TestEvent::tendermint_client(RawEvent::ClientUpdated(
+                SIGNER,
+                client_id.clone(),
+                b"fedzone-3".to_vec(),
+                *height
+            ))
+        );
+    }
+}
+
+#[test]
+fn replays_tendermint_0_34_stream() {
+    let updates = jsonl(&[include_str!("../../../test_update_v034.json")]);
+    let steps = replay(include_str!("../../../test_create_v034.json"), &updates);
+    report("testchain-2", &steps);
+
+    assert_eq!(
+        steps.iter().map(|s| s.height).collect::<Vec<_>>(),
+        vec![Height::new(2, 150)]
+    );
+}
+
+#[test]
+fn replays_recorded_stream() {
+    let (create, updates) = match (
+        std::env::var("TM_REPLAY_CREATE"),
+        std::env::var("TM_REPLAY_UPDATES"),
+    ) {
+        (Ok(create), Ok(updates)) => (create, updates),
+        _ => return,
+    };
+    let read = |path: &str| {
+        std::fs::read_to_string(path).unwrap_or_else(|e| panic!("unable to read {}: {}", path, e))
+    };
+    let steps = replay(&read(&create), &read(&updates));
+    report(&updates, &steps);
+}
@@ -0,0 +1,237 @@
+//! Validation of the counterparty's client of this chain.
+//!
+//! ICS-03 has each end of a connection check that the counterparty's client of it tracks this chain:
+//! that the client state holds this chain's parameters, and that its consensus states match blocks
+//! of this chain's history. The pallet records the consensus state of each of its recent blocks, and
+//! the checks here are given the ones a claim is compared to.
+
+use codec::{Decode, Encode};
+use sp_std::vec::Vec;
+
+use crate::types::Height;
+
+/// Reasons a client or consensus state of this chain can be rejected.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub enum SelfClientError {
+    /// Client tracks a different chain id.
+    ChainIdMismatch,
+    /// Client is frozen.
+    Frozen,
+    /// Height is of another revision than this chain's.
+    RevisionMismatch,
+    /// Height is not below this chain's current block.
+    HeightFromFuture,
+    /// Height is not above the client's latest height.
+    HeightAboveLatest,
+    /// Authority set id is ahead of this chain's, or differs from the recorded one.
+    AuthoritySetMismatch,
+    /// No consensus state of this chain is recorded at the height, which is out of the history kept.
+    ConsensusStateNotFound,
+    /// Consensus state differs from the one recorded at the height.
+    ConsensusStateMismatch,
+}
+
+/// Client state of this chain held by the counterparty.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SelfClientState {
+    pub chain_id: Vec<u8>,
+    pub latest_height: Height,
+    /// Height the client was frozen at for misbehaviour, or zero.
+    pub frozen_height: Height,
+    /// Id of the GRANDPA authority set the client trusts at its latest height.
+    pub authority_set_id: u64,
+}
+
+/// Consensus state of this chain at a block: the hash of its header, which commits to its state
+/// root, its time and the GRANDPA authority set finalizing it.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SelfConsensusState {
+    pub block_hash: Vec<u8>,
+    /// Block time, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub authority_set_id: u64,
+}
+
+/// Checks `client_state` against the parameters of this chain: its `chain_id`, the height of the
+/// block being built and the current authority set id. `recorded` is this chain's consensus state at
+/// the client's latest height, if still kept.
+pub fn validate_client_state(
+    client_state: &SelfClientState,
+    chain_id: &[u8],
+    own_height: Height,
+    authority_set_id: u64,
+    recorded: Option<&SelfConsensusState>,
+) -> Result<(), SelfClientError> {
+    if client_state.chain_id != chain_id {
+        return Err(SelfClientError::ChainIdMismatch);
+    }
+    if client_state.frozen_height != Height::default() {
+        return Err(SelfClientError::Frozen);
+    }
+    validate_height(client_state.latest_height, own_height)?;
+    let set_id_matches = match recorded {
+        Some(recorded) => recorded.authority_set_id == client_state.authority_set_id,
+        None => client_state.authority_set_id <= authority_set_id,
+    };
+    if !set_id_matches {
+        return Err(SelfClientError::AuthoritySetMismatch);
+    }
+    Ok(())
+}
+
+/// Checks that `consensus_state`, claimed at `height` by a client at `latest_height`, is this
+/// chain's `recorded` consensus state at `height`.
+pub fn validate_consensus_state(
+    consensus_state: &SelfConsensusState,
+    height: Height,
+    latest_height: Height,
+    own_height: Height,
+    recorded: Option<&SelfConsensusState>,
+) -> Result<(), SelfClientError> {
+    validate_height(height, own_height)?;
+    if height > latest_height {
+        return Err(SelfClientError::HeightAboveLatest);
+    }
+    match recorded {
+        Some(recorded) if recorded == consensus_state => Ok(()),
+        Some(_) => Err(SelfClientError::ConsensusStateMismatch),
+        None => Err(SelfClientError::ConsensusStateNotFound),
+    }
+}
+
+/// Checks that `height` is of a block of this chain before the one being built, at `own_height`.
+fn validate_height(height: Height, own_height: Height) -> Result<(), SelfClientError> {
+    if height.revision_number != own_height.revision_number {
+        return Err(SelfClientError::RevisionMismatch);
+    }
+    if height >= own_height {
+        return Err(SelfClientError::HeightFromFuture);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAIN_ID: &[u8] = b"wormhole";
+
+    fn own_height() -> Height {
+        Height::new(0, 100)
+    }
+
+    fn client_state() -> SelfClientState {
+        SelfClientState {
+            chain_id: CHAIN_ID.to_vec(),
+            latest_height: Height::new(0, 90),
+            frozen_height: Height::default(),
+            authority_set_id: 2,
+        }
+    }
+
+    fn consensus_state() -> SelfConsensusState {
+        SelfConsensusState {
+            block_hash: vec![9; 32],
+            timestamp: 1_600_000_000_000,
+            authority_set_id: 2,
+        }
+    }
+
+    #[test]
+    fn validates_client_state() {
+        let recorded = consensus_state();
+        let validate = |state: &SelfClientState, recorded: Option<&SelfConsensusState>| {
+            validate_client_state(state, CHAIN_ID, own_height(), 3, recorded)
+        };
+        assert_eq!(validate(&client_state(), Some(&recorded)), Ok(()));
+        // Out of the history kept, set ids are only checked not to be ahead of this chain's
+        assert_eq!(validate(&client_state(), None), Ok(()));
+
+        let other_chain = SelfClientState {
+            chain_id: b"other".to_vec(),
+            ..client_state()
+        };
+        assert_eq!(
+            validate(&other_chain, Some(&recorded)),
+            Err(SelfClientError::ChainIdMismatch)
+        );
+        let frozen = SelfClientState {
+            frozen_height: Height::new(0, 80),
+            ..client_state()
+        };
+        assert_eq!(
+            validate(&frozen, Some(&recorded)),
+            Err(SelfClientError::Frozen)
+        );
+        let ahead = SelfClientState {
+            latest_height: own_height(),
+            ..client_state()
+        };
+        assert_eq!(
+            validate(&ahead, None),
+            Err(SelfClientError::HeightFromFuture)
+        );
+        let other_revision = SelfClientState {
+            latest_height: Height::new(1, 90),
+            ..client_state()
+        };
+        assert_eq!(
+            validate(&other_revision, None),
+            Err(SelfClientError::RevisionMismatch)
+        );
+        let other_set = SelfClientState {
+            authority_set_id: 1,
+            ..client_state()
+        };
+        assert_eq!(
+            validate(&other_set, Some(&recorded)),
+            Err(SelfClientError::AuthoritySetMismatch)
+        );
+        let future_set = SelfClientState {
+            authority_set_id: 4,
+            ..client_state()
+        };
+        assert_eq!(
+            validate(&future_set, None),
+            Err(SelfClientError::AuthoritySetMismatch)
+        );
+    }
+
+    #[test]
+    fn validates_consensus_state() {
+        let recorded = consensus_state();
+        let latest_height = client_state().latest_height;
+        let validate = |state: &SelfConsensusState, height: Height| {
+            let recorded = Some(&recorded).filter(|_| height.revision_height > 50);
+            validate_consensus_state(state, height, latest_height, own_height(), recorded)
+        };
+        assert_eq!(validate(&consensus_state(), Height::new(0, 90)), Ok(()));
+        assert_eq!(
+            validate(&consensus_state(), Height::new(0, 95)),
+            Err(SelfClientError::HeightAboveLatest)
+        );
+        assert_eq!(
+            validate(&consensus_state(), Height::new(0, 40)),
+            Err(SelfClientError::ConsensusStateNotFound)
+        );
+        assert_eq!(
+            validate(&consensus_state(), Height::new(0, 100)),
+            Err(SelfClientError::HeightFromFuture)
+        );
+
+        let mut forged = consensus_state();
+        forged.block_hash[0] = 0;
+        assert_eq!(
+            validate(&forged, Height::new(0, 90)),
+            Err(SelfClientError::ConsensusStateMismatch)
+        );
+        let late = SelfConsensusState {
+            timestamp: 1_600_000_006_000,
+            ..consensus_state()
+        };
+        assert_eq!(
+            validate(&late, Height::new(0, 90)),
+            Err(SelfClientError::ConsensusStateMismatch)
+        );
+    }
+}
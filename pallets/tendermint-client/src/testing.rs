@@ -0,0 +1,812 @@
+//! Deterministic generator of Tendermint headers and commits, for tests and benchmarks.
+//!
+//! A [`TestChain`] is a chain of ed25519 validators whose keys are derived from their seeds, so the
+//! same chain always produces the same headers and signatures. Headers use the Tendermint 0.34
+//! encoding, and are signed by every validator unless their [`Vote`]s are given, which allows
+//! generating commits with absent or byzantine signers.
+//!
+//! A [`TestStore`] simulates the IBC store of a Cosmos SDK chain, whose root a `TestChain` commits
+//! to as its app hash, and proves its entries the way the counterparty of an IBC handshake does.
+//!
+//! ```ignore
+//! let mut chain = TestChain::new("testchain-1", validators(4, 10));
+//! let create = chain.create_payload(b"client".to_vec(), 86400);
+//! chain.advance(1, 5);
+//! chain.change_validators(validators(5, 10));
+//! let update = chain.update_payload(b"client".to_vec());
+//! ```
+//!
+//! Only compiled for tests, or with the `test-utils` feature.
+
+use alloc::string::String;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use ed25519_dalek::{Keypair, PublicKey as DalekPublicKey, SecretKey, Signer};
+use prost::Message;
+use sha2::{Digest, Sha256};
+use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
+
+use crate::channel::{channel_path, ChannelEnd};
+use crate::commitment::length_prefixed;
+use crate::connection::{connection_path, ConnectionEnd};
+use crate::convert::proto_time;
+
+use crate::light_client::encoding;
+use crate::light_client::types::{PartSetHeader, Version};
+use crate::light_client::{
+    BlockId, BlockIdFlag, Commit, CommitSig, Encoding, Header, PublicKey, SignedHeader, Validator,
+    ValidatorSet,
+};
+use crate::proto::{
+    self, commitment_proof, ExistenceProof, HashOp, InnerOp, LeafOp, LengthOp, NonExistenceProof,
+};
+use crate::types::{Height, TMCreateClientPayload, TMHeader, TMUpdateClientPayload};
+
+/// Time of the first header of a chain, in seconds since the unix epoch.
+pub const GENESIS_TIME: i64 = 1_600_000_000;
+
+/// Validator of a test chain, whose key is derived from its seed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestValidator {
+    pub seed: u32,
+    pub voting_power: u64,
+}
+
+impl TestValidator {
+    pub fn new(seed: u32, voting_power: u64) -> Self {
+        TestValidator { seed, voting_power }
+    }
+
+    pub fn keypair(&self) -> Keypair {
+        let mut bytes = [0u8; 32];
+        bytes[..4].copy_from_slice(&self.seed.to_le_bytes());
+        let secret = SecretKey::from_bytes(&bytes).expect("seed has the length of a secret key");
+        let public = DalekPublicKey::from(&secret);
+        Keypair { secret, public }
+    }
+
+    pub fn validator(&self) -> Validator {
+        let pub_key = PublicKey::Ed25519(self.keypair().public.to_bytes().to_vec());
+        Validator {
+            address: pub_key.address().expect("ed25519 keys are supported"),
+            pub_key,
+            voting_power: self.voting_power,
+            proposer_priority: 0,
+        }
+    }
+}
+
+/// `count` validators of seeds `0..count`, each with `voting_power`.
+pub fn validators(count: u32, voting_power: u64) -> Vec<TestValidator> {
+    (0..count)
+        .map(|seed| TestValidator::new(seed, voting_power))
+        .collect()
+}
+
+/// Validator set of `validators`, in the given order, proposed by the first one.
+pub fn validator_set(validators: &[TestValidator]) -> ValidatorSet {
+    let validators: Vec<Validator> = validators.iter().map(TestValidator::validator).collect();
+    ValidatorSet {
+        proposer: validators.first().cloned(),
+        validators,
+    }
+}
+
+/// How a validator takes part in the commit of a header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Vote {
+    /// Signs the committed block.
+    Commit,
+    /// Does not sign.
+    Absent,
+    /// Votes for no block, which does not count towards the commit.
+    Nil,
+    /// Claims to commit the block with a signature of another block at the same height.
+    ConflictingBlock,
+    /// Claims to commit the block with a signature that does not verify.
+    CorruptSignature,
+}
+
+/// Chain of generated headers. Each header is signed by the current validator set, and commits
+/// to the next one.
+#[derive(Clone, Debug)]
+pub struct TestChain {
+    pub chain_id: String,
+    pub height: u64,
+    pub time: DateTime<Utc>,
+    pub validators: Vec<TestValidator>,
+    pub next_validators: Vec<TestValidator>,
+    /// App hash of the current header, e.g. the root of a `TestStore`.
+    pub app_hash: Vec<u8>,
+    /// Block id the current header links to as its parent's. Empty unless set.
+    pub last_block_id: BlockId,
+}
+
+impl TestChain {
+    /// Chain at height 1 and `GENESIS_TIME`, whose validators do not change.
+    pub fn new(chain_id: &str, validators: Vec<TestValidator>) -> Self {
+        TestChain {
+            chain_id: chain_id.into(),
+            height: 1,
+            time: Utc.timestamp(GENESIS_TIME, 0),
+            next_validators: validators.clone(),
+            validators,
+            app_hash: Vec::new(),
+            last_block_id: BlockId::default(),
+        }
+    }
+
+    /// Moves the chain `blocks` blocks and `secs` seconds forward, handing over to the next
+    /// validator set.
+    pub fn advance(&mut self, blocks: u64, secs: i64) {
+        self.height += blocks;
+        self.time = self.time + Duration::seconds(secs);
+        self.validators = self.next_validators.clone();
+    }
+
+    /// Makes `next_validators` the validator set of the block after the current one.
+    pub fn change_validators(&mut self, next_validators: Vec<TestValidator>) {
+        self.next_validators = next_validators;
+    }
+
+    /// Height of the current header, as tracked by a client of the chain.
+    pub fn ibc_height(&self) -> Height {
+        Height::from_chain_id(self.chain_id.as_bytes(), self.height)
+    }
+
+    /// Current header, signed by all validators.
+    pub fn header(&self) -> TMHeader {
+        self.header_with_votes(&[])
+    }
+
+    /// Current header, with the vote of each validator given in `votes` in validator set order.
+    /// Validators beyond `votes` sign the header.
+    pub fn header_with_votes(&self, votes: &[Vote]) -> TMHeader {
+        let validator_set = validator_set(&self.validators);
+        let header = Header {
+            version: Version {
+                block: encoding::BLOCK_VERSION_PROTOBUF,
+                app: 0,
+            },
+            chain_id: self.chain_id.clone(),
+            height: self.height,
+            time: self.time,
+            last_block_id: self.last_block_id.clone(),
+            last_commit_hash: Vec::new(),
+            data_hash: Vec::new(),
+            validators_hash: encoding::validator_set_hash(&validator_set, Encoding::Protobuf),
+            next_validators_hash: encoding::validator_set_hash(
+                &self::validator_set(&self.next_validators),
+                Encoding::Protobuf,
+            ),
+            consensus_hash: Vec::new(),
+            app_hash: self.app_hash.clone(),
+            last_results_hash: Vec::new(),
+            evidence_hash: Vec::new(),
+            proposer_address: validator_set
+                .validators
+                .first()
+                .map(|v| v.address.clone())
+                .unwrap_or_default(),
+        };
+        let block_id = BlockId {
+            hash: encoding::header_hash(&header, Encoding::Protobuf),
+            parts: PartSetHeader::default(),
+        };
+        let mut commit = Commit {
+            height: self.height,
+            round: 0,
+            block_id,
+            signatures: Vec::new(),
+        };
+
+        let mut signatures = Vec::with_capacity(self.validators.len());
+        for (i, (signer, validator)) in self
+            .validators
+            .iter()
+            .zip(validator_set.validators.iter())
+            .enumerate()
+        {
+            let vote = votes.get(i).copied().unwrap_or(Vote::Commit);
+            signatures.push(self.sign(&commit, signer, validator, vote));
+        }
+        commit.signatures = signatures;
+
+        TMHeader {
+            signed_header: SignedHeader { header, commit },
+            validator_set,
+        }
+    }
+
+    fn sign(
+        &self,
+        commit: &Commit,
+        signer: &TestValidator,
+        validator: &Validator,
+        vote: Vote,
+    ) -> CommitSig {
+        let mut sig = CommitSig {
+            block_id_flag: BlockIdFlag::Commit,
+            validator_address: validator.address.clone(),
+            timestamp: self.time,
+            signature: Vec::new(),
+        };
+        let signed = match vote {
+            Vote::Absent => {
+                sig.block_id_flag = BlockIdFlag::Absent;
+                sig.validator_address = Vec::new();
+                return sig;
+            }
+            Vote::Nil => {
+                sig.block_id_flag = BlockIdFlag::Nil;
+                Commit {
+                    block_id: BlockId::default(),
+                    ..commit.clone()
+                }
+            }
+            Vote::ConflictingBlock => {
+                let mut other = commit.clone();
+                other.block_id.hash[0] ^= 1;
+                other
+            }
+            Vote::Commit | Vote::CorruptSignature => commit.clone(),
+        };
+        let sign_bytes =
+            encoding::vote_sign_bytes(&self.chain_id, &signed, &sig, Encoding::Protobuf);
+        sig.signature = signer.keypair().sign(&sign_bytes).to_bytes().to_vec();
+        if vote == Vote::CorruptSignature {
+            sig.signature[0] ^= 1;
+        }
+        sig
+    }
+
+    /// Payload creating client `client_id` from the current header.
+    pub fn create_payload(
+        &self,
+        client_id: Vec<u8>,
+        trusting_period: u64,
+    ) -> TMCreateClientPayload {
+        TMCreateClientPayload {
+            header: self.header(),
+            trusting_period,
+            max_clock_drift: 30,
+            unbonding_period: trusting_period * 3 / 2,
+            client_id,
+            upgrade_path: Vec::new(),
+        }
+    }
+
+    /// Payload updating client `client_id` to the current header.
+    pub fn update_payload(&self, client_id: Vec<u8>) -> TMUpdateClientPayload {
+        TMUpdateClientPayload {
+            header: self.header(),
+            client_id,
+            next_validator_set: validator_set(&self.next_validators),
+        }
+    }
+}
+
+/// Protobuf encoded `Any` wrapping `message` as `type_url`.
+pub fn any<M: Message>(type_url: &str, message: &M) -> Vec<u8> {
+    proto::encode(&proto::Any::pack(type_url, message))
+}
+
+impl TestChain {
+    /// ibc-go client state of the chain at the current header.
+    pub fn proto_client_state(
+        &self,
+        trusting_period: u64,
+        upgrade_path: &[&str],
+    ) -> proto::ClientState {
+        let height = self.ibc_height();
+        let duration = |seconds: u64| proto::Duration {
+            seconds: seconds as i64,
+            nanos: 0,
+        };
+        proto::ClientState {
+            chain_id: self.chain_id.clone(),
+            trust_level: Some(proto::Fraction {
+                numerator: 1,
+                denominator: 3,
+            }),
+            trusting_period: Some(duration(trusting_period)),
+            unbonding_period: Some(duration(trusting_period * 3 / 2)),
+            max_clock_drift: Some(duration(30)),
+            frozen_height: None,
+            latest_height: Some(proto::Height {
+                revision_number: height.revision_number,
+                revision_height: height.revision_height,
+            }),
+            upgrade_path: upgrade_path.iter().map(|p| String::from(*p)).collect(),
+            allow_update_after_expiry: false,
+            allow_update_after_misbehaviour: false,
+        }
+    }
+
+    /// ibc-go consensus state of the current header.
+    pub fn proto_consensus_state(&self) -> proto::ConsensusState {
+        let header = self.header().signed_header.header;
+        proto::ConsensusState {
+            timestamp: Some(proto_time(header.time)),
+            root: Some(proto::MerkleRoot {
+                hash: header.app_hash,
+            }),
+            next_validators_hash: header.next_validators_hash,
+        }
+    }
+
+    /// ibc-go header of the current header, trusting the consensus state of the client at
+    /// `trusted_height`, whose next validators are `trusted_validators`.
+    pub fn proto_header(
+        &self,
+        trusted_height: Option<Height>,
+        trusted_validators: Option<&[TestValidator]>,
+    ) -> proto::Header {
+        let header = self.header();
+        proto::Header {
+            signed_header: Some(proto_signed_header(&header.signed_header)),
+            validator_set: Some(proto_validator_set(&header.validator_set)),
+            trusted_height: trusted_height.map(|height| proto::Height {
+                revision_number: height.revision_number,
+                revision_height: height.revision_height,
+            }),
+            trusted_validators: trusted_validators
+                .map(|validators| proto_validator_set(&validator_set(validators))),
+        }
+    }
+}
+
+fn proto_block_id(block_id: &BlockId) -> proto::BlockId {
+    proto::BlockId {
+        hash: block_id.hash.clone(),
+        part_set_header: Some(proto::PartSetHeader {
+            total: block_id.parts.total,
+            hash: block_id.parts.hash.clone(),
+        }),
+    }
+}
+
+fn proto_signed_header(signed_header: &SignedHeader) -> proto::SignedHeader {
+    let header = &signed_header.header;
+    let commit = &signed_header.commit;
+    proto::SignedHeader {
+        header: Some(proto::TmHeader {
+            version: Some(proto::Consensus {
+                block: header.version.block,
+                app: header.version.app,
+            }),
+            chain_id: header.chain_id.clone(),
+            height: header.height as i64,
+            time: Some(proto_time(header.time)),
+            last_block_id: Some(proto_block_id(&header.last_block_id)),
+            last_commit_hash: header.last_commit_hash.clone(),
+            data_hash: header.data_hash.clone(),
+            validators_hash: header.validators_hash.clone(),
+            next_validators_hash: header.next_validators_hash.clone(),
+            consensus_hash: header.consensus_hash.clone(),
+            app_hash: header.app_hash.clone(),
+            last_results_hash: header.last_results_hash.clone(),
+            evidence_hash: header.evidence_hash.clone(),
+            proposer_address: header.proposer_address.clone(),
+        }),
+        commit: Some(proto::Commit {
+            height: commit.height as i64,
+            round: commit.round as i32,
+            block_id: Some(proto_block_id(&commit.block_id)),
+            signatures: commit
+                .signatures
+                .iter()
+                .map(|sig| proto::CommitSig {
+                    block_id_flag: u8::from(sig.block_id_flag).into(),
+                    validator_address: sig.validator_address.clone(),
+                    timestamp: Some(proto_time(sig.timestamp)),
+                    signature: sig.signature.clone(),
+                })
+                .collect(),
+        }),
+    }
+}
+
+fn proto_validator(validator: &Validator) -> proto::Validator {
+    let sum = match &validator.pub_key {
+        PublicKey::Ed25519(key) => Some(proto::public_key::Sum::Ed25519(key.clone())),
+        PublicKey::Secp256k1(key) => Some(proto::public_key::Sum::Secp256k1(key.clone())),
+        PublicKey::Unsupported { .. } => None,
+    };
+    proto::Validator {
+        address: validator.address.clone(),
+        pub_key: Some(proto::PublicKey { sum }),
+        voting_power: validator.voting_power as i64,
+        proposer_priority: validator.proposer_priority,
+    }
+}
+
+fn proto_validator_set(validator_set: &ValidatorSet) -> proto::ValidatorSet {
+    proto::ValidatorSet {
+        validators: validator_set
+            .validators
+            .iter()
+            .map(proto_validator)
+            .collect(),
+        proposer: validator_set.proposer.as_ref().map(proto_validator),
+        total_voting_power: validator_set
+            .validators
+            .iter()
+            .map(|v| v.voting_power as i64)
+            .sum(),
+    }
+}
+
+/// Name of the store a `TestStore` keeps its entries in, besides which the multistore holds a bank
+/// store.
+pub const TEST_STORE_NAME: &[u8] = b"ibc";
+
+/// Entries of the IBC store of a simulated Cosmos SDK chain. Entries are proven with ICS-23 proofs
+/// of the shape `SDK_SPECS` accept: an IAVL tree of the entries, below the simple merkle tree of the
+/// multistore's store roots.
+#[derive(Clone, Debug, Default)]
+pub struct TestStore {
+    entries: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl TestStore {
+    pub fn new() -> Self {
+        TestStore::default()
+    }
+
+    pub fn set(&mut self, path: Vec<u8>, value: Vec<u8>) {
+        self.entries.insert(path, value);
+    }
+
+    /// Sets connection `connection_id` to `end`, protobuf encoded as ibc-go stores it.
+    pub fn set_connection(&mut self, connection_id: &[u8], end: &ConnectionEnd) {
+        self.set(
+            connection_path(connection_id),
+            proto::encode(&end.to_proto()),
+        );
+    }
+
+    /// Sets the channel of `port_id` and `channel_id` to `end`, protobuf encoded as ibc-go stores it.
+    pub fn set_channel(&mut self, port_id: &[u8], channel_id: &[u8], end: &ChannelEnd) {
+        self.set(
+            channel_path(port_id, channel_id),
+            proto::encode(&end.to_proto()),
+        );
+    }
+
+    /// App hash committing to the entries.
+    pub fn root(&self) -> Vec<u8> {
+        let (store_root, _) = self.iavl_root(None);
+        multistore_proof(store_root).0
+    }
+
+    /// Protobuf encoded `MerkleProof` of the entry at `path`.
+    pub fn prove(&self, path: &[u8]) -> Vec<u8> {
+        let index = self
+            .entries
+            .keys()
+            .position(|key| &key[..] == path)
+            .expect("proven entries are set");
+        let (store_root, iavl_proof) = self.iavl_root(Some(index));
+        let iavl_proof = proto::CommitmentProof {
+            proof: Some(commitment_proof::Proof::Exist(iavl_proof.unwrap())),
+        };
+        let (_, store_proof) = multistore_proof(store_root);
+        proto::encode(&proto::MerkleProof {
+            proofs: vec![iavl_proof, store_proof],
+        })
+    }
+
+    /// Protobuf encoded `MerkleProof` that nothing is stored at `path`.
+    pub fn prove_absence(&self, path: &[u8]) -> Vec<u8> {
+        assert!(
+            !self.entries.contains_key(path),
+            "proven absent entries are not set"
+        );
+        let index = self.entries.keys().filter(|key| &key[..] < path).count();
+        let (store_root, left) = self.iavl_root(index.checked_sub(1));
+        let (_, right) = self.iavl_root(Some(index).filter(|i| *i < self.entries.len()));
+        let iavl_proof = proto::CommitmentProof {
+            proof: Some(commitment_proof::Proof::Nonexist(NonExistenceProof {
+                key: path.to_vec(),
+                left,
+                right,
+            })),
+        };
+        let (_, store_proof) = multistore_proof(store_root);
+        proto::encode(&proto::MerkleProof {
+            proofs: vec![iavl_proof, store_proof],
+        })
+    }
+
+    /// Root of the IAVL tree of the entries, and the proof of the entry at `index`.
+    fn iavl_root(&self, index: Option<usize>) -> (Vec<u8>, Option<ExistenceProof>) {
+        let entries: Vec<(&Vec<u8>, &Vec<u8>)> = self.entries.iter().collect();
+        if entries.is_empty() {
+            return (Vec::new(), None);
+        }
+        let mut path = Vec::new();
+        let (root, _) = iavl_node(&entries, index, &mut path);
+        let proof = index.map(|index| {
+            let (key, value) = entries[index];
+            existence_proof(key, value, iavl_leaf_prefix(), path)
+        });
+        (root, proof)
+    }
+}
+
+/// Zigzag varint, as IAVL node prefixes are made of.
+fn varint(value: i64) -> Vec<u8> {
+    let mut zigzag = ((value << 1) ^ (value >> 63)) as u64;
+    let mut out = Vec::new();
+    while zigzag >= 0x80 {
+        out.push((zigzag as u8) | 0x80);
+        zigzag >>= 7;
+    }
+    out.push(zigzag as u8);
+    out
+}
+
+/// Prefix of IAVL leaves: height 0, size 1 and version 1.
+fn iavl_leaf_prefix() -> Vec<u8> {
+    [varint(0), varint(1), varint(1)].concat()
+}
+
+fn leaf_hash(prefix: &[u8], key: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.input(prefix);
+    hasher.input(length_prefixed(key));
+    hasher.input(length_prefixed(&Sha256::digest(value)));
+    hasher.result().to_vec()
+}
+
+fn existence_proof(
+    key: &[u8],
+    value: &[u8],
+    leaf_prefix: Vec<u8>,
+    path: Vec<InnerOp>,
+) -> ExistenceProof {
+    ExistenceProof {
+        key: key.to_vec(),
+        value: value.to_vec(),
+        leaf: Some(LeafOp {
+            hash: HashOp::Sha256 as i32,
+            prehash_key: HashOp::NoHash as i32,
+            prehash_value: HashOp::Sha256 as i32,
+            length: LengthOp::VarProto as i32,
+            prefix: leaf_prefix,
+        }),
+        path,
+    }
+}
+
+/// Hash and height of the IAVL node of `entries`. The inner operations from the entry at `index` up
+/// to the node are appended to `path`.
+fn iavl_node(
+    entries: &[(&Vec<u8>, &Vec<u8>)],
+    index: Option<usize>,
+    path: &mut Vec<InnerOp>,
+) -> (Vec<u8>, i64) {
+    if let [(key, value)] = entries {
+        return (leaf_hash(&iavl_leaf_prefix(), key, value), 0);
+    }
+    let mid = entries.len() / 2;
+    let (left, left_height) = iavl_node(&entries[..mid], index.filter(|i| *i < mid), path);
+    let (right, right_height) = iavl_node(
+        &entries[mid..],
+        index.filter(|i| *i >= mid).map(|i| i - mid),
+        path,
+    );
+    let height = left_height.max(right_height) + 1;
+    let prefix = [varint(height), varint(entries.len() as i64), varint(1)].concat();
+
+    let hash = Sha256::digest(
+        &[
+            &prefix[..],
+            &length_prefixed(&left),
+            &length_prefixed(&right),
+        ]
+        .concat(),
+    )
+    .to_vec();
+    match index {
+        Some(i) if i < mid => path.push(InnerOp {
+            hash: HashOp::Sha256 as i32,
+            prefix: [&prefix[..], &[32]].concat(),
+            suffix: length_prefixed(&right),
+        }),
+        Some(_) => path.push(InnerOp {
+            hash: HashOp::Sha256 as i32,
+            prefix: [&prefix[..], &length_prefixed(&left), &[32]].concat(),
+            suffix: Vec::new(),
+        }),
+        None => {}
+    }
+    (hash, height)
+}
+
+/// App hash of a multistore of a bank store and the IBC store of root `store_root`, and the proof of
+/// the IBC store's root.
+fn multistore_proof(store_root: Vec<u8>) -> (Vec<u8>, proto::CommitmentProof) {
+    let bank = leaf_hash(&[0], b"bank", &Sha256::digest(b"bank"));
+    let ibc = leaf_hash(&[0], TEST_STORE_NAME, &store_root);
+    let root = Sha256::digest(&[&[1][..], &bank, &ibc].concat()).to_vec();
+    let path = vec![InnerOp {
+        hash: HashOp::Sha256 as i32,
+        prefix: [&[1][..], &bank].concat(),
+        suffix: Vec::new(),
+    }];
+    let proof = existence_proof(TEST_STORE_NAME, &store_root, vec![0], path);
+    (
+        root,
+        proto::CommitmentProof {
+            proof: Some(commitment_proof::Proof::Exist(proof)),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commitment::{verify_membership, verify_non_membership, CommitmentError, SDK_SPECS};
+    use crate::light_client::VerificationError;
+    use crate::types::TendermintClient;
+    use crate::validation::{client_from_payload, initialise_client, verify_update};
+
+    const CLIENT_ID: &[u8] = b"testclient";
+
+    fn created_client(chain: &TestChain) -> TendermintClient {
+        let payload = chain.create_payload(CLIENT_ID.to_vec(), 86400);
+        let (mut client, header) = client_from_payload(payload);
+        initialise_client(&mut client, header, chain.time).unwrap();
+        client
+    }
+
+    /// Verifies `header` against the state of `client` at `trusted`, and moves the client to it.
+    fn update(
+        client: &mut TendermintClient,
+        trusted: Height,
+        header: &TMHeader,
+        next_validator_set: &ValidatorSet,
+    ) -> Result<Height, VerificationError> {
+        let now = header.signed_header.header.time + Duration::seconds(1);
+        let (height, trusted_state) = verify_update(
+            client,
+            &client.state.as_ref().unwrap().state,
+            trusted,
+            header,
+            Some(next_validator_set),
+            None,
+            now,
+        )?;
+        client.state.as_mut().unwrap().state = trusted_state;
+        Ok(height)
+    }
+
+    #[test]
+    fn generates_deterministic_headers() {
+        let chain = TestChain::new("testchain-1", validators(4, 10));
+        assert_eq!(
+            chain.header().signed_header,
+            chain.clone().header().signed_header
+        );
+        assert_eq!(chain.ibc_height(), Height::new(1, 1));
+    }
+
+    #[test]
+    fn converts_headers_to_protobuf() {
+        let chain = TestChain::new("testchain-1", validators(4, 10));
+        let header = crate::convert::tm_header(&chain.proto_header(None, None)).unwrap();
+        assert_eq!(header.signed_header, chain.header().signed_header);
+        assert_eq!(header.validator_set, validator_set(&chain.validators));
+    }
+
+    #[test]
+    fn updates_across_validator_set_changes() {
+        let mut chain = TestChain::new("testchain-1", validators(4, 10));
+        let mut client = created_client(&chain);
+        let mut stale = client.clone();
+
+        // The header at height 2 commits to the validators taking over at height 3
+        chain.advance(1, 5);
+        chain.change_validators(vec![TestValidator::new(7, 30), TestValidator::new(1, 5)]);
+        let payload = chain.update_payload(CLIENT_ID.to_vec());
+        let next = &payload.next_validator_set;
+        assert_eq!(
+            update(&mut client, Height::new(1, 1), &payload.header, next),
+            Ok(Height::new(1, 2))
+        );
+
+        chain.advance(1, 5);
+        let payload = chain.update_payload(CLIENT_ID.to_vec());
+        let next = &payload.next_validator_set;
+        assert_eq!(
+            update(&mut client, Height::new(1, 2), &payload.header, next),
+            Ok(Height::new(1, 3))
+        );
+
+        // Only a quarter of the initial voting power signs for the new validators
+        assert_eq!(
+            update(&mut stale, Height::new(1, 1), &payload.header, next),
+            Err(VerificationError::InsufficientVotingPower)
+        );
+    }
+
+    #[test]
+    fn tallies_votes_of_signers() {
+        let mut chain = TestChain::new("testchain-1", validators(4, 10));
+        let client = created_client(&chain);
+        chain.advance(1, 5);
+        let next = validator_set(&chain.next_validators);
+        let trusted = Height::new(1, 1);
+
+        let header = chain.header_with_votes(&[Vote::Absent]);
+        assert_eq!(
+            update(&mut client.clone(), trusted, &header, &next),
+            Ok(Height::new(1, 2))
+        );
+
+        let header = chain.header_with_votes(&[Vote::Absent, Vote::Nil]);
+        assert_eq!(
+            update(&mut client.clone(), trusted, &header, &next),
+            Err(VerificationError::InsufficientVotingPower)
+        );
+
+        for vote in [Vote::ConflictingBlock, Vote::CorruptSignature].iter() {
+            let header = chain.header_with_votes(&[Vote::Commit, *vote]);
+            assert_eq!(
+                update(&mut client.clone(), trusted, &header, &next),
+                Err(VerificationError::InvalidSignature)
+            );
+        }
+    }
+
+    #[test]
+    fn proves_store_entries() {
+        let mut store = TestStore::new();
+        for i in 0..5u8 {
+            store.set(vec![b'k', i], vec![i; 3]);
+        }
+        let root = store.root();
+
+        for i in 0..5u8 {
+            let proof: proto::MerkleProof =
+                prost::Message::decode(&store.prove(&[b'k', i])[..]).unwrap();
+            let path = [TEST_STORE_NAME.to_vec(), vec![b'k', i]];
+            assert_eq!(
+                verify_membership(&SDK_SPECS, &root, &proof, &path, &[i; 3]),
+                Ok(())
+            );
+            assert!(verify_membership(&SDK_SPECS, &root, &proof, &path, &[9; 3]).is_err());
+        }
+
+        for key in [&b"a"[..], b"k\x01\x00", b"k\x04\x00", b"z"].iter() {
+            let proof: proto::MerkleProof =
+                prost::Message::decode(&store.prove_absence(key)[..]).unwrap();
+            let path = [TEST_STORE_NAME.to_vec(), key.to_vec()];
+            assert_eq!(
+                verify_non_membership(&SDK_SPECS, &root, &proof, &path),
+                Ok(())
+            );
+            let other = [TEST_STORE_NAME.to_vec(), b"k\x02".to_vec()];
+            assert!(verify_non_membership(&SDK_SPECS, &root, &proof, &other).is_err());
+        }
+
+        // Neighbours that are not adjacent leave room for the key
+        let decode = |key: &[u8]| -> proto::MerkleProof {
+            prost::Message::decode(&store.prove_absence(key)[..]).unwrap()
+        };
+        let mut proof = decode(b"k\x01\x00");
+        let far = match decode(b"k\x03\x00").proofs[0].proof.clone() {
+            Some(commitment_proof::Proof::Nonexist(far)) => far.right,
+            _ => unreachable!(),
+        };
+        if let Some(commitment_proof::Proof::Nonexist(p)) = &mut proof.proofs[0].proof {
+            p.right = far;
+        }
+        let path = [TEST_STORE_NAME.to_vec(), b"k\x01\x00".to_vec()];
+        assert_eq!(
+            verify_non_membership(&SDK_SPECS, &root, &proof, &path),
+            Err(CommitmentError::InvalidNeighbours)
+        );
+    }
+}
@@ -1,26 +1,2214 @@
-// Tests to be written here
+use crate::channel::{
+    acknowledgement_commitment, channel_path, next_sequence_recv_path, packet_acknowledgement_path,
+    packet_commitment_path, packet_receipt_path, AcknowledgePacketPayload, ChanOpenAckPayload,
+    ChanOpenConfirmPayload, ChanOpenInitPayload, ChanOpenTryPayload, ChannelCounterparty,
+    ChannelEnd, ChannelState, Order, Packet, RecvPacketPayload, TimeoutPacketPayload,
+};
+use crate::connection::{
+    client_state_path, connection_path, consensus_state_path, ConnOpenAckPayload,
+    ConnOpenConfirmPayload, ConnOpenInitPayload, ConnOpenTryPayload, ConnectionEnd,
+    ConnectionState, Counterparty, Version,
+};
+use crate::light_client::encoding::{self, Encoding};
+use crate::light_client::{BlockId, ValidatorSet};
+use crate::proto;
+use crate::self_client::{SelfClientError, SelfClientState, SelfConsensusState};
+use crate::testing::{any, validators, TestChain, TestStore, TestValidator, Vote, GENESIS_TIME};
+use crate::types::{
+    ConsensusStateWrapper, ExportedClient, Height, RetentionPolicy, TMBackfillPayload,
+    TMClientStorageWrapper, TMCreateClientPayload, TMUpdateClientPayload, TMUpgradeClientPayload,
+};
+use crate::weights::WeightInfo;
+use crate::{
+    mock::*, Channels, ClientConnections, ClientInfoMap, Connections, ConsensusHeights,
+    ConsensusStates, Error, PacketAcknowledgements, PacketCommitments, PacketReceipts, RawEvent,
+    TMClientStorage,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use codec::{Decode, Encode};
+use frame_support::{
+    assert_noop, assert_ok,
+    dispatch::{DispatchResult, DispatchResultWithPostInfo},
+    traits::{Get, OnInitialize},
+    StorageDoubleMap, StorageMap,
+};
+use prost::Message;
+use serde::Deserialize;
+use sp_core::H256;
+use sp_runtime::traits::BadOrigin;
 
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+const SIGNER: u64 = 1;
+const CREATE: &[u8] = include_bytes!("../../../test_create_v034.json");
+const UPDATE: &[u8] = include_bytes!("../../../test_update_v034.json");
+const CREATE_PROTO: &[u8] = include_bytes!("../../../test_create_proto.json");
+const UPDATE_PROTO: &[u8] = include_bytes!("../../../test_update_proto.json");
+const CHAIN_ID: &[u8] = b"testchain-2";
+/// Trusting period of the client of `CREATE`, in seconds.
+const TRUSTING_PERIOD: i64 = 1209600;
+
+fn client_id() -> Vec<u8> {
+    vec![245, 123, 214]
+}
+
+fn create_payload() -> TMCreateClientPayload {
+    serde_json::from_slice(CREATE).unwrap()
+}
+
+fn update_payload() -> TMUpdateClientPayload {
+    serde_json::from_slice(UPDATE).unwrap()
+}
+
+fn created_at() -> DateTime<Utc> {
+    create_payload().header.signed_header.header.time
+}
+
+fn updated_at() -> DateTime<Utc> {
+    update_payload().header.signed_header.header.time
+}
+
+/// Moves the block time `secs` seconds past `time`.
+fn set_time(time: DateTime<Utc>, secs: i64) {
+    Timestamp::set_timestamp((time.timestamp_millis() + secs * 1_000) as u64);
+}
+
+fn create_client() {
+    set_time(created_at(), 1);
+    assert_ok!(TemplateModule::init_client(
+        Origin::signed(SIGNER),
+        CREATE.to_vec()
+    ));
+}
+
+fn update_client() {
+    set_time(updated_at(), 1);
+    assert_ok!(TemplateModule::update_client(
+        Origin::signed(SIGNER),
+        UPDATE.to_vec()
+    ));
+}
+
+fn events() -> Vec<TestEvent> {
+    System::events().into_iter().map(|r| r.event).collect()
+}
+
+fn event(e: RawEvent<u64, Height>) -> TestEvent {
+    TestEvent::tendermint_client(e)
+}
+
+#[test]
+fn creates_client() {
+    new_test_ext().execute_with(|| {
+        create_client();
+
+        let height = Height::new(2, 100);
+        assert_eq!(TemplateModule::clients(), vec![client_id()]);
+        assert_eq!(TemplateModule::latest_height(&client_id()), Some(height));
+        let info = TemplateModule::client_info(client_id());
+        assert_eq!(info.chain_id, CHAIN_ID.to_vec());
+        assert_eq!(info.trusting_period, TRUSTING_PERIOD as u64);
+        assert_eq!(TemplateModule::consensus_heights(client_id()), vec![height]);
+        let state = TemplateModule::consensus_state(client_id(), height)
+            .unwrap()
+            .state;
+        assert_eq!(state.state.header().height, 100);
+
+        assert_eq!(
+            events(),
+            vec![event(RawEvent::ClientCreated(
+                SIGNER,
+                client_id(),
+                CHAIN_ID.to_vec(),
+                height
+            ))]
+        );
+    });
+}
+
+#[test]
+fn rejects_duplicate_client() {
+    new_test_ext().execute_with(|| {
+        create_client();
+        assert_noop!(
+            TemplateModule::init_client(Origin::signed(SIGNER), CREATE.to_vec()),
+            Error::<Test>::ClientAlreadyInitialized
+        );
+    });
+}
+
+#[test]
+fn rejects_malformed_payloads() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            TemplateModule::init_client(Origin::signed(SIGNER), b"{}".to_vec()),
+            Error::<Test>::DeserializeError
+        );
+        assert_noop!(
+            TemplateModule::update_client(Origin::signed(SIGNER), b"not json".to_vec()),
+            Error::<Test>::DeserializeError
+        );
+    });
+}
+
+/// ibc-go message of a `test_*_proto.json` fixture: base64 encoded protobuf `Any`s of the client
+/// state, consensus state and header of `protochain-1`.
+#[derive(Deserialize)]
+struct ProtoFixture {
+    client_id: String,
+    #[serde(default)]
+    client_state: String,
+    #[serde(default)]
+    consensus_state: String,
+    header: String,
+}
+
+impl ProtoFixture {
+    fn load(json: &[u8]) -> Self {
+        serde_json::from_slice(json).unwrap()
+    }
+
+    fn client_id(&self) -> Vec<u8> {
+        self.client_id.clone().into_bytes()
+    }
+
+    fn client_state(&self) -> Vec<u8> {
+        base64::decode(&self.client_state).unwrap()
+    }
+
+    fn consensus_state(&self) -> Vec<u8> {
+        base64::decode(&self.consensus_state).unwrap()
+    }
+
+    fn header(&self) -> Vec<u8> {
+        base64::decode(&self.header).unwrap()
+    }
+}
+
+fn init_client_proto(
+    fixture: &ProtoFixture,
+    client_state: Vec<u8>,
+    consensus_state: Vec<u8>,
+    header: Vec<u8>,
+) -> DispatchResultWithPostInfo {
+    TemplateModule::init_client_proto(
+        Origin::signed(SIGNER),
+        fixture.client_id(),
+        client_state,
+        consensus_state,
+        header,
+    )
+}
+
+#[test]
+fn creates_and_updates_client_from_protobuf() {
+    new_test_ext().execute_with(|| {
+        let create = ProtoFixture::load(CREATE_PROTO);
+        let client_id = create.client_id();
+        set_time(Utc.timestamp(GENESIS_TIME, 0), 1);
+        assert_ok!(init_client_proto(
+            &create,
+            create.client_state(),
+            create.consensus_state(),
+            create.header()
+        ));
+        assert_eq!(
+            TemplateModule::latest_height(&client_id),
+            Some(Height::new(1, 1))
+        );
+        let info = TemplateModule::client_info(&client_id);
+        assert_eq!(info.chain_id, b"protochain-1".to_vec());
+        assert_eq!(info.trusting_period, 86400);
+        assert_eq!(
+            TMClientStorage::get(&client_id).client.upgrade_path,
+            vec![b"upgrade".to_vec(), b"upgradedIBCState".to_vec()]
+        );
+
+        let update = ProtoFixture::load(UPDATE_PROTO);
+        set_time(Utc.timestamp(GENESIS_TIME, 0), 6);
+        assert_ok!(TemplateModule::update_client_proto(
+            Origin::signed(SIGNER),
+            update.client_id(),
+            update.header()
+        ));
+        assert_eq!(
+            TemplateModule::latest_height(&client_id),
+            Some(Height::new(1, 2))
+        );
+        assert_eq!(
+            events().last(),
+            Some(&event(RawEvent::ClientUpdated(
+                SIGNER,
+                client_id,
+                b"protochain-1".to_vec(),
+                Height::new(1, 2)
+            )))
+        );
+    });
+}
+
+#[test]
+fn rejects_malformed_protobuf() {
+    new_test_ext().execute_with(|| {
+        let create = ProtoFixture::load(CREATE_PROTO);
+        set_time(Utc.timestamp(GENESIS_TIME, 0), 1);
+        // Messages of another type than the argument's
+        assert_noop!(
+            init_client_proto(
+                &create,
+                create.consensus_state(),
+                create.client_state(),
+                create.header()
+            ),
+            Error::<Test>::DeserializeError
+        );
+        let mut header = create.header();
+        header.truncate(header.len() - 10);
+        assert_noop!(
+            init_client_proto(
+                &create,
+                create.client_state(),
+                create.consensus_state(),
+                header
+            ),
+            Error::<Test>::DeserializeError
+        );
+
+        // Block id flags out of the range of the enum are not reinterpreted
+        let mut header: proto::Header = proto::Any::decode(&create.header()[..])
+            .unwrap()
+            .unpack(proto::TENDERMINT_HEADER_TYPE_URL)
+            .unwrap();
+        let signatures = &mut header
+            .signed_header
+            .as_mut()
+            .unwrap()
+            .commit
+            .as_mut()
+            .unwrap()
+            .signatures;
+        signatures[0].block_id_flag = 258;
+        assert_noop!(
+            init_client_proto(
+                &create,
+                create.client_state(),
+                create.consensus_state(),
+                any(proto::TENDERMINT_HEADER_TYPE_URL, &header)
+            ),
+            Error::<Test>::DeserializeError
+        );
+
+        let mut client_state: proto::ClientState = proto::Any::decode(&create.client_state()[..])
+            .unwrap()
+            .unpack(proto::TENDERMINT_CLIENT_STATE_TYPE_URL)
+            .unwrap();
+        client_state.frozen_height = Some(proto::Height {
+            revision_number: 1,
+            revision_height: 1,
+        });
+        assert_noop!(
+            init_client_proto(
+                &create,
+                any(proto::TENDERMINT_CLIENT_STATE_TYPE_URL, &client_state),
+                create.consensus_state(),
+                create.header()
+            ),
+            Error::<Test>::ClientFrozen
+        );
+
+        assert_ok!(init_client_proto(
+            &create,
+            create.client_state(),
+            create.consensus_state(),
+            create.header()
+        ));
+        let update = ProtoFixture::load(UPDATE_PROTO);
+        set_time(Utc.timestamp(GENESIS_TIME, 0), 6);
+        let update_client_proto = |header: Vec<u8>| {
+            TemplateModule::update_client_proto(Origin::signed(SIGNER), update.client_id(), header)
+        };
+        assert_noop!(
+            update_client_proto(create.client_state()),
+            Error::<Test>::DeserializeError
+        );
+        let mut header = update.header();
+        header.truncate(header.len() / 2);
+        assert_noop!(update_client_proto(header), Error::<Test>::DeserializeError);
+        // The create header does not name the trusted height
+        assert_noop!(
+            update_client_proto(create.header()),
+            Error::<Test>::ValidationError
+        );
+    });
+}
+
+#[test]
+fn rejects_unsigned_origin() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            TemplateModule::init_client(Origin::root(), CREATE.to_vec()),
+            BadOrigin
+        );
+    });
+}
+
+#[test]
+fn updates_client() {
+    new_test_ext().execute_with(|| {
+        create_client();
+        update_client();
+
+        let height = Height::new(2, 150);
+        assert_eq!(TemplateModule::latest_height(&client_id()), Some(height));
+        assert_eq!(
+            TemplateModule::consensus_heights(client_id()),
+            vec![Height::new(2, 100), height]
+        );
+        assert_eq!(
+            TMClientStorage::get(client_id())
+                .client
+                .state
+                .unwrap()
+                .state
+                .header()
+                .height,
+            150
+        );
+        assert_eq!(
+            events().last(),
+            Some(&event(RawEvent::ClientUpdated(
+                SIGNER,
+                client_id(),
+                CHAIN_ID.to_vec(),
+                height
+            )))
+        );
+    });
+}
+
+#[test]
+fn charges_updates_by_validator_count() {
+    new_test_ext().execute_with(|| {
+        create_client();
+        set_time(updated_at(), 1);
+        let validators = update_payload().header.validator_set.validators.len() as u32;
+        let info = TemplateModule::update_client(Origin::signed(SIGNER), UPDATE.to_vec()).unwrap();
+        assert_eq!(
+            info.actual_weight,
+            Some(<() as WeightInfo>::update_client(validators))
+        );
+        assert!(
+            <() as WeightInfo>::update_client(validators)
+                < <() as WeightInfo>::update_client(crate::max_validators(UPDATE.len()))
+        );
+    });
+}
+
+#[test]
+fn rejects_update_of_unknown_client() {
+    new_test_ext().execute_with(|| {
+        set_time(updated_at(), 1);
+        assert_noop!(
+            TemplateModule::update_client(Origin::signed(SIGNER), UPDATE.to_vec()),
+            Error::<Test>::ItemNotFound
+        );
+    });
+}
+
+#[test]
+fn rejects_replayed_update() {
+    new_test_ext().execute_with(|| {
+        create_client();
+        update_client();
+        assert_noop!(
+            TemplateModule::update_client(Origin::signed(SIGNER), UPDATE.to_vec()),
+            Error::<Test>::NonIncreasingHeight
+        );
+    });
+}
+
+#[test]
+fn rejects_update_of_other_chain() {
+    new_test_ext().execute_with(|| {
+        create_client();
+        set_time(updated_at(), 1);
+        let mut payload = update_payload();
+        payload.header.signed_header.header.chain_id = "testchain-3".into();
+        assert_noop!(
+            TemplateModule::update_client(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap()
+            ),
+            Error::<Test>::ChainIdMismatch
+        );
+    });
+}
+
+#[test]
+fn rejects_update_with_invalid_signature() {
+    new_test_ext().execute_with(|| {
+        create_client();
+        set_time(updated_at(), 1);
+        let mut payload = update_payload();
+        payload.header.signed_header.commit.signatures[0].signature[0] ^= 1;
+        assert_noop!(
+            TemplateModule::update_client(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap()
+            ),
+            Error::<Test>::InvalidSignature
+        );
+    });
+}
+
+#[test]
+fn rejects_update_with_mismatching_validator_set() {
+    new_test_ext().execute_with(|| {
+        create_client();
+        set_time(updated_at(), 1);
+        let mut payload = update_payload();
+        payload.header.validator_set = ValidatorSet {
+            validators: payload.header.validator_set.validators[..1].to_vec(),
+            proposer: None,
+        };
+        assert_noop!(
+            TemplateModule::update_client(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap()
+            ),
+            Error::<Test>::ValidatorSetHashMismatch
+        );
+    });
+}
+
+#[test]
+fn rejects_header_from_future() {
+    new_test_ext().execute_with(|| {
+        create_client();
+        // The update header is five minutes after the trusted one, beyond the 10s clock drift
+        set_time(created_at(), 1);
+        assert_noop!(
+            TemplateModule::update_client(Origin::signed(SIGNER), UPDATE.to_vec()),
+            Error::<Test>::HeaderFromFuture
+        );
+    });
+}
+
+#[test]
+fn rejects_update_of_expired_client() {
+    new_test_ext().execute_with(|| {
+        create_client();
+        set_time(created_at(), TRUSTING_PERIOD);
+        assert_noop!(
+            TemplateModule::update_client(Origin::signed(SIGNER), UPDATE.to_vec()),
+            Error::<Test>::ClientExpired
+        );
+        // Within the trusting period the same update is accepted
+        set_time(created_at(), TRUSTING_PERIOD - 1);
+        assert_ok!(TemplateModule::update_client(
+            Origin::signed(SIGNER),
+            UPDATE.to_vec()
+        ));
+    });
+}
+
+#[test]
+fn follows_validator_set_changes() {
+    new_test_ext().execute_with(|| {
+        let client_id = b"generated".to_vec();
+        let mut chain = TestChain::new("testchain-1", validators(4, 10));
+        let submit = |chain: &TestChain, payload: &TMUpdateClientPayload| {
+            set_time(chain.time, 1);
+            TemplateModule::update_client(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(payload).unwrap(),
+            )
+        };
+
+        set_time(chain.time, 1);
+        let payload = chain.create_payload(client_id.clone(), 86400);
+        assert_ok!(TemplateModule::init_client(
+            Origin::signed(SIGNER),
+            serde_json::to_vec(&payload).unwrap()
+        ));
+
+        chain.advance(1, 5);
+        chain.change_validators(vec![TestValidator::new(4, 25), TestValidator::new(5, 25)]);
+        let payload = chain.update_payload(client_id.clone());
+        assert_ok!(submit(&chain, &payload));
+
+        // Without the new validators' signatures the header lacks voting power
+        chain.advance(1, 5);
+        let mut payload = chain.update_payload(client_id.clone());
+        payload.header = chain.header_with_votes(&[Vote::Absent]);
+        assert_noop!(
+            submit(&chain, &payload),
+            Error::<Test>::InsufficientVotingPower
+        );
+
+        let payload = chain.update_payload(client_id.clone());
+        assert_ok!(submit(&chain, &payload));
+        assert_eq!(
+            TemplateModule::latest_height(&client_id),
+            Some(chain.ibc_height())
+        );
+    });
+}
+
+#[test]
+fn sets_canonical_client() {
+    new_test_ext().execute_with(|| {
+        create_client();
+        assert_noop!(
+            TemplateModule::set_canonical_client(
+                Origin::signed(SIGNER),
+                CHAIN_ID.to_vec(),
+                client_id()
+            ),
+            BadOrigin
+        );
+        assert_noop!(
+            TemplateModule::set_canonical_client(
+                Origin::root(),
+                b"testchain-3".to_vec(),
+                client_id()
+            ),
+            Error::<Test>::ChainIdMismatch
+        );
+        assert_noop!(
+            TemplateModule::set_canonical_client(
+                Origin::root(),
+                CHAIN_ID.to_vec(),
+                b"unknown".to_vec()
+            ),
+            Error::<Test>::ItemNotFound
+        );
+
+        assert_ok!(TemplateModule::set_canonical_client(
+            Origin::root(),
+            CHAIN_ID.to_vec(),
+            client_id()
+        ));
+        assert_eq!(
+            TemplateModule::canonical_client(CHAIN_ID.to_vec()),
+            Some(client_id())
+        );
+        assert_eq!(
+            events().last(),
+            Some(&event(RawEvent::CanonicalClientSet(
+                CHAIN_ID.to_vec(),
+                client_id()
+            )))
+        );
+
+        assert_noop!(
+            TemplateModule::clear_canonical_client(Origin::signed(SIGNER), CHAIN_ID.to_vec()),
+            BadOrigin
+        );
+        assert_ok!(TemplateModule::clear_canonical_client(
+            Origin::root(),
+            CHAIN_ID.to_vec()
+        ));
+        assert_eq!(TemplateModule::canonical_client(CHAIN_ID.to_vec()), None);
+        assert_eq!(
+            events().last(),
+            Some(&event(RawEvent::CanonicalClientCleared(CHAIN_ID.to_vec())))
+        );
+        assert_noop!(
+            TemplateModule::clear_canonical_client(Origin::root(), CHAIN_ID.to_vec()),
+            Error::<Test>::ItemNotFound
+        );
+    });
+}
+
+const BACKFILL_CLIENT_ID: &[u8] = b"07-tendermint-4";
+
+/// Chain `backfillchain-1` at heights 1, 3, 4 and 5, the latter linking to the hash of the header at 4.
+fn backfill_chain() -> Vec<TestChain> {
+    let genesis = TestChain::new("backfillchain-1", validators(4, 10));
+    let at = |blocks, secs| {
+        let mut chain = genesis.clone();
+        chain.advance(blocks, secs);
+        chain
+    };
+    let (at3, at4) = (at(2, 10), at(3, 15));
+    let mut at5 = at(4, 20);
+    at5.last_block_id = BlockId {
+        hash: encoding::header_hash(&at4.header().signed_header.header, Encoding::Protobuf),
+        parts: Default::default(),
+    };
+    vec![genesis, at3, at4, at5]
+}
+
+/// Creates client `BACKFILL_CLIENT_ID` of `backfill_chain` at height 1 and updates it to 5.
+fn create_backfill_client(chains: &[TestChain]) {
+    let (genesis, latest) = (&chains[0], &chains[3]);
+    set_time(genesis.time, 1);
+    assert_ok!(TemplateModule::init_client(
+        Origin::signed(SIGNER),
+        serde_json::to_vec(&genesis.create_payload(BACKFILL_CLIENT_ID.to_vec(), 86400)).unwrap()
+    ));
+    set_time(latest.time, 1);
+    assert_ok!(TemplateModule::update_client(
+        Origin::signed(SIGNER),
+        serde_json::to_vec(&latest.update_payload(BACKFILL_CLIENT_ID.to_vec())).unwrap()
+    ));
+}
+
+fn backfill(chain: &TestChain) -> DispatchResultWithPostInfo {
+    let payload = TMBackfillPayload {
+        header: chain.header(),
+        client_id: BACKFILL_CLIENT_ID.to_vec(),
+        trusted_validators: None,
+    };
+    TemplateModule::backfill_consensus_state(
+        Origin::signed(SIGNER),
+        serde_json::to_vec(&payload).unwrap(),
+    )
+}
+
+#[test]
+fn backfills_consensus_states() {
+    new_test_ext().execute_with(|| {
+        let chains = backfill_chain();
+        create_backfill_client(&chains);
+        let (at3, at4) = (&chains[1], &chains[2]);
+
+        let info = backfill(at4).unwrap();
+        // Charged as an update for the 4 validators of the header
+        assert_eq!(
+            info.actual_weight,
+            Some(<() as WeightInfo>::update_client(4))
+        );
+        assert_eq!(
+            events().last(),
+            Some(&event(RawEvent::ConsensusStateBackfilled(
+                SIGNER,
+                BACKFILL_CLIENT_ID.to_vec(),
+                at4.ibc_height()
+            )))
+        );
+        assert_ok!(backfill(at3));
+        assert_eq!(
+            TemplateModule::consensus_heights(BACKFILL_CLIENT_ID.to_vec()),
+            vec![
+                Height::new(1, 1),
+                Height::new(1, 3),
+                Height::new(1, 4),
+                Height::new(1, 5)
+            ]
+        );
+        assert_eq!(
+            ConsensusStates::get(BACKFILL_CLIENT_ID, at3.ibc_height())
+                .unwrap()
+                .state
+                .state
+                .header(),
+            &at3.header().signed_header.header
+        );
+        // The latest state is left as is
+        assert_eq!(
+            TemplateModule::latest_height(BACKFILL_CLIENT_ID),
+            Some(Height::new(1, 5))
+        );
+    });
+}
+
+#[test]
+fn rejects_invalid_backfills() {
+    new_test_ext().execute_with(|| {
+        let chains = backfill_chain();
+        create_backfill_client(&chains);
+        let (at4, at5) = (&chains[2], &chains[3]);
+
+        assert_noop!(backfill(at5), Error::<Test>::ConsensusStateExists);
+
+        let mut above = at5.clone();
+        above.advance(1, 5);
+        assert_noop!(backfill(&above), Error::<Test>::BackfillOutOfRange);
+
+        // Valid header at 4, but not the one the header at 5 links to
+        let mut forked = at4.clone();
+        forked.time = forked.time + chrono::Duration::seconds(1);
+        assert_noop!(backfill(&forked), Error::<Test>::HashLinkMismatch);
+    });
+}
+
+const UPGRADE_CLIENT_ID: &[u8] = b"07-tendermint-9";
+const UPGRADE_PATH: &[&str] = &["ibc", "upgradedIBCState"];
+/// Keys the chain commits to the upgraded states under, at the height of the client.
+const UPGRADED_CLIENT_KEY: &[u8] = b"upgradedIBCState/1/upgradedClient";
+const UPGRADED_CONSENSUS_KEY: &[u8] = b"upgradedIBCState/1/upgradedConsState";
+
+/// Chain `upgradechain-1` is upgraded to.
+fn upgraded_chain() -> TestChain {
+    TestChain::new("upgradechain-2", validators(3, 10))
+}
+
+/// Store of `upgradechain-1`, committing to `client_state` and the consensus state of `upgraded`.
+fn upgrade_store(upgraded: &TestChain, client_state: &proto::ClientState) -> TestStore {
+    let mut store = TestStore::new();
+    store.set(
+        UPGRADED_CLIENT_KEY.to_vec(),
+        any(proto::TENDERMINT_CLIENT_STATE_TYPE_URL, client_state),
+    );
+    store.set(
+        UPGRADED_CONSENSUS_KEY.to_vec(),
+        any(
+            proto::TENDERMINT_CONSENSUS_STATE_TYPE_URL,
+            &upgraded.proto_consensus_state(),
+        ),
+    );
+    store
+}
+
+/// Creates client `UPGRADE_CLIENT_ID` of `upgradechain-1` from a header committing to `store`.
+fn create_upgradable_client(store: &TestStore) -> TestChain {
+    let mut chain = TestChain::new("upgradechain-1", validators(4, 10));
+    chain.app_hash = store.root();
+    set_time(chain.time, 1);
+    let mut payload = chain.create_payload(UPGRADE_CLIENT_ID.to_vec(), 86400);
+    payload.upgrade_path = UPGRADE_PATH.iter().map(|p| p.to_string()).collect();
+    assert_ok!(TemplateModule::init_client(
+        Origin::signed(SIGNER),
+        serde_json::to_vec(&payload).unwrap()
+    ));
+    chain
+}
+
+fn upgrade_payload(
+    upgraded: &TestChain,
+    client_state: &proto::ClientState,
+    store: &TestStore,
+) -> TMUpgradeClientPayload {
+    TMUpgradeClientPayload {
+        client_id: UPGRADE_CLIENT_ID.to_vec(),
+        upgraded_client_state: any(proto::TENDERMINT_CLIENT_STATE_TYPE_URL, client_state),
+        upgraded_consensus_state: any(
+            proto::TENDERMINT_CONSENSUS_STATE_TYPE_URL,
+            &upgraded.proto_consensus_state(),
+        ),
+        proof_upgrade_client: store.prove(UPGRADED_CLIENT_KEY),
+        proof_upgrade_consensus_state: store.prove(UPGRADED_CONSENSUS_KEY),
+        header: upgraded.header(),
+    }
+}
+
+fn submit_upgrade(payload: &TMUpgradeClientPayload) -> DispatchResultWithPostInfo {
+    TemplateModule::upgrade_client(Origin::signed(SIGNER), serde_json::to_vec(payload).unwrap())
+}
+
+#[test]
+fn upgrades_client() {
+    new_test_ext().execute_with(|| {
+        let upgraded = upgraded_chain();
+        let client_state = upgraded.proto_client_state(86400, UPGRADE_PATH);
+        let store = upgrade_store(&upgraded, &client_state);
+        create_upgradable_client(&store);
+        assert_ok!(TemplateModule::set_canonical_client(
+            Origin::root(),
+            b"upgradechain-1".to_vec(),
+            UPGRADE_CLIENT_ID.to_vec()
+        ));
+
+        let info = submit_upgrade(&upgrade_payload(&upgraded, &client_state, &store)).unwrap();
+        // Charged for the 3 validators of the upgraded chain
+        assert_eq!(
+            info.actual_weight,
+            Some(<() as WeightInfo>::upgrade_client(3))
+        );
+        let height = Height::new(2, 1);
+        assert_eq!(
+            TemplateModule::latest_height(UPGRADE_CLIENT_ID),
+            Some(height)
+        );
+        assert_eq!(
+            TemplateModule::client_info(UPGRADE_CLIENT_ID.to_vec()).chain_id,
+            b"upgradechain-2".to_vec()
+        );
+        assert!(TemplateModule::consensus_state(UPGRADE_CLIENT_ID.to_vec(), height).is_some());
+        // The client stays canonical for the upgraded chain
+        assert_eq!(
+            TemplateModule::canonical_client(b"upgradechain-2".to_vec()),
+            Some(UPGRADE_CLIENT_ID.to_vec())
+        );
+        assert_eq!(
+            TemplateModule::canonical_client(b"upgradechain-1".to_vec()),
+            None
+        );
+        assert_eq!(
+            events().last(),
+            Some(&event(RawEvent::ClientUpgraded(
+                SIGNER,
+                UPGRADE_CLIENT_ID.to_vec(),
+                b"upgradechain-2".to_vec(),
+                height
+            )))
+        );
+    });
+}
+
+#[test]
+fn rejects_invalid_upgrade_proofs() {
+    new_test_ext().execute_with(|| {
+        let upgraded = upgraded_chain();
+        let client_state = upgraded.proto_client_state(86400, UPGRADE_PATH);
+        let store = upgrade_store(&upgraded, &client_state);
+        create_upgradable_client(&store);
+
+        // Client state other than the committed one
+        let other = upgraded.proto_client_state(3600, UPGRADE_PATH);
+        assert_noop!(
+            submit_upgrade(&upgrade_payload(&upgraded, &other, &store)),
+            Error::<Test>::InvalidUpgradeProof
+        );
+
+        let mut payload = upgrade_payload(&upgraded, &client_state, &store);
+        payload.proof_upgrade_client = store.prove(UPGRADED_CONSENSUS_KEY);
+        assert_noop!(submit_upgrade(&payload), Error::<Test>::InvalidUpgradeProof);
+
+        let mut payload = upgrade_payload(&upgraded, &client_state, &store);
+        payload.proof_upgrade_consensus_state = b"not a proof".to_vec();
+        assert_noop!(submit_upgrade(&payload), Error::<Test>::DeserializeError);
+    });
+}
+
+#[test]
+fn rejects_upgrade_of_expired_client() {
+    new_test_ext().execute_with(|| {
+        let upgraded = upgraded_chain();
+        let client_state = upgraded.proto_client_state(86400, UPGRADE_PATH);
+        let store = upgrade_store(&upgraded, &client_state);
+        let chain = create_upgradable_client(&store);
+
+        set_time(chain.time, 86400 + 1);
+        assert_noop!(
+            submit_upgrade(&upgrade_payload(&upgraded, &client_state, &store)),
+            Error::<Test>::ClientExpired
+        );
+    });
+}
+
+#[test]
+fn rejects_non_increasing_upgrade() {
+    new_test_ext().execute_with(|| {
+        // Claims the height of the client on the old chain
+        let upgraded = TestChain::new("upgradechain-1", validators(3, 10));
+        let client_state = upgraded.proto_client_state(86400, UPGRADE_PATH);
+        let store = upgrade_store(&upgraded, &client_state);
+        create_upgradable_client(&store);
+
+        assert_noop!(
+            submit_upgrade(&upgrade_payload(&upgraded, &client_state, &store)),
+            Error::<Test>::InvalidUpgrade
+        );
+        assert_eq!(
+            TemplateModule::latest_height(UPGRADE_CLIENT_ID),
+            Some(Height::new(1, 1))
+        );
+    });
+}
+
+#[test]
+fn prunes_expired_consensus_states() {
+    new_test_ext().execute_with(|| {
+        create_client();
+        update_client();
+
+        // Only the state at height 100 is outside of the trusting period
+        set_time(created_at(), TRUSTING_PERIOD);
+        TemplateModule::on_initialize(2);
+
+        let (pruned, latest) = (Height::new(2, 100), Height::new(2, 150));
+        assert_eq!(TemplateModule::consensus_heights(client_id()), vec![latest]);
+        assert!(ConsensusStates::get(client_id(), pruned).is_none());
+        assert!(ConsensusStates::get(client_id(), latest).is_some());
+        assert_eq!(
+            events().last(),
+            Some(&event(RawEvent::ConsensusStatesPruned(
+                client_id(),
+                pruned,
+                pruned
+            )))
+        );
+
+        // The latest state is kept even once it expires
+        set_time(updated_at(), TRUSTING_PERIOD);
+        TemplateModule::on_initialize(3);
+        assert_eq!(TemplateModule::consensus_heights(client_id()), vec![latest]);
+    });
+}
+
+#[test]
+fn reports_retention_policy() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TemplateModule::retention_policy(&client_id()), None);
+        create_client();
+        assert_eq!(
+            TemplateModule::retention_policy(&client_id()),
+            Some(RetentionPolicy {
+                trusting_period: TRUSTING_PERIOD as u64,
+                max_consensus_states: 3,
+                pruning_budget: 10,
+            })
+        );
+    });
+}
+
+#[test]
+fn creates_genesis_clients() {
+    let mut storage = frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    crate::GenesisConfig {
+        clients: vec![create_payload()],
+        imported_clients: Vec::new(),
+    }
+    .assimilate_storage::<Test>(&mut storage)
+    .unwrap();
+
+    sp_io::TestExternalities::from(storage).execute_with(|| {
+        assert_eq!(TemplateModule::clients(), vec![client_id()]);
+        assert_eq!(
+            TemplateModule::latest_height(&client_id()),
+            Some(Height::new(2, 100))
+        );
+    });
+}
+
+/// Client created and updated to height 2-150, as `export-tm-clients` exports it.
+fn exported_client() -> ExportedClient {
+    new_test_ext().execute_with(|| {
+        create_client();
+        update_client();
+        ExportedClient {
+            client: TMClientStorage::get(client_id()).client,
+            info: ClientInfoMap::get(client_id()),
+        }
+    })
+}
+
+/// Genesis storage importing `exported`.
+fn import_storage(exported: ExportedClient) -> sp_runtime::Storage {
+    let mut storage = frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    crate::GenesisConfig {
+        clients: Vec::new(),
+        imported_clients: vec![exported],
+    }
+    .assimilate_storage::<Test>(&mut storage)
+    .unwrap();
+    storage
+}
+
+#[test]
+fn imports_exported_clients() {
+    let storage = import_storage(exported_client());
+    sp_io::TestExternalities::from(storage).execute_with(|| {
+        let height = Height::new(2, 150);
+        assert_eq!(TemplateModule::clients(), vec![client_id()]);
+        assert_eq!(TemplateModule::latest_height(&client_id()), Some(height));
+        assert_eq!(ConsensusHeights::get(client_id()), vec![height]);
+    });
+}
+
+#[test]
+#[should_panic(expected = "client info is at another height than the client's consensus state")]
+fn rejects_imported_client_of_another_revision() {
+    let mut exported = exported_client();
+    // `testchain-2` is at revision 2
+    exported.info.last_block = Height::new(3, 150);
+    import_storage(exported);
+}
+
+#[test]
+fn rejects_corrupted_json_storage() {
+    for bytes in [&b""[..], b"not json", b"{}"].iter() {
+        assert!(TMClientStorageWrapper::decode(&mut &bytes[..]).is_err());
+        assert!(ConsensusStateWrapper::decode(&mut &bytes[..]).is_err());
+    }
+}
+
+const CONNECTION_CLIENT_ID: &[u8] = b"07-tendermint-0";
+const COUNTERPARTY_CLIENT_ID: &[u8] = b"07-tendermint-5";
+const COUNTERPARTY_CONNECTION_ID: &[u8] = b"connection-3";
+
+/// Counterparty chain, of which the client `CONNECTION_CLIENT_ID` is created.
+fn connection_counterparty() -> TestChain {
+    build_blocks(10);
+    let chain = TestChain::new("counterparty-1", validators(4, 10));
+    set_time(chain.time, 1);
+    let payload = chain.create_payload(CONNECTION_CLIENT_ID.to_vec(), 86400);
+    assert_ok!(TemplateModule::init_client(
+        Origin::signed(SIGNER),
+        serde_json::to_vec(&payload).unwrap()
+    ));
+    chain
+}
+
+/// Counterparty's client of this chain, at the latest block `build_blocks(10)` recorded.
+fn self_client_state() -> SelfClientState {
+    SelfClientState {
+        chain_id: b"wormhole".to_vec(),
+        latest_height: Height::new(0, 9),
+        frozen_height: Height::default(),
+        authority_set_id: 1,
+    }
+}
+
+/// Store of the counterparty, holding its end of connection `connection-0` of this chain in
+/// `state` and its client of this chain.
+fn counterparty_store(state: ConnectionState) -> TestStore {
+    counterparty_client_store(state, &self_client_state(), &self_consensus_state(7))
+}
+
+/// Store of the counterparty, holding its end of connection `connection-0` of this chain in
+/// `state` and its client of this chain with `client_state`, and `consensus_state` at height 7.
+fn counterparty_client_store(
+    state: ConnectionState,
+    client_state: &SelfClientState,
+    consensus_state: &SelfConsensusState,
+) -> TestStore {
+    let connection_id = match state {
+        ConnectionState::Init => Vec::new(),
+        _ => b"connection-0".to_vec(),
+    };
+    let end = ConnectionEnd {
+        state,
+        client_id: COUNTERPARTY_CLIENT_ID.to_vec(),
+        counterparty: Counterparty {
+            client_id: CONNECTION_CLIENT_ID.to_vec(),
+            connection_id,
+            prefix: b"ibc".to_vec(),
+        },
+        versions: vec![Version::default()],
+        delay_period: 0,
+    };
+    let mut store = TestStore::new();
+    store.set_connection(COUNTERPARTY_CONNECTION_ID, &end);
+    store.set(
+        client_state_path(COUNTERPARTY_CLIENT_ID),
+        client_state.encode(),
+    );
+    store.set(
+        consensus_state_path(COUNTERPARTY_CLIENT_ID, Height::new(0, 7)),
+        consensus_state.encode(),
+    );
+    store
+}
+
+/// Updates the client of `chain` to a header committing to `store`.
+fn commit_store(chain: &mut TestChain, store: &TestStore) -> Height {
+    chain.advance(1, 5);
+    chain.app_hash = store.root();
+    set_time(chain.time, 1);
+    let payload = chain.update_payload(CONNECTION_CLIENT_ID.to_vec());
+    assert_ok!(TemplateModule::update_client(
+        Origin::signed(SIGNER),
+        serde_json::to_vec(&payload).unwrap()
+    ));
+    chain.ibc_height()
+}
+
+fn counterparty(connection_id: &[u8]) -> Counterparty {
+    Counterparty {
+        client_id: COUNTERPARTY_CLIENT_ID.to_vec(),
+        connection_id: connection_id.to_vec(),
+        prefix: b"ibc".to_vec(),
+    }
+}
+
+#[test]
+fn opens_connection_from_init() {
+    new_test_ext().execute_with(|| {
+        let mut chain = connection_counterparty();
+        let store = counterparty_store(ConnectionState::TryOpen);
+        let connection_id = b"connection-0".to_vec();
+        let payload = ConnOpenInitPayload {
+            client_id: CONNECTION_CLIENT_ID.to_vec(),
+            counterparty: counterparty(b""),
+            version: None,
+            delay_period: 0,
+        };
+        assert_ok!(TemplateModule::conn_open_init(
+            Origin::signed(SIGNER),
+            serde_json::to_vec(&payload).unwrap()
+        ));
+        assert_eq!(
+            TemplateModule::connection(&connection_id).map(|end| end.state),
+            Some(ConnectionState::Init)
+        );
+        assert_eq!(
+            ClientConnections::get(CONNECTION_CLIENT_ID),
+            vec![connection_id.clone()]
+        );
+
+        let proof_height = commit_store(&mut chain, &store);
+        let mut payload = ConnOpenAckPayload {
+            connection_id: connection_id.clone(),
+            counterparty_connection_id: COUNTERPARTY_CONNECTION_ID.to_vec(),
+            version: Version::default(),
+            client_state: self_client_state().encode(),
+            consensus_state: self_consensus_state(7).encode(),
+            consensus_height: Height::new(0, 7),
+            proof_height: Height::new(1, 1),
+            proof_try: store.prove(&connection_path(COUNTERPARTY_CONNECTION_ID)),
+            proof_client: store.prove(&client_state_path(COUNTERPARTY_CLIENT_ID)),
+            proof_consensus: store.prove(&consensus_state_path(
+                COUNTERPARTY_CLIENT_ID,
+                Height::new(0, 7),
+            )),
+        };
+        // The app hash of the client's initial header is not the counterparty store's
+        assert_noop!(
+            TemplateModule::conn_open_ack(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap()
+            ),
+            Error::<Test>::InvalidCounterpartyProof
+        );
+        payload.proof_height = Height::new(1, 100);
+        assert_noop!(
+            TemplateModule::conn_open_ack(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap()
+            ),
+            Error::<Test>::ConsensusStateNotFound
+        );
+
+        payload.proof_height = proof_height;
+        assert_ok!(TemplateModule::conn_open_ack(
+            Origin::signed(SIGNER),
+            serde_json::to_vec(&payload).unwrap()
+        ));
+        let end = Connections::get(&connection_id).unwrap();
+        assert_eq!(end.state, ConnectionState::Open);
+        assert_eq!(end.counterparty, counterparty(COUNTERPARTY_CONNECTION_ID));
+        assert_eq!(
+            events().last(),
+            Some(&event(RawEvent::ConnectionOpenAck(
+                SIGNER,
+                connection_id,
+                COUNTERPARTY_CONNECTION_ID.to_vec()
+            )))
+        );
+
+        assert_noop!(
+            TemplateModule::conn_open_ack(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap()
+            ),
+            Error::<Test>::InvalidConnectionState
+        );
+    });
+}
+
+#[test]
+fn opens_connection_from_try() {
+    new_test_ext().execute_with(|| {
+        let mut chain = connection_counterparty();
+        let store = counterparty_store(ConnectionState::Init);
+        let connection_id = b"connection-0".to_vec();
+        let proof_height = commit_store(&mut chain, &store);
+        let payload = ConnOpenTryPayload {
+            client_id: CONNECTION_CLIENT_ID.to_vec(),
+            counterparty: counterparty(COUNTERPARTY_CONNECTION_ID),
+            delay_period: 0,
+            counterparty_versions: vec![Version::default()],
+            client_state: self_client_state().encode(),
+            consensus_state: self_consensus_state(7).encode(),
+            consensus_height: Height::new(0, 7),
+            proof_height,
+            proof_init: store.prove(&connection_path(COUNTERPARTY_CONNECTION_ID)),
+            proof_client: store.prove(&client_state_path(COUNTERPARTY_CLIENT_ID)),
+            proof_consensus: store.prove(&consensus_state_path(
+                COUNTERPARTY_CLIENT_ID,
+                Height::new(0, 7),
+            )),
+        };
+        assert_ok!(TemplateModule::conn_open_try(
+            Origin::signed(SIGNER),
+            serde_json::to_vec(&payload).unwrap()
+        ));
+        assert_eq!(
+            events().last(),
+            Some(&event(RawEvent::ConnectionOpenTry(
+                SIGNER,
+                connection_id.clone(),
+                CONNECTION_CLIENT_ID.to_vec()
+            )))
+        );
+
+        let confirm = |proof_height: Height, store: &TestStore| {
+            let payload = ConnOpenConfirmPayload {
+                connection_id: connection_id.clone(),
+                proof_height,
+                proof_ack: store.prove(&connection_path(COUNTERPARTY_CONNECTION_ID)),
+            };
+            TemplateModule::conn_open_confirm(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap(),
+            )
+        };
+        // The counterparty's end is still in Init
+        assert_noop!(
+            confirm(proof_height, &store),
+            Error::<Test>::InvalidCounterpartyProof
+        );
+
+        let opened = counterparty_store(ConnectionState::Open);
+        let proof_height = commit_store(&mut chain, &opened);
+        // Proofs are not accepted from an expired client
+        set_time(chain.time, 86400);
+        assert_noop!(confirm(proof_height, &opened), Error::<Test>::ClientExpired);
+
+        set_time(chain.time, 1);
+        assert_ok!(confirm(proof_height, &opened));
+        assert_eq!(
+            Connections::get(&connection_id).map(|end| end.state),
+            Some(ConnectionState::Open)
+        );
+    });
+}
+
+/// `ConnOpenTryPayload` answering the counterparty's `Init` end, whose client of this chain has
+/// `client_state`, and `consensus_state` at height 7.
+fn conn_open_try_payload(
+    chain: &mut TestChain,
+    client_state: &SelfClientState,
+    consensus_state: &SelfConsensusState,
+) -> ConnOpenTryPayload {
+    let store = counterparty_client_store(ConnectionState::Init, client_state, consensus_state);
+    ConnOpenTryPayload {
+        client_id: CONNECTION_CLIENT_ID.to_vec(),
+        counterparty: counterparty(COUNTERPARTY_CONNECTION_ID),
+        delay_period: 0,
+        counterparty_versions: vec![Version::default()],
+        client_state: client_state.encode(),
+        consensus_state: consensus_state.encode(),
+        consensus_height: Height::new(0, 7),
+        proof_height: commit_store(chain, &store),
+        proof_init: store.prove(&connection_path(COUNTERPARTY_CONNECTION_ID)),
+        proof_client: store.prove(&client_state_path(COUNTERPARTY_CLIENT_ID)),
+        proof_consensus: store.prove(&consensus_state_path(
+            COUNTERPARTY_CLIENT_ID,
+            Height::new(0, 7),
+        )),
+    }
+}
+
+#[cfg(feature = "self-client-validation")]
+#[test]
+fn rejects_invalid_self_clients() {
+    new_test_ext().execute_with(|| {
+        let mut chain = connection_counterparty();
+        let conn_open_try = |payload: &ConnOpenTryPayload| {
+            TemplateModule::conn_open_try(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(payload).unwrap(),
+            )
+        };
+        // The counterparty stores the states, but they are not of this chain
+        let other_chain = SelfClientState {
+            chain_id: b"wormhole-2".to_vec(),
+            ..self_client_state()
+        };
+        let payload = conn_open_try_payload(&mut chain, &other_chain, &self_consensus_state(7));
+        assert_noop!(conn_open_try(&payload), Error::<Test>::InvalidSelfClient);
+
+        // Block 10 is the one being built
+        let ahead = SelfClientState {
+            latest_height: Height::new(0, 10),
+            ..self_client_state()
+        };
+        let payload = conn_open_try_payload(&mut chain, &ahead, &self_consensus_state(7));
+        assert_noop!(conn_open_try(&payload), Error::<Test>::InvalidSelfClient);
+
+        // Block 9 was finalized by set 1
+        let other_set = SelfClientState {
+            authority_set_id: 2,
+            ..self_client_state()
+        };
+        let payload = conn_open_try_payload(&mut chain, &other_set, &self_consensus_state(7));
+        assert_noop!(conn_open_try(&payload), Error::<Test>::InvalidSelfClient);
+
+        let unknown_block = SelfConsensusState {
+            block_hash: H256::repeat_byte(0xff).as_bytes().to_vec(),
+            ..self_consensus_state(7)
+        };
+        let payload = conn_open_try_payload(&mut chain, &self_client_state(), &unknown_block);
+        assert_noop!(conn_open_try(&payload), Error::<Test>::InvalidSelfClient);
+
+        let mut payload =
+            conn_open_try_payload(&mut chain, &self_client_state(), &self_consensus_state(7));
+        payload.client_state.truncate(4);
+        assert_noop!(conn_open_try(&payload), Error::<Test>::DeserializeError);
+
+        // `ConnOpenAck` validates the client too
+        assert_ok!(TemplateModule::conn_open_init(
+            Origin::signed(SIGNER),
+            serde_json::to_vec(&ConnOpenInitPayload {
+                client_id: CONNECTION_CLIENT_ID.to_vec(),
+                counterparty: counterparty(b""),
+                version: None,
+                delay_period: 0,
+            })
+            .unwrap()
+        ));
+        let store = counterparty_client_store(
+            ConnectionState::TryOpen,
+            &other_set,
+            &self_consensus_state(7),
+        );
+        let payload = ConnOpenAckPayload {
+            connection_id: b"connection-0".to_vec(),
+            counterparty_connection_id: COUNTERPARTY_CONNECTION_ID.to_vec(),
+            version: Version::default(),
+            client_state: other_set.encode(),
+            consensus_state: self_consensus_state(7).encode(),
+            consensus_height: Height::new(0, 7),
+            proof_height: commit_store(&mut chain, &store),
+            proof_try: store.prove(&connection_path(COUNTERPARTY_CONNECTION_ID)),
+            proof_client: store.prove(&client_state_path(COUNTERPARTY_CLIENT_ID)),
+            proof_consensus: store.prove(&consensus_state_path(
+                COUNTERPARTY_CLIENT_ID,
+                Height::new(0, 7),
+            )),
+        };
+        assert_noop!(
+            TemplateModule::conn_open_ack(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap()
+            ),
+            Error::<Test>::InvalidSelfClient
+        );
+    });
+}
+
+#[cfg(not(feature = "self-client-validation"))]
+#[test]
+fn accepts_unvalidated_self_clients() {
+    new_test_ext().execute_with(|| {
+        let mut chain = connection_counterparty();
+        // The states are only proven to be stored by the counterparty
+        let other_chain = SelfClientState {
+            chain_id: b"wormhole-2".to_vec(),
+            ..self_client_state()
+        };
+        let payload = conn_open_try_payload(&mut chain, &other_chain, &self_consensus_state(7));
+        assert_ok!(TemplateModule::conn_open_try(
+            Origin::signed(SIGNER),
+            serde_json::to_vec(&payload).unwrap()
+        ));
+    });
+}
+
+const PORT_ID: &[u8] = b"mock";
+const CHANNEL_ID: &[u8] = b"channel-0";
+const COUNTERPARTY_CHANNEL_ID: &[u8] = b"channel-7";
+
+/// Opens connection `connection-0` to the counterparty from `ConnOpenTry`.
+fn open_connection() -> TestChain {
+    let mut chain = connection_counterparty();
+    let payload = conn_open_try_payload(&mut chain, &self_client_state(), &self_consensus_state(7));
+    assert_ok!(TemplateModule::conn_open_try(
+        Origin::signed(SIGNER),
+        serde_json::to_vec(&payload).unwrap()
+    ));
+
+    let store = counterparty_store(ConnectionState::Open);
+    let payload = ConnOpenConfirmPayload {
+        connection_id: b"connection-0".to_vec(),
+        proof_height: commit_store(&mut chain, &store),
+        proof_ack: store.prove(&connection_path(COUNTERPARTY_CONNECTION_ID)),
+    };
+    assert_ok!(TemplateModule::conn_open_confirm(
+        Origin::signed(SIGNER),
+        serde_json::to_vec(&payload).unwrap()
+    ));
+    chain
+}
+
+/// Store of the counterparty, holding its end of the channel to `channel_id` of this chain in
+/// `state`.
+fn counterparty_channel_store(
+    state: ChannelState,
+    ordering: Order,
+    channel_id: &[u8],
+) -> TestStore {
+    let end = ChannelEnd {
+        state,
+        ordering,
+        counterparty: ChannelCounterparty {
+            port_id: PORT_ID.to_vec(),
+            channel_id: channel_id.to_vec(),
+        },
+        connection_hops: vec![COUNTERPARTY_CONNECTION_ID.to_vec()],
+        version: b"mock-1".to_vec(),
+    };
+    let mut store = TestStore::new();
+    store.set_channel(PORT_ID, COUNTERPARTY_CHANNEL_ID, &end);
+    store
+}
+
+/// Opens channel `channel-0` of the `mock` port over an open connection, from `ChanOpenTry`.
+fn open_channel(ordering: Order) -> TestChain {
+    let mut chain = open_connection();
+    let store = counterparty_channel_store(ChannelState::Init, ordering, b"");
+    let payload = ChanOpenTryPayload {
+        port_id: PORT_ID.to_vec(),
+        connection_id: b"connection-0".to_vec(),
+        ordering,
+        counterparty: ChannelCounterparty {
+            port_id: PORT_ID.to_vec(),
+            channel_id: COUNTERPARTY_CHANNEL_ID.to_vec(),
+        },
+        counterparty_version: b"mock-1".to_vec(),
+        proof_height: commit_store(&mut chain, &store),
+        proof_init: store.prove(&channel_path(PORT_ID, COUNTERPARTY_CHANNEL_ID)),
+    };
+    assert_ok!(TemplateModule::chan_open_try(
+        Origin::signed(SIGNER),
+        serde_json::to_vec(&payload).unwrap()
+    ));
+
+    let store = counterparty_channel_store(ChannelState::Open, ordering, CHANNEL_ID);
+    let payload = ChanOpenConfirmPayload {
+        port_id: PORT_ID.to_vec(),
+        channel_id: CHANNEL_ID.to_vec(),
+        proof_height: commit_store(&mut chain, &store),
+        proof_ack: store.prove(&channel_path(PORT_ID, COUNTERPARTY_CHANNEL_ID)),
+    };
+    assert_ok!(TemplateModule::chan_open_confirm(
+        Origin::signed(SIGNER),
+        serde_json::to_vec(&payload).unwrap()
+    ));
+    chain
+}
+
+/// Packet `sequence` sent by the counterparty to channel `channel-0`.
+fn received_packet(sequence: u64, timeout_height: Height) -> Packet {
+    Packet {
+        sequence,
+        source_port: PORT_ID.to_vec(),
+        source_channel: COUNTERPARTY_CHANNEL_ID.to_vec(),
+        destination_port: PORT_ID.to_vec(),
+        destination_channel: CHANNEL_ID.to_vec(),
+        data: b"hello".to_vec(),
+        timeout_height,
+        timeout_timestamp: 0,
+    }
+}
+
+#[test]
+fn opens_channel_from_init() {
+    new_test_ext().execute_with(|| {
+        let mut chain = open_connection();
+        let mut payload = ChanOpenInitPayload {
+            port_id: b"unbound".to_vec(),
+            connection_id: b"connection-0".to_vec(),
+            ordering: Order::Unordered,
+            counterparty_port_id: PORT_ID.to_vec(),
+            version: b"mock-1".to_vec(),
+        };
+        assert_noop!(
+            TemplateModule::chan_open_init(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap()
+            ),
+            Error::<Test>::PortNotBound
+        );
+
+        payload.port_id = PORT_ID.to_vec();
+        assert_ok!(TemplateModule::chan_open_init(
+            Origin::signed(SIGNER),
+            serde_json::to_vec(&payload).unwrap()
+        ));
+        assert_eq!(
+            TemplateModule::channel(PORT_ID, CHANNEL_ID).map(|end| end.state),
+            Some(ChannelState::Init)
+        );
+        assert_eq!(TemplateModule::next_sequence_send(PORT_ID, CHANNEL_ID), 1);
+
+        let store = counterparty_channel_store(ChannelState::TryOpen, Order::Unordered, CHANNEL_ID);
+        let payload = ChanOpenAckPayload {
+            port_id: PORT_ID.to_vec(),
+            channel_id: CHANNEL_ID.to_vec(),
+            counterparty_channel_id: COUNTERPARTY_CHANNEL_ID.to_vec(),
+            counterparty_version: b"mock-1".to_vec(),
+            proof_height: commit_store(&mut chain, &store),
+            proof_try: store.prove(&channel_path(PORT_ID, COUNTERPARTY_CHANNEL_ID)),
+        };
+        assert_ok!(TemplateModule::chan_open_ack(
+            Origin::signed(SIGNER),
+            serde_json::to_vec(&payload).unwrap()
+        ));
+        let end = Channels::get(PORT_ID, CHANNEL_ID).unwrap();
+        assert_eq!(end.state, ChannelState::Open);
+        assert_eq!(
+            end.counterparty.channel_id,
+            COUNTERPARTY_CHANNEL_ID.to_vec()
+        );
+        assert_eq!(
+            events().last(),
+            Some(&event(RawEvent::ChannelOpenAck(
+                SIGNER,
+                PORT_ID.to_vec(),
+                CHANNEL_ID.to_vec()
+            )))
+        );
+
+        assert_ok!(TemplateModule::chan_close_init(
+            Origin::signed(SIGNER),
+            PORT_ID.to_vec(),
+            CHANNEL_ID.to_vec()
+        ));
+        assert_eq!(
+            Channels::get(PORT_ID, CHANNEL_ID).map(|end| end.state),
+            Some(ChannelState::Closed)
+        );
+        assert_noop!(
+            TemplateModule::chan_close_init(
+                Origin::signed(SIGNER),
+                PORT_ID.to_vec(),
+                CHANNEL_ID.to_vec()
+            ),
+            Error::<Test>::InvalidChannelState
+        );
+    });
+}
+
+#[test]
+fn receives_packets() {
+    new_test_ext().execute_with(|| {
+        let mut chain = open_channel(Order::Unordered);
+        let packet = received_packet(1, Height::new(0, 1000));
+        let timed_out = received_packet(2, Height::new(0, 1));
+        let mut store = TestStore::new();
+        for packet in &[&packet, &timed_out] {
+            store.set(
+                packet_commitment_path(PORT_ID, COUNTERPARTY_CHANNEL_ID, packet.sequence),
+                packet.commitment(),
+            );
+        }
+        let proof_height = commit_store(&mut chain, &store);
+        let recv = |packet: &Packet| {
+            let payload = RecvPacketPayload {
+                packet: packet.clone(),
+                proof_height,
+                proof_commitment: store.prove(&packet_commitment_path(
+                    PORT_ID,
+                    COUNTERPARTY_CHANNEL_ID,
+                    packet.sequence,
+                )),
+            };
+            TemplateModule::recv_packet(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap(),
+            )
+        };
+
+        assert_noop!(recv(&timed_out), Error::<Test>::PacketTimedOut);
+        let mut forged = packet.clone();
+        forged.data = b"forged".to_vec();
+        assert_noop!(recv(&forged), Error::<Test>::InvalidCounterpartyProof);
+
+        assert_ok!(recv(&packet));
+        let channel = (PORT_ID.to_vec(), CHANNEL_ID.to_vec());
+        assert!(PacketReceipts::get(&channel, 1));
+        assert_eq!(
+            PacketAcknowledgements::get(&channel, 1),
+            Some(acknowledgement_commitment(b"olleh"))
+        );
+        assert_eq!(MockModule::packets(), vec![("recv", 1)]);
+        assert_eq!(
+            events().last(),
+            Some(&event(RawEvent::RecvPacket(
+                SIGNER,
+                packet.clone(),
+                b"olleh".to_vec()
+            )))
+        );
+
+        assert_noop!(recv(&packet), Error::<Test>::PacketAlreadyReceived);
+    });
+}
 
 #[test]
-fn it_works_for_default_value() {
+fn acknowledges_sent_packets() {
     new_test_ext().execute_with(|| {
-        // Just a dummy test for the dummy function `do_something`
-        // calling the `do_something` function with a value 42
-        assert_ok!(TemplateModule::do_something(Origin::signed(1), 42));
-        // asserting that the stored value is equal to what we stored
-        assert_eq!(TemplateModule::something(), Some(42));
+        let mut chain = open_channel(Order::Ordered);
+        assert_noop!(
+            TemplateModule::send_packet(
+                PORT_ID.to_vec(),
+                CHANNEL_ID.to_vec(),
+                b"hello".to_vec(),
+                chain.ibc_height(),
+                0
+            ),
+            Error::<Test>::PacketTimedOut
+        );
+        let timeout_height = Height::new(1, chain.height + 10);
+        assert_eq!(
+            TemplateModule::send_packet(
+                PORT_ID.to_vec(),
+                CHANNEL_ID.to_vec(),
+                b"hello".to_vec(),
+                timeout_height,
+                0
+            ),
+            Ok(1)
+        );
+        let mut packet = received_packet(1, timeout_height);
+        packet.source_channel = CHANNEL_ID.to_vec();
+        packet.destination_channel = COUNTERPARTY_CHANNEL_ID.to_vec();
+        let channel = (PORT_ID.to_vec(), CHANNEL_ID.to_vec());
+        assert_eq!(
+            PacketCommitments::get(&channel, 1),
+            Some(packet.commitment())
+        );
+        assert_eq!(
+            events().last(),
+            Some(&event(RawEvent::SendPacket(packet.clone())))
+        );
+
+        let mut store = TestStore::new();
+        store.set(
+            packet_acknowledgement_path(PORT_ID, COUNTERPARTY_CHANNEL_ID, 1),
+            acknowledgement_commitment(b"ok"),
+        );
+        let mut payload = AcknowledgePacketPayload {
+            packet: packet.clone(),
+            acknowledgement: b"not ok".to_vec(),
+            proof_height: commit_store(&mut chain, &store),
+            proof_acked: store.prove(&packet_acknowledgement_path(
+                PORT_ID,
+                COUNTERPARTY_CHANNEL_ID,
+                1,
+            )),
+        };
+        assert_noop!(
+            TemplateModule::acknowledge_packet(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap()
+            ),
+            Error::<Test>::InvalidCounterpartyProof
+        );
+
+        payload.acknowledgement = b"ok".to_vec();
+        assert_ok!(TemplateModule::acknowledge_packet(
+            Origin::signed(SIGNER),
+            serde_json::to_vec(&payload).unwrap()
+        ));
+        assert_eq!(PacketCommitments::get(&channel, 1), None);
+        assert_eq!(TemplateModule::next_sequence_ack(PORT_ID, CHANNEL_ID), 2);
+        assert_eq!(MockModule::packets(), vec![("acknowledgement", 1)]);
+
+        assert_noop!(
+            TemplateModule::acknowledge_packet(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap()
+            ),
+            Error::<Test>::PacketNotCommitted
+        );
     });
 }
 
 #[test]
-fn correct_error_for_none_value() {
+fn keeps_commitment_when_acknowledgement_fails() {
     new_test_ext().execute_with(|| {
-        // Ensure the correct error is thrown on None value
+        let mut chain = open_channel(Order::Ordered);
+        let timeout_height = Height::new(1, chain.height + 10);
+        assert_ok!(TemplateModule::send_packet(
+            PORT_ID.to_vec(),
+            CHANNEL_ID.to_vec(),
+            b"hello".to_vec(),
+            timeout_height,
+            0
+        ));
+        let mut packet = received_packet(1, timeout_height);
+        packet.source_channel = CHANNEL_ID.to_vec();
+        packet.destination_channel = COUNTERPARTY_CHANNEL_ID.to_vec();
+
+        let mut store = TestStore::new();
+        store.set(
+            packet_acknowledgement_path(PORT_ID, COUNTERPARTY_CHANNEL_ID, 1),
+            acknowledgement_commitment(FAILING_ACKNOWLEDGEMENT),
+        );
+        let payload = AcknowledgePacketPayload {
+            packet: packet.clone(),
+            acknowledgement: FAILING_ACKNOWLEDGEMENT.to_vec(),
+            proof_height: commit_store(&mut chain, &store),
+            proof_acked: store.prove(&packet_acknowledgement_path(
+                PORT_ID,
+                COUNTERPARTY_CHANNEL_ID,
+                1,
+            )),
+        };
+        // The commitment is removed before the module is called, and restored when it fails
         assert_noop!(
-            TemplateModule::cause_error(Origin::signed(1)),
-            Error::<Test>::NoneValue
+            TemplateModule::acknowledge_packet(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap()
+            ),
+            "acknowledgement failed"
+        );
+        assert_eq!(MockModule::packets(), vec![("acknowledgement", 1)]);
+        let channel = (PORT_ID.to_vec(), CHANNEL_ID.to_vec());
+        assert_eq!(
+            PacketCommitments::get(&channel, 1),
+            Some(packet.commitment())
+        );
+        assert_eq!(TemplateModule::next_sequence_ack(PORT_ID, CHANNEL_ID), 1);
+    });
+}
+
+#[test]
+fn times_out_sent_packets() {
+    new_test_ext().execute_with(|| {
+        let mut chain = open_channel(Order::Unordered);
+        let timeout_height = Height::new(1, chain.height + 2);
+        assert_ok!(TemplateModule::send_packet(
+            PORT_ID.to_vec(),
+            CHANNEL_ID.to_vec(),
+            b"hello".to_vec(),
+            timeout_height,
+            0
+        ));
+        let mut packet = received_packet(1, timeout_height);
+        packet.source_channel = CHANNEL_ID.to_vec();
+        packet.destination_channel = COUNTERPARTY_CHANNEL_ID.to_vec();
+
+        let store = counterparty_channel_store(ChannelState::Open, Order::Unordered, CHANNEL_ID);
+        let receipt_path = packet_receipt_path(PORT_ID, COUNTERPARTY_CHANNEL_ID, 1);
+        let timeout = |proof_height: Height| {
+            let payload = TimeoutPacketPayload {
+                packet: packet.clone(),
+                next_sequence_recv: 0,
+                proof_height,
+                proof_unreceived: store.prove_absence(&receipt_path),
+            };
+            TemplateModule::timeout_packet(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap(),
+            )
+        };
+        let proof_height = commit_store(&mut chain, &store);
+        assert_noop!(timeout(proof_height), Error::<Test>::PacketNotTimedOut);
+
+        let proof_height = commit_store(&mut chain, &store);
+        assert_ok!(timeout(proof_height));
+        let channel = (PORT_ID.to_vec(), CHANNEL_ID.to_vec());
+        assert_eq!(PacketCommitments::get(&channel, 1), None);
+        assert_eq!(MockModule::packets(), vec![("timeout", 1)]);
+        // Unordered channels stay open
+        assert_eq!(
+            Channels::get(PORT_ID, CHANNEL_ID).map(|end| end.state),
+            Some(ChannelState::Open)
+        );
+        assert_noop!(timeout(proof_height), Error::<Test>::PacketNotCommitted);
+    });
+}
+
+#[test]
+fn times_out_packets_over_closed_ordered_channels() {
+    new_test_ext().execute_with(|| {
+        let mut chain = open_channel(Order::Ordered);
+        let timeout_height = Height::new(1, chain.height + 2);
+        let packets: Vec<Packet> = (1..=2)
+            .map(|sequence| {
+                assert_eq!(
+                    TemplateModule::send_packet(
+                        PORT_ID.to_vec(),
+                        CHANNEL_ID.to_vec(),
+                        b"hello".to_vec(),
+                        timeout_height,
+                        0
+                    ),
+                    Ok(sequence)
+                );
+                let mut packet = received_packet(sequence, timeout_height);
+                packet.source_channel = CHANNEL_ID.to_vec();
+                packet.destination_channel = COUNTERPARTY_CHANNEL_ID.to_vec();
+                packet
+            })
+            .collect();
+
+        // The counterparty still waits for the first packet
+        let mut store = TestStore::new();
+        let path = next_sequence_recv_path(PORT_ID, COUNTERPARTY_CHANNEL_ID);
+        store.set(path.clone(), 1u64.to_be_bytes().to_vec());
+        commit_store(&mut chain, &store);
+        let proof_height = commit_store(&mut chain, &store);
+        let timeout = |packet: &Packet| {
+            let payload = TimeoutPacketPayload {
+                packet: packet.clone(),
+                next_sequence_recv: 1,
+                proof_height,
+                proof_unreceived: store.prove(&path),
+            };
+            TemplateModule::timeout_packet(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap(),
+            )
+        };
+
+        assert_ok!(timeout(&packets[0]));
+        assert_eq!(
+            Channels::get(PORT_ID, CHANNEL_ID).map(|end| end.state),
+            Some(ChannelState::Closed)
+        );
+        assert_ok!(timeout(&packets[1]));
+        let channel = (PORT_ID.to_vec(), CHANNEL_ID.to_vec());
+        assert_eq!(PacketCommitments::get(&channel, 1), None);
+        assert_eq!(PacketCommitments::get(&channel, 2), None);
+        assert_eq!(MockModule::packets(), vec![("timeout", 1), ("timeout", 2)]);
+        assert_eq!(
+            events().last(),
+            Some(&event(RawEvent::TimeoutPacket(SIGNER, packets[1].clone())))
+        );
+    });
+}
+
+/// Delay period of 1 minute, which lasts 10 blocks of the mock runtime.
+const DELAY_PERIOD: u64 = 60_000_000_000;
+
+/// Sets the delay period of connection `connection-0`, as if it had been opened with it.
+fn set_delay_period(delay_period: u64) {
+    Connections::mutate(b"connection-0".to_vec(), |end| {
+        end.as_mut().unwrap().delay_period = delay_period
+    });
+}
+
+/// Checks that `submit`, whose proof height was just processed, is only accepted once the delay
+/// period passed both in time and in blocks.
+fn assert_delayed(submit: impl Fn() -> DispatchResult) {
+    let (processed_time, processed_block) = (Timestamp::now(), System::block_number());
+    let delay_blocks = DELAY_PERIOD / (MaxExpectedTimePerBlock::get() * 1_000_000);
+    assert_noop!(submit(), Error::<Test>::DelayPeriodNotPassed);
+
+    // Enough blocks, not enough time
+    System::set_block_number(processed_block + delay_blocks);
+    Timestamp::set_timestamp(processed_time + DELAY_PERIOD / 1_000_000 - 1);
+    assert_noop!(submit(), Error::<Test>::DelayPeriodNotPassed);
+
+    // Enough time, not enough blocks
+    System::set_block_number(processed_block + delay_blocks - 1);
+    Timestamp::set_timestamp(processed_time + DELAY_PERIOD / 1_000_000);
+    assert_noop!(submit(), Error::<Test>::DelayPeriodNotPassed);
+
+    System::set_block_number(processed_block + delay_blocks);
+    assert_ok!(submit());
+}
+
+#[test]
+fn delays_received_packets() {
+    new_test_ext().execute_with(|| {
+        let mut chain = open_channel(Order::Unordered);
+        set_delay_period(DELAY_PERIOD);
+        let packet = received_packet(1, Height::new(0, 1000));
+        let path = packet_commitment_path(PORT_ID, COUNTERPARTY_CHANNEL_ID, 1);
+        let mut store = TestStore::new();
+        store.set(path.clone(), packet.commitment());
+        let payload = RecvPacketPayload {
+            packet,
+            proof_height: commit_store(&mut chain, &store),
+            proof_commitment: store.prove(&path),
+        };
+        assert_delayed(|| {
+            TemplateModule::recv_packet(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap(),
+            )
+        });
+        assert!(PacketReceipts::get(
+            (PORT_ID.to_vec(), CHANNEL_ID.to_vec()),
+            1
+        ));
+    });
+}
+
+#[test]
+fn delays_acknowledgements() {
+    new_test_ext().execute_with(|| {
+        let mut chain = open_channel(Order::Unordered);
+        set_delay_period(DELAY_PERIOD);
+        let timeout_height = Height::new(1, chain.height + 10);
+        assert_ok!(TemplateModule::send_packet(
+            PORT_ID.to_vec(),
+            CHANNEL_ID.to_vec(),
+            b"hello".to_vec(),
+            timeout_height,
+            0
+        ));
+        let mut packet = received_packet(1, timeout_height);
+        packet.source_channel = CHANNEL_ID.to_vec();
+        packet.destination_channel = COUNTERPARTY_CHANNEL_ID.to_vec();
+
+        let path = packet_acknowledgement_path(PORT_ID, COUNTERPARTY_CHANNEL_ID, 1);
+        let mut store = TestStore::new();
+        store.set(path.clone(), acknowledgement_commitment(b"ok"));
+        let payload = AcknowledgePacketPayload {
+            packet,
+            acknowledgement: b"ok".to_vec(),
+            proof_height: commit_store(&mut chain, &store),
+            proof_acked: store.prove(&path),
+        };
+        assert_delayed(|| {
+            TemplateModule::acknowledge_packet(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap(),
+            )
+        });
+        assert_eq!(
+            PacketCommitments::get((PORT_ID.to_vec(), CHANNEL_ID.to_vec()), 1),
+            None
+        );
+    });
+}
+
+#[test]
+fn delays_timeouts() {
+    new_test_ext().execute_with(|| {
+        let mut chain = open_channel(Order::Unordered);
+        set_delay_period(DELAY_PERIOD);
+        let timeout_height = Height::new(1, chain.height + 1);
+        assert_ok!(TemplateModule::send_packet(
+            PORT_ID.to_vec(),
+            CHANNEL_ID.to_vec(),
+            b"hello".to_vec(),
+            timeout_height,
+            0
+        ));
+        let mut packet = received_packet(1, timeout_height);
+        packet.source_channel = CHANNEL_ID.to_vec();
+        packet.destination_channel = COUNTERPARTY_CHANNEL_ID.to_vec();
+
+        let store = counterparty_channel_store(ChannelState::Open, Order::Unordered, CHANNEL_ID);
+        let payload = TimeoutPacketPayload {
+            packet,
+            next_sequence_recv: 0,
+            proof_height: commit_store(&mut chain, &store),
+            proof_unreceived: store.prove_absence(&packet_receipt_path(
+                PORT_ID,
+                COUNTERPARTY_CHANNEL_ID,
+                1,
+            )),
+        };
+        assert_delayed(|| {
+            TemplateModule::timeout_packet(
+                Origin::signed(SIGNER),
+                serde_json::to_vec(&payload).unwrap(),
+            )
+        });
+        assert_eq!(MockModule::packets(), vec![("timeout", 1)]);
+    });
+}
+
+#[test]
+fn commits_to_host_store() {
+    new_test_ext().execute_with(|| {
+        let chain = open_channel(Order::Unordered);
+        let connection = Connections::get(b"connection-0".to_vec()).unwrap();
+        assert_eq!(
+            sp_io::storage::get(b"ibc/connections/connection-0"),
+            Some(proto::encode(&connection.to_proto()))
+        );
+        let channel = Channels::get(PORT_ID, CHANNEL_ID).unwrap();
+        assert_eq!(
+            TemplateModule::host_commitment(&channel_path(PORT_ID, CHANNEL_ID)),
+            Some(proto::encode(&channel.to_proto()))
+        );
+        // Clients are committed to as ibc-go encodes them, for Cosmos chains to verify
+        assert_eq!(
+            TemplateModule::host_commitment(&client_state_path(CONNECTION_CLIENT_ID)),
+            Some(any(
+                proto::TENDERMINT_CLIENT_STATE_TYPE_URL,
+                &chain.proto_client_state(86400, &[])
+            ))
+        );
+        assert_eq!(
+            TemplateModule::host_commitment(&consensus_state_path(
+                CONNECTION_CLIENT_ID,
+                chain.ibc_height()
+            )),
+            Some(any(
+                proto::TENDERMINT_CONSENSUS_STATE_TYPE_URL,
+                &chain.proto_consensus_state()
+            ))
+        );
+
+        let timeout_height = Height::new(1, chain.height + 10);
+        assert_ok!(TemplateModule::send_packet(
+            PORT_ID.to_vec(),
+            CHANNEL_ID.to_vec(),
+            b"hello".to_vec(),
+            timeout_height,
+            0
+        ));
+        let mut packet = received_packet(1, timeout_height);
+        packet.source_channel = CHANNEL_ID.to_vec();
+        packet.destination_channel = COUNTERPARTY_CHANNEL_ID.to_vec();
+        assert_eq!(
+            TemplateModule::host_commitment(&packet_commitment_path(PORT_ID, CHANNEL_ID, 1)),
+            Some(packet.commitment())
+        );
+        assert_eq!(
+            TemplateModule::host_commitment(&packet_receipt_path(PORT_ID, CHANNEL_ID, 1)),
+            None
+        );
+    });
+}
+
+/// Consensus state of block `n` of this chain, as `build_blocks` builds it.
+fn self_consensus_state(n: u64) -> SelfConsensusState {
+    SelfConsensusState {
+        block_hash: H256::repeat_byte(n as u8).as_bytes().to_vec(),
+        timestamp: n * 6_000,
+        authority_set_id: 1,
+    }
+}
+
+/// Builds the blocks of this chain up to block `n`.
+fn build_blocks(n: u64) {
+    for number in 1..=n {
+        build_block(number);
+    }
+}
+
+/// Starts block `number` of this chain, on top of the parent `build_blocks` builds.
+fn build_block(number: u64) {
+    let parent = self_consensus_state(number - 1);
+    Timestamp::set_timestamp(parent.timestamp);
+    System::set_parent_hash(H256::from_slice(&parent.block_hash));
+    System::set_block_number(number);
+    TemplateModule::on_initialize(number);
+}
+
+#[test]
+fn records_authority_set_of_parent() {
+    new_test_ext().execute_with(|| {
+        build_blocks(5);
+        // Block 5 enacts a set change: set 1 finalizes it, and set 2 the blocks after it
+        AuthoritySetId::set(2);
+        build_block(6);
+        assert_eq!(
+            TemplateModule::recorded_self_consensus_state(Height::new(0, 5)),
+            Some(self_consensus_state(5))
+        );
+        build_block(7);
+        assert_eq!(
+            TemplateModule::recorded_self_consensus_state(Height::new(0, 6)),
+            Some(SelfConsensusState {
+                authority_set_id: 2,
+                ..self_consensus_state(6)
+            })
+        );
+
+        let client_state = SelfClientState {
+            chain_id: b"wormhole".to_vec(),
+            latest_height: Height::new(0, 5),
+            frozen_height: Height::default(),
+            authority_set_id: 1,
+        };
+        assert_eq!(
+            TemplateModule::validate_self_client(
+                &client_state,
+                &self_consensus_state(5),
+                Height::new(0, 5)
+            ),
+            Ok(())
+        );
+        let new_set = SelfClientState {
+            authority_set_id: 2,
+            ..client_state.clone()
+        };
+        assert_eq!(
+            TemplateModule::validate_self_client(
+                &new_set,
+                &self_consensus_state(5),
+                Height::new(0, 5)
+            ),
+            Err(SelfClientError::AuthoritySetMismatch)
+        );
+        let latest = SelfClientState {
+            latest_height: Height::new(0, 6),
+            ..new_set
+        };
+        assert_eq!(
+            TemplateModule::validate_self_client(
+                &latest,
+                &self_consensus_state(5),
+                Height::new(0, 5)
+            ),
+            Ok(())
+        );
+    });
+}
+
+#[test]
+fn validates_self_client() {
+    new_test_ext().execute_with(|| {
+        build_blocks(10);
+        assert_eq!(
+            TemplateModule::recorded_self_consensus_state(Height::new(0, 9)),
+            Some(self_consensus_state(9))
+        );
+        // Only the `SelfHistory` most recent blocks are kept
+        assert_eq!(
+            TemplateModule::recorded_self_consensus_state(Height::new(0, 6)),
+            None
+        );
+
+        let client_state = SelfClientState {
+            chain_id: b"wormhole".to_vec(),
+            latest_height: Height::new(0, 9),
+            frozen_height: Height::default(),
+            authority_set_id: 1,
+        };
+        let validate = |client_state: &SelfClientState, height: u64, state: SelfConsensusState| {
+            TemplateModule::validate_self_client(client_state, &state, Height::new(0, height))
+        };
+        assert_eq!(validate(&client_state, 8, self_consensus_state(8)), Ok(()));
+        assert_eq!(
+            validate(&client_state, 8, self_consensus_state(7)),
+            Err(SelfClientError::ConsensusStateMismatch)
+        );
+        assert_eq!(
+            validate(&client_state, 6, self_consensus_state(6)),
+            Err(SelfClientError::ConsensusStateNotFound)
+        );
+
+        let ahead = SelfClientState {
+            latest_height: Height::new(0, 10),
+            ..client_state.clone()
+        };
+        assert_eq!(
+            validate(&ahead, 8, self_consensus_state(8)),
+            Err(SelfClientError::HeightFromFuture)
+        );
+        let other_chain = SelfClientState {
+            chain_id: b"wormhole-2".to_vec(),
+            ..client_state
+        };
+        assert_eq!(
+            validate(&other_chain, 8, self_consensus_state(8)),
+            Err(SelfClientError::ChainIdMismatch)
+        );
+    });
+}
+
+#[test]
+fn keeps_parent_consensus_state_without_history() {
+    new_test_ext().execute_with(|| {
+        SelfHistory::set(0);
+        build_blocks(4);
+        assert_eq!(
+            TemplateModule::recorded_self_consensus_state(Height::new(0, 3)),
+            Some(self_consensus_state(3))
+        );
+        assert_eq!(
+            TemplateModule::recorded_self_consensus_state(Height::new(0, 2)),
+            None
         );
     });
 }
@@ -1,17 +1,81 @@
 use codec::{Decode, Encode, EncodeLike, Error, Input, Output};
-use serde::{Deserialize, Serialize};
-use sp_std::{default::Default, vec::Vec};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sp_std::{cmp::Ordering, default::Default, fmt, vec::Vec};
 
+use alloc::string::String;
+
+use crate::light_client::{SignedHeader, TrustThresholdFraction, TrustedState, ValidatorSet};
 use chrono::{DateTime, Utc};
-use tendermint_light_client::{
-    ClientId, Commit, LightHeader, LightSignedHeader, LightValidator, LightValidatorSet,
-    TrustThresholdFraction, TrustedState,
-};
+
+/// ICS-02 height: a block height qualified by the revision of the chain it belongs to.
+/// Heights are ordered by revision first, so heights of an upgraded chain are always greater.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Encode, Decode,
+)]
+pub struct Height {
+    pub revision_number: u64,
+    pub revision_height: u64,
+}
+
+impl Height {
+    pub fn new(revision_number: u64, revision_height: u64) -> Self {
+        Height {
+            revision_number,
+            revision_height,
+        }
+    }
+
+    /// Height of block `revision_height` of the chain identified by `chain_id`.
+    pub fn from_chain_id(chain_id: &[u8], revision_height: u64) -> Self {
+        Height::new(Self::revision_from_chain_id(chain_id), revision_height)
+    }
+
+    /// Parses the revision number from chain ids of the form `{name}-{N}`, e.g. `cosmoshub-4`.
+    /// Chain ids not in that form have revision 0.
+    pub fn revision_from_chain_id(chain_id: &[u8]) -> u64 {
+        let split = match chain_id.iter().rposition(|c| *c == b'-') {
+            Some(split) => split,
+            None => return 0,
+        };
+        let (name, revision) = (&chain_id[..split], &chain_id[split + 1..]);
+        if name.is_empty()
+            || name.ends_with(b"-")
+            || revision.is_empty()
+            || revision[0] == b'0'
+            || !revision.iter().all(u8::is_ascii_digit)
+        {
+            return 0;
+        }
+        core::str::from_utf8(revision)
+            .ok()
+            .and_then(|r| r.parse().ok())
+            .unwrap_or(0)
+    }
+}
+
+impl Ord for Height {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.revision_number, self.revision_height)
+            .cmp(&(other.revision_number, other.revision_height))
+    }
+}
+
+impl PartialOrd for Height {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.revision_number, self.revision_height)
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TMHeader {
-    pub signed_header: LightSignedHeader,
-    pub validator_set: LightValidatorSet<LightValidator>,
+    pub signed_header: SignedHeader,
+    pub validator_set: ValidatorSet,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -20,19 +84,46 @@ pub struct TMCreateClientPayload {
     pub trusting_period: u64,
     pub max_clock_drift: u64,
     pub unbonding_period: u64,
-    pub client_id: ClientId,
+    pub client_id: Vec<u8>,
+    #[serde(default = "default_upgrade_path")]
+    pub upgrade_path: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TMUpdateClientPayload {
     pub header: TMHeader,
-    pub client_id: ClientId,
-    pub next_validator_set: LightValidatorSet<LightValidator>,
+    pub client_id: Vec<u8>,
+    pub next_validator_set: ValidatorSet,
+}
+
+/// Consensus state of a past height, between two heights the client already trusts.
+/// `trusted_validators` is the validator set the trusted header below `header` committed to as its next one,
+/// needed unless it is known to the client or the heights are adjacent.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TMBackfillPayload {
+    pub header: TMHeader,
+    pub client_id: Vec<u8>,
+    #[serde(default)]
+    pub trusted_validators: Option<ValidatorSet>,
+}
+
+/// Upgrade of a client to the next revision of its chain.
+/// `upgraded_client_state` and `upgraded_consensus_state` are the protobuf `Any` encoded states
+/// committed by the old chain, and the proofs are protobuf encoded ICS-23 `MerkleProof`s.
+/// `header` is the first header of the upgraded chain, signed by the upgraded validator set.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TMUpgradeClientPayload {
+    pub client_id: Vec<u8>,
+    pub upgraded_client_state: Vec<u8>,
+    pub upgraded_consensus_state: Vec<u8>,
+    pub proof_upgrade_client: Vec<u8>,
+    pub proof_upgrade_consensus_state: Vec<u8>,
+    pub header: TMHeader,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ConsensusState {
-    pub state: TrustedState<Commit, LightHeader, LightValidator>,
+    pub state: TrustedState,
     pub last_update: DateTime<Utc>,
 }
 
@@ -45,9 +136,23 @@ pub struct TendermintClient {
     pub max_clock_drift: u64,
     pub unbonding_period: u64,
     pub trust_threshold: TrustThresholdFraction,
+    #[serde(default = "default_upgrade_path_bytes")]
+    pub upgrade_path: Vec<Vec<u8>>,
     //pub owner: cosmosAddress,
 }
 
+/// Store and key under which Cosmos SDK chains commit to upgraded client states.
+fn default_upgrade_path() -> Vec<String> {
+    alloc::vec!["upgrade".into(), "upgradedIBCState".into()]
+}
+
+fn default_upgrade_path_bytes() -> Vec<Vec<u8>> {
+    default_upgrade_path()
+        .into_iter()
+        .map(String::into_bytes)
+        .collect()
+}
+
 impl Default for TendermintClient {
     fn default() -> Self {
         TendermintClient {
@@ -58,6 +163,7 @@ impl Default for TendermintClient {
             max_clock_drift: 30,
             unbonding_period: 86400 * 7 * 3,
             trust_threshold: TrustThresholdFraction::default(),
+            upgrade_path: default_upgrade_path_bytes(),
         }
     }
 }
@@ -68,7 +174,38 @@ pub struct TMClientInfo {
     pub trusting_period: u64,
     pub max_clock_drift: u64,
     pub unbonding_period: u64,
-    pub last_block: u64,
+    pub last_block: Height,
+}
+
+/// Client as exported from a chain by `export-tm-clients`, to be imported at genesis of another chain.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExportedClient {
+    pub client: TendermintClient,
+    pub info: TMClientInfo,
+}
+
+/// Rules the consensus states of a client are pruned by.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Encode, Decode)]
+pub struct RetentionPolicy {
+    /// Consensus states whose header is older than this many seconds are pruned.
+    pub trusting_period: u64,
+    /// Only this many of the most recent consensus states are kept.
+    pub max_consensus_states: u32,
+    /// Number of steps pruning may take per block, across all clients.
+    pub pruning_budget: u32,
+}
+
+/// Reads the rest of `input` as a json encoded value, as stored by the json backed storage wrappers.
+/// Storage written by other versions of the pallet, or corrupted, fails to decode rather than panicking.
+#[allow(deprecated)]
+fn decode_json<I: Input, T: DeserializeOwned>(input: &mut I) -> Result<T, Error> {
+    let len = input
+        .remaining_len()?
+        .ok_or("length of json encoded value is unknown")?;
+    let mut vec: Vec<u8> = Vec::new();
+    vec.resize(len, 0);
+    input.read(&mut vec)?;
+    serde_json::from_slice(&vec).map_err(|_| "invalid json encoded value".into())
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
@@ -83,18 +220,35 @@ impl Encode for TMClientStorageWrapper {
     }
 }
 
-#[allow(deprecated)]
 impl Decode for TMClientStorageWrapper {
     fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
-        let len = input.remaining_len().unwrap().ok_or_else(|| "meh")?;
-        let mut vec: Vec<u8> = Vec::with_capacity(len);
-        vec.resize_with(len, Default::default);
-        let buf = vec.as_mut_slice();
-        input.read(buf)?;
         Ok(TMClientStorageWrapper {
-            client: serde_json::from_slice(&vec[..]).ok().unwrap(),
+            client: decode_json(input)?,
         })
     }
 }
 
 impl EncodeLike for TMClientStorageWrapper {}
+
+/// Consensus state stored in the per-height historical index.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConsensusStateWrapper {
+    pub state: ConsensusState,
+}
+
+impl Encode for ConsensusStateWrapper {
+    fn encode_to<W: Output>(&self, dest: &mut W) {
+        let json: Vec<u8> = serde_json::to_vec(&self.state).ok().unwrap();
+        dest.write(&json[..]);
+    }
+}
+
+impl Decode for ConsensusStateWrapper {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+        Ok(ConsensusStateWrapper {
+            state: decode_json(input)?,
+        })
+    }
+}
+
+impl EncodeLike for ConsensusStateWrapper {}
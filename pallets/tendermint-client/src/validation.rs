@@ -0,0 +1,172 @@
+//! Checks of client creation and update payloads that do not depend on chain storage.
+//!
+//! The pallet runs these checks on every `init_client` and `update_client`, and offline tooling runs
+//! them natively to find out whether a payload would be accepted before submitting it.
+
+use chrono::{DateTime, Utc};
+use sp_std::vec::Vec;
+
+use crate::light_client::{
+    validate_initial_signed_header_and_valset, verify_single, Options, TrustThresholdFraction,
+    TrustedState, ValidatorSet, VerificationError,
+};
+use crate::types::{ConsensusState, Height, TMCreateClientPayload, TMHeader, TendermintClient};
+
+/// Client described by `payload`, along with the header it is to be initialised with.
+pub fn client_from_payload(payload: TMCreateClientPayload) -> (TendermintClient, TMHeader) {
+    let tmclient = TendermintClient {
+        state: None,
+        trusting_period: payload.trusting_period,
+        client_id: payload.client_id,
+        max_clock_drift: payload.max_clock_drift,
+        unbonding_period: payload.unbonding_period,
+        chain_id: Vec::new(),
+        trust_threshold: TrustThresholdFraction::default(),
+        upgrade_path: payload
+            .upgrade_path
+            .into_iter()
+            .map(|p| p.into_bytes())
+            .collect(),
+    };
+    (tmclient, payload.header)
+}
+
+/// Validates the initial `header` of `tmclient` and sets the client's chain id and state from it.
+/// Returns the height of the header.
+pub fn initialise_client(
+    tmclient: &mut TendermintClient,
+    header: TMHeader,
+    now: DateTime<Utc>,
+) -> Result<Height, VerificationError> {
+    let validator_set = header.validator_set;
+    let header = header.signed_header;
+
+    validate_initial_signed_header_and_valset(&header, &validator_set)?;
+
+    tmclient.chain_id = header.header.chain_id.as_bytes().to_vec();
+    let height = Height::from_chain_id(&tmclient.chain_id, header.header.height);
+    tmclient.state = Some(ConsensusState {
+        state: TrustedState::initial(header, validator_set),
+        last_update: now,
+    });
+    Ok(height)
+}
+
+/// Verifies `header` against `trusted_state`, the state of `tmclient` at `latest_height`.
+/// Returns the height of the header and the state the client moves to.
+///
+/// The chain id, height and time are checked ahead of the light client, so that updates are only
+/// ever applied to the client's own chain and move it forward.
+pub fn verify_update(
+    tmclient: &TendermintClient,
+    trusted_state: &TrustedState,
+    latest_height: Height,
+    header: &TMHeader,
+    next_validator_set: Option<&ValidatorSet>,
+    trusted_validators: Option<&ValidatorSet>,
+    now: DateTime<Utc>,
+) -> Result<(Height, TrustedState), VerificationError> {
+    let signed_header = &header.signed_header;
+    let height = Height::from_chain_id(&tmclient.chain_id, signed_header.header.height);
+    if signed_header.header.chain_id.as_bytes() != &tmclient.chain_id[..] {
+        return Err(VerificationError::ChainIdMismatch);
+    }
+    if height <= latest_height {
+        return Err(VerificationError::NonIncreasingHeight);
+    }
+    if signed_header.header.time <= trusted_state.header().time {
+        return Err(VerificationError::NonIncreasingTime);
+    }
+
+    let trusted_state = verify_single(
+        trusted_state,
+        signed_header,
+        &header.validator_set,
+        next_validator_set,
+        trusted_validators,
+        Options {
+            trust_threshold: tmclient.trust_threshold,
+            trusting_period: tmclient.trusting_period,
+            max_clock_drift: tmclient.max_clock_drift,
+            now,
+        },
+    )?;
+    Ok((height, trusted_state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TMUpdateClientPayload;
+    use chrono::Duration;
+
+    fn created_client() -> (TendermintClient, Height) {
+        let payload: TMCreateClientPayload =
+            serde_json::from_str(include_str!("../../../test_create_v034.json")).unwrap();
+        let (mut client, header) = client_from_payload(payload);
+        let now = header.signed_header.header.time;
+        let height = initialise_client(&mut client, header, now).unwrap();
+        (client, height)
+    }
+
+    fn update_payload() -> TMUpdateClientPayload {
+        serde_json::from_str(include_str!("../../../test_update_v034.json")).unwrap()
+    }
+
+    #[test]
+    fn initialises_client_from_payload() {
+        let (client, height) = created_client();
+        assert_eq!(client.chain_id, b"testchain-2".to_vec());
+        assert_eq!(height, Height::new(2, 100));
+        assert_eq!(client.state.unwrap().state.header().height, 100);
+    }
+
+    #[test]
+    fn verifies_update_against_client_state() {
+        let (client, latest_height) = created_client();
+        let trusted_state = client.state.clone().unwrap().state;
+        let update = update_payload();
+        let now = update.header.signed_header.header.time + Duration::seconds(1);
+
+        let (height, state) = verify_update(
+            &client,
+            &trusted_state,
+            latest_height,
+            &update.header,
+            Some(&update.next_validator_set),
+            None,
+            now,
+        )
+        .unwrap();
+        assert_eq!(height, Height::new(2, 150));
+        assert_eq!(state.header().height, 150);
+
+        assert_eq!(
+            verify_update(
+                &client,
+                &trusted_state,
+                height,
+                &update.header,
+                Some(&update.next_validator_set),
+                None,
+                now,
+            ),
+            Err(VerificationError::NonIncreasingHeight)
+        );
+
+        let mut other_chain = client.clone();
+        other_chain.chain_id = b"testchain-3".to_vec();
+        assert_eq!(
+            verify_update(
+                &other_chain,
+                &trusted_state,
+                latest_height,
+                &update.header,
+                Some(&update.next_validator_set),
+                None,
+                now,
+            ),
+            Err(VerificationError::ChainIdMismatch)
+        );
+    }
+}
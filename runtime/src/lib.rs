@@ -31,7 +31,7 @@ pub use pallet_balances::Call as BalancesCall;
 pub use sp_runtime::{Permill, Perbill};
 pub use frame_support::{
 	construct_runtime, parameter_types, StorageValue,
-	traits::{KeyOwnerProofSystem, Randomness},
+	traits::{Get, KeyOwnerProofSystem, Randomness},
 	weights::{
 		Weight, IdentityFee,
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
@@ -95,7 +95,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("wormhole"),
 	impl_name: create_runtime_str!("wormhole"),
 	authoring_version: 1,
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -261,9 +261,45 @@ impl pallet_sudo::Trait for Runtime {
 	type Call = Call;
 }
 
+parameter_types! {
+	pub const MaxConsensusStates: u32 = 1_000;
+	pub const PruningBudget: u32 = 50;
+	pub const SelfChainId: &'static [u8] = b"wormhole";
+	pub const SelfHistory: u32 = 1_000;
+	pub const MaxExpectedTimePerBlock: u64 = 2 * MILLISECS_PER_BLOCK;
+}
+
+/// Id of the GRANDPA authority set currently finalizing the chain.
+pub struct GrandpaAuthoritySetId;
+
+impl Get<u64> for GrandpaAuthoritySetId {
+	fn get() -> u64 {
+		Grandpa::current_set_id()
+	}
+}
+
 /// Configure the template pallet in pallets/template.
 impl tendermint_client::Trait for Runtime {
 	type Event = Event;
+	type MaxConsensusStates = MaxConsensusStates;
+	type PruningBudget = PruningBudget;
+	type Modules = (IbcTransfer,);
+	type SelfChainId = SelfChainId;
+	type AuthoritySetId = GrandpaAuthoritySetId;
+	type SelfHistory = SelfHistory;
+	type MaxExpectedTimePerBlock = MaxExpectedTimePerBlock;
+	type WeightInfo = tendermint_client::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	pub const NativeDenom: &'static [u8] = b"worm";
+}
+
+impl ibc_transfer::Trait for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type NativeDenom = NativeDenom;
+	type WeightInfo = ibc_transfer::weights::SubstrateWeight<Runtime>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -282,7 +318,8 @@ construct_runtime!(
 		TransactionPayment: pallet_transaction_payment::{Module, Storage},
 		Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
 		// Include the custom logic from the template pallet in the runtime.
-		TendermintClientModule: tendermint_client::{Module, Call, Storage, Event<T>},
+		TendermintClientModule: tendermint_client::{Module, Call, Storage, Event<T>, Config},
+		IbcTransfer: ibc_transfer::{Module, Call, Storage, Event<T>},
 	}
 );
 
@@ -445,6 +482,38 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl tendermint_client_runtime_api::TendermintClientApi<Block> for Runtime {
+		fn canonical_client(chain_id: Vec<u8>) -> Option<Vec<u8>> {
+			TendermintClientModule::canonical_client(chain_id)
+		}
+
+		fn latest_height(client_id: Vec<u8>) -> Option<tendermint_client::types::Height> {
+			TendermintClientModule::latest_height(&client_id)
+		}
+
+		fn retention_policy(client_id: Vec<u8>) -> Option<tendermint_client::types::RetentionPolicy> {
+			TendermintClientModule::retention_policy(&client_id)
+		}
+
+		fn host_commitment(path: Vec<u8>) -> Option<Vec<u8>> {
+			TendermintClientModule::host_commitment(&path)
+		}
+
+		fn self_consensus_state(
+			height: tendermint_client::types::Height,
+		) -> Option<tendermint_client::self_client::SelfConsensusState> {
+			TendermintClientModule::recorded_self_consensus_state(height)
+		}
+
+		fn validate_self_client(
+			client_state: tendermint_client::self_client::SelfClientState,
+			consensus_state: tendermint_client::self_client::SelfConsensusState,
+			consensus_height: tendermint_client::types::Height,
+		) -> Result<(), tendermint_client::self_client::SelfClientError> {
+			TendermintClientModule::validate_self_client(&client_state, &consensus_state, consensus_height)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(
@@ -479,6 +548,8 @@ impl_runtime_apis! {
 			add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
 			add_benchmark!(params, batches, pallet_balances, Balances);
 			add_benchmark!(params, batches, pallet_timestamp, Timestamp);
+			add_benchmark!(params, batches, tendermint_client, TendermintClientModule);
+			add_benchmark!(params, batches, ibc_transfer, IbcTransfer);
 
 			if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
 			Ok(batches)